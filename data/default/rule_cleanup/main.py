@@ -23,7 +23,7 @@ except ModuleNotFoundError as exc:
 
 RULES_DIR     = Path(os.path.expanduser("../../data/rules"))
 URL_KEY_RE    = re.compile(r"url$", re.IGNORECASE)          # keys literally named “url”
-TEMPLATE_RE   = re.compile(r"\{\{.*?\}\}")                  # strip Liquid placeholders
+TEMPLATE_RE   = re.compile(r"\{\{.*?\}\}|\$\{.*?\}")        # strip Liquid and template placeholders
 DOMAIN_RE     = re.compile(r"^(?:[a-z][a-z0-9+\-.]*://)?([^/]+)", re.I)
 
 
@@ -0,0 +1,375 @@
+//! Allowlist files (`--allowlist`): scoped, owned and time-bounded suppressions.
+//!
+//! ```yaml
+//! entries:
+//!   - id: payments-test-fixtures
+//!     rule: kingfisher.stripe.*
+//!     path: "tests/fixtures/**"
+//!     reason: Stripe test-mode keys used by the integration suite
+//!     owner: payments-team@example.com
+//!     expires: 2026-12-31
+//! ```
+//!
+//! An entry suppresses a match when every scope it sets (`rule`, `path`,
+//! `repository`, `secret_hash`) matches. Entries past their `expires` date no
+//! longer suppress anything and are reported instead.
+
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveDate, Utc};
+use dashmap::DashMap;
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::origin::get_repo_url;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawAllowlist {
+    #[serde(default)]
+    entries: Vec<RawEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawEntry {
+    id: Option<String>,
+    rule: Option<String>,
+    path: Option<String>,
+    repository: Option<String>,
+    secret_hash: Option<String>,
+    reason: String,
+    owner: String,
+    expires: String,
+}
+
+struct Entry {
+    id: String,
+    rule: Option<GlobMatcher>,
+    path: Option<GlobMatcher>,
+    repository: Option<GlobMatcher>,
+    secret_hash: Option<String>,
+    reason: String,
+    owner: String,
+    expires: NaiveDate,
+    expired: bool,
+    hits: AtomicU64,
+}
+
+thread_local! {
+    /// Entries counted on this thread while `record_hits` runs.
+    static RECORDED_HITS: RefCell<Option<Vec<usize>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` and returns the indices of the allowlist entries it counted
+/// matches for, so that a cached scan result can count them again with
+/// `Allowlist::count_hits`.
+pub fn record_hits<T>(f: impl FnOnce() -> T) -> (T, Vec<usize>) {
+    RECORDED_HITS.with(|hits| *hits.borrow_mut() = Some(Vec::new()));
+    let out = f();
+    let hits = RECORDED_HITS.with(|hits| hits.borrow_mut().take()).unwrap_or_default();
+    (out, hits)
+}
+
+/// Where a match was found, as far as allowlist scopes are concerned.
+#[derive(Clone, Copy, Debug)]
+pub struct AllowlistContext<'a> {
+    pub rule_id: &'a str,
+    pub path: Option<&'a Path>,
+    pub repository: Option<&'a Path>,
+}
+
+/// Per-entry outcome shown in the scan summary.
+#[derive(Debug, Clone, Serialize)]
+pub struct AllowlistEntrySummary {
+    pub id: String,
+    pub owner: String,
+    pub reason: String,
+    pub expires: String,
+    pub expired: bool,
+    /// Matches suppressed by the entry, or for an expired entry, the matches
+    /// it would have suppressed
+    pub matches: u64,
+}
+
+/// A loaded allowlist file.
+pub struct Allowlist {
+    entries: Vec<Entry>,
+    /// Remote URL (or `None`) of each repository seen, for `repository` scopes.
+    repo_urls: DashMap<PathBuf, Option<String>>,
+}
+
+impl Allowlist {
+    /// Loads an allowlist file, judging expiry against today's date (UTC).
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read allowlist {}", path.display()))?;
+        Self::from_yaml(&contents, Utc::now().date_naive())
+            .with_context(|| format!("Invalid allowlist {}", path.display()))
+    }
+
+    pub fn from_yaml(contents: &str, today: NaiveDate) -> Result<Self> {
+        let raw: RawAllowlist = serde_yaml::from_str(contents)?;
+        let mut entries = Vec::with_capacity(raw.entries.len());
+        for (i, raw) in raw.entries.into_iter().enumerate() {
+            let id = raw.id.clone().unwrap_or_else(|| format!("#{}", i + 1));
+            let entry = Entry::compile(id.clone(), raw, today)
+                .with_context(|| format!("allowlist entry `{id}`"))?;
+            if entry.expired {
+                warn!(
+                    "allowlist entry expired: `{}` (owner: {}, expired {}); its findings are reported again",
+                    entry.id, entry.owner, entry.expires
+                );
+            }
+            entries.push(entry);
+        }
+        Ok(Self { entries, repo_urls: DashMap::new() })
+    }
+
+    /// Returns `true` when an unexpired entry covers the match. Every entry
+    /// that covers it, expired or not, has its count incremented.
+    pub fn matches(&self, secret: &[u8], ctx: &AllowlistContext<'_>) -> bool {
+        let mut secret_hash = None;
+        let mut suppressed = false;
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if entry.rule.as_ref().is_some_and(|g| !g.is_match(ctx.rule_id)) {
+                continue;
+            }
+            if entry.path.as_ref().is_some_and(|g| !ctx.path.is_some_and(|p| g.is_match(p))) {
+                continue;
+            }
+            if let Some(glob) = &entry.repository {
+                if !ctx.repository.is_some_and(|repo| self.repository_matches(glob, repo)) {
+                    continue;
+                }
+            }
+            if let Some(expected) = &entry.secret_hash {
+                let actual = secret_hash.get_or_insert_with(|| hex::encode(Sha256::digest(secret)));
+                if actual != expected {
+                    continue;
+                }
+            }
+            entry.hits.fetch_add(1, Ordering::Relaxed);
+            RECORDED_HITS.with(|hits| {
+                if let Some(hits) = hits.borrow_mut().as_mut() {
+                    hits.push(idx);
+                }
+            });
+            if !entry.expired {
+                debug!("Safe match: allowlist entry `{}`", entry.id);
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    fn repository_matches(&self, glob: &GlobMatcher, repo: &Path) -> bool {
+        if glob.is_match(repo) {
+            return true;
+        }
+        let url = self
+            .repo_urls
+            .entry(repo.to_path_buf())
+            .or_insert_with(|| get_repo_url(repo).ok().map(|u| normalize_repo_url(&u)))
+            .clone();
+        url.is_some_and(|u| glob.is_match(u))
+    }
+
+    /// Counts matches again for the entries `record_hits` returned, when a
+    /// blob's earlier scan result is reused instead of scanning it.
+    pub fn count_hits(&self, hits: &[usize]) {
+        for entry in hits.iter().filter_map(|&idx| self.entries.get(idx)) {
+            entry.hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether any entry is limited to certain paths.
+    pub fn has_path_scopes(&self) -> bool {
+        self.entries.iter().any(|e| e.path.is_some())
+    }
+
+    /// Whether any entry is limited to certain repositories.
+    pub fn has_repository_scopes(&self) -> bool {
+        self.entries.iter().any(|e| e.repository.is_some())
+    }
+
+    pub fn summary(&self) -> Vec<AllowlistEntrySummary> {
+        self.entries
+            .iter()
+            .map(|e| AllowlistEntrySummary {
+                id: e.id.clone(),
+                owner: e.owner.clone(),
+                reason: e.reason.clone(),
+                expires: e.expires.to_string(),
+                expired: e.expired,
+                matches: e.hits.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+impl Entry {
+    fn compile(id: String, raw: RawEntry, today: NaiveDate) -> Result<Self> {
+        if raw.rule.is_none()
+            && raw.path.is_none()
+            && raw.repository.is_none()
+            && raw.secret_hash.is_none()
+        {
+            bail!("set at least one of `rule`, `path`, `repository` or `secret_hash`");
+        }
+        if raw.reason.trim().is_empty() || raw.owner.trim().is_empty() {
+            bail!("`reason` and `owner` must not be empty");
+        }
+        let expires =
+            NaiveDate::parse_from_str(raw.expires.trim(), "%Y-%m-%d").with_context(|| {
+                format!("`expires` must be a YYYY-MM-DD date, got `{}`", raw.expires)
+            })?;
+        let secret_hash = match raw.secret_hash {
+            Some(h) if h.len() == 64 && h.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                Some(h.to_ascii_lowercase())
+            }
+            Some(h) => bail!("`secret_hash` must be a hex SHA-256 digest, got `{h}`"),
+            None => None,
+        };
+        Ok(Self {
+            id,
+            rule: raw.rule.map(|r| compile_glob(&r, false)).transpose()?,
+            path: raw.path.map(|p| compile_glob(&anchor(&p), true)).transpose()?,
+            repository: raw.repository.map(|r| compile_glob(&r, true)).transpose()?,
+            secret_hash,
+            reason: raw.reason,
+            owner: raw.owner,
+            expires,
+            expired: expires < today,
+            hits: AtomicU64::new(0),
+        })
+    }
+}
+
+/// Path globs match at any depth unless they start with `/` or `**`, as in rule scopes.
+fn anchor(pattern: &str) -> String {
+    if pattern.starts_with('/') || pattern.starts_with("**") {
+        pattern.to_string()
+    } else {
+        format!("**/{pattern}")
+    }
+}
+
+fn compile_glob(pattern: &str, literal_separator: bool) -> Result<GlobMatcher> {
+    let glob: Glob = GlobBuilder::new(pattern)
+        .literal_separator(literal_separator)
+        .build()
+        .with_context(|| format!("Invalid glob `{pattern}`"))?;
+    Ok(glob.compile_matcher())
+}
+
+/// `https://github.com/org/repo.git` becomes `github.com/org/repo`.
+fn normalize_repo_url(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority_end = rest.find('/').unwrap_or(rest.len());
+    let rest = rest[..authority_end].rfind('@').map_or(rest, |at| &rest[at + 1..]);
+    rest.trim_end_matches('/').trim_end_matches(".git").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+    }
+
+    fn ctx<'a>(rule_id: &'a str, path: &'a str) -> AllowlistContext<'a> {
+        AllowlistContext { rule_id, path: Some(Path::new(path)), repository: None }
+    }
+
+    #[test]
+    fn entries_suppress_only_within_their_scope_until_they_expire() -> Result<()> {
+        let secret_hash = hex::encode(Sha256::digest(b"sk_test_123"));
+        let yaml = format!(
+            r#"
+entries:
+  - id: fixtures
+    rule: kingfisher.stripe.*
+    path: "tests/fixtures/**"
+    reason: test keys
+    owner: payments
+    expires: 2026-12-31
+  - id: old-key
+    secret_hash: {secret_hash}
+    reason: rotated
+    owner: security
+    expires: 2026-01-31
+"#
+        );
+        let allowlist = Allowlist::from_yaml(&yaml, today())?;
+
+        assert!(allowlist.matches(b"x", &ctx("kingfisher.stripe.1", "repo/tests/fixtures/a.json")));
+        assert!(!allowlist.matches(b"x", &ctx("kingfisher.stripe.1", "repo/src/a.json")));
+        assert!(!allowlist.matches(b"x", &ctx("kingfisher.aws.1", "repo/tests/fixtures/a.json")));
+        // Expired: counted but no longer suppressing
+        assert!(!allowlist.matches(b"sk_test_123", &ctx("kingfisher.aws.1", "src/a.rs")));
+
+        let summary = allowlist.summary();
+        assert_eq!((summary[0].matches, summary[0].expired), (1, false));
+        assert_eq!((summary[1].matches, summary[1].expired), (1, true));
+        Ok(())
+    }
+
+    #[test]
+    fn recorded_hits_can_be_counted_again() -> Result<()> {
+        let yaml = r#"
+entries:
+  - id: fixtures
+    path: "tests/fixtures/**"
+    reason: test keys
+    owner: payments
+    expires: 2026-12-31
+  - id: stripe
+    rule: kingfisher.stripe.*
+    reason: test keys
+    owner: payments
+    expires: 2026-12-31
+"#;
+        let allowlist = Allowlist::from_yaml(yaml, today())?;
+        let (suppressed, hits) = record_hits(|| {
+            allowlist.matches(b"x", &ctx("kingfisher.stripe.1", "repo/tests/fixtures/a.json"))
+                && !allowlist.matches(b"x", &ctx("kingfisher.aws.1", "repo/src/a.json"))
+        });
+        assert!(suppressed);
+        assert_eq!(hits, vec![0, 1]);
+        // Outside `record_hits` nothing is collected
+        assert!(allowlist.matches(b"x", &ctx("kingfisher.aws.1", "tests/fixtures/b.json")));
+        assert_eq!(record_hits(|| ()).1, Vec::<usize>::new());
+
+        allowlist.count_hits(&hits);
+        let counts: Vec<u64> = allowlist.summary().iter().map(|e| e.matches).collect();
+        assert_eq!(counts, vec![3, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn entries_need_a_scope_and_a_valid_expiry() {
+        let entry = |body: &str| {
+            Allowlist::from_yaml(&format!("entries:\n  - reason: r\n    owner: o\n{body}"), today())
+        };
+        assert!(entry("    expires: 2026-12-31\n").is_err());
+        assert!(entry("    rule: a.b\n    expires: 31/12/2026\n").is_err());
+        assert!(entry("    secret_hash: abc\n    expires: 2026-12-31\n").is_err());
+        assert!(entry("    rule: a.b\n    expires: 2026-12-31\n").is_ok());
+    }
+
+    #[test]
+    fn repository_urls_are_normalized() {
+        assert_eq!(normalize_repo_url("https://github.com/org/repo.git"), "github.com/org/repo");
+        assert_eq!(normalize_repo_url("https://user@gitlab.com/g/r/"), "gitlab.com/g/r");
+    }
+}
@@ -3,9 +3,19 @@ use std::{fs, io::Read, path::Path};
 use anyhow::Result;
 use content_inspector::{inspect, ContentType};
 
-use crate::util::is_safe_path;
+use crate::{blob::Blob, transcode::TextEncoding, util::is_safe_path};
 const MAX_PEEK_SIZE: usize = 1024;
 pub fn is_binary(data: &[u8]) -> bool {
+    // UTF-16 and UTF-32 text is full of zero bytes but is transcoded before scanning
+    looks_binary(data) && TextEncoding::detect(data).is_none()
+}
+
+/// Like [`is_binary`], but reuses the blob's detected text encoding.
+pub fn is_binary_blob(blob: &Blob) -> bool {
+    looks_binary(blob.bytes()) && blob.text_encoding().is_none()
+}
+
+fn looks_binary(data: &[u8]) -> bool {
     // Use content_inspector to determine if the data is binary
     matches!(inspect(&data[..std::cmp::min(data.len(), MAX_PEEK_SIZE)]), ContentType::BINARY)
 }
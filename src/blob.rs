@@ -1,9 +1,10 @@
 use std::{
     convert::TryInto,
-    fs::File,
-    io::{Read, Write},
+    fs::{File, Metadata},
+    io::{Read, Seek, SeekFrom, Write},
     path::Path,
     sync::Arc,
+    time::{Duration, SystemTime},
 };
 
 use anyhow::Result;
@@ -17,10 +18,16 @@ use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use smallvec::SmallVec;
 use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::debug;
 
-use crate::git_commit_metadata::CommitMetadata;
-// const LARGE_FILE_THRESHOLD: u64 = 512 * 1024; // 512 KB
-const LARGE_FILE_THRESHOLD: u64 = 0; // always mmap
+use crate::{git_commit_metadata::CommitMetadata, transcode::TextEncoding};
+/// Regular files larger than this are memory mapped instead of read into memory.
+const LARGE_FILE_THRESHOLD: u64 = 512 * 1024; // 512 KB
+/// Files modified more recently than this may still be written to, so they are
+/// read rather than mapped.
+const MAP_QUIET_PERIOD: Duration = Duration::from_secs(60);
+/// Blobs larger than this get a `BlobId` computed from their first and last 64 KB only.
+const SAMPLED_ID_THRESHOLD: usize = 128 * 1024;
 
 static NEXT_ID: AtomicU64 = AtomicU64::new(1);
 
@@ -29,8 +36,9 @@ pub enum BlobData<'a> {
     /// Small blobs – remains as-is.
     Owned(Vec<u8>),
 
-    /// Large blobs read from disk with `memmap2`.
-    Mapped(memmap2::Mmap),
+    /// Large blobs read from disk with `memmap2`, along with the file, which
+    /// holds a shared advisory lock on Unix for as long as the mapping lives.
+    Mapped(memmap2::Mmap, File),
 
     /// Bytes that already live inside gix’s pack-file mmap;
     /// we only keep a pointer and length.
@@ -41,7 +49,7 @@ impl<'a> AsRef<[u8]> for BlobData<'a> {
     fn as_ref(&self) -> &[u8] {
         match self {
             BlobData::Owned(v) => v,
-            BlobData::Mapped(m) => m,
+            BlobData::Mapped(m, _) => m,
             BlobData::Borrowed(slice) => slice,
         }
     }
@@ -82,25 +90,46 @@ pub struct Blob<'a> {
     id: OnceCell<BlobId>,
     data: BlobData<'a>,
     temp_id: u64,
+    /// `id` was supplied by Git rather than computed by `BlobId::new`
+    git_id: bool,
+    /// UTF-16 or UTF-32 encoding and byte order mark length, detected on first use
+    text_encoding: OnceCell<Option<(TextEncoding, usize)>>,
 }
 
 impl Blob<'_> {
     #[inline]
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let mut file = File::open(&path)?;
-        let file_size = file.metadata()?.len();
+        let metadata = file.metadata()?;
+        let file_size = metadata.len();
         let temp_id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
 
-        if file_size > LARGE_FILE_THRESHOLD {
+        if metadata.is_file() && file_size > LARGE_FILE_THRESHOLD {
             // Large files: one mmap, zero extra copies.
-            let mmap = unsafe { memmap2::Mmap::map(&file)? };
-            Ok(Blob { id: OnceCell::new(), data: BlobData::Mapped(mmap), temp_id })
-        } else {
-            // Small files: reuse the same handle and pre-allocate exact capacity
-            let mut bytes = Vec::with_capacity(file_size as usize);
-            file.read_to_end(&mut bytes)?;
-            Ok(Blob { id: OnceCell::new(), data: BlobData::Owned(bytes), temp_id })
+            match map_file(&file, &metadata) {
+                Some(mmap) => {
+                    let data = BlobData::Mapped(mmap, file);
+                    return Ok(Blob {
+                        id: OnceCell::new(),
+                        data,
+                        temp_id,
+                        git_id: false,
+                        text_encoding: OnceCell::new(),
+                    });
+                }
+                None => debug!("Reading {} instead of mapping it", path.as_ref().display()),
+            }
         }
+        // Small files: reuse the same handle and pre-allocate exact capacity
+        let mut bytes = Vec::with_capacity(file_size as usize);
+        file.read_to_end(&mut bytes)?;
+        Ok(Blob {
+            id: OnceCell::new(),
+            data: BlobData::Owned(bytes),
+            temp_id,
+            git_id: false,
+            text_encoding: OnceCell::new(),
+        })
     }
 
     /// Returns the blob's bytes as a slice.
@@ -121,6 +150,19 @@ impl Blob<'_> {
         self.id.get_or_init(|| BlobId::new(self.bytes()))
     }
 
+    /// Return an ID covering every byte of the blob.
+    ///
+    /// `id()` only samples the head and tail of very large blobs, which is
+    /// enough to de-duplicate within one scan but not to recognise unchanged
+    /// content across scans. Git blobs already carry their exact object ID.
+    pub fn content_id(&self) -> BlobId {
+        if self.git_id || self.len() <= SAMPLED_ID_THRESHOLD {
+            self.id()
+        } else {
+            BlobId::compute_from_bytes(self.bytes())
+        }
+    }
+
     /// Return the temporary identifier assigned on blob creation.
     #[inline]
     pub fn temp_id(&self) -> u64 {
@@ -131,7 +173,13 @@ impl Blob<'_> {
     #[inline]
     pub fn from_bytes(bytes: Vec<u8>) -> Self {
         let temp_id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
-        Blob { id: OnceCell::new(), data: BlobData::Owned(bytes), temp_id }
+        Blob {
+            id: OnceCell::new(),
+            data: BlobData::Owned(bytes),
+            temp_id,
+            git_id: false,
+            text_encoding: OnceCell::new(),
+        }
     }
 
     /// Create a new `Blob` with the given id and data.
@@ -140,7 +188,32 @@ impl Blob<'_> {
         let temp_id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
         let cell = OnceCell::new();
         let _ = cell.set(id);
-        Blob { id: cell, data: BlobData::Owned(bytes), temp_id }
+        Blob {
+            id: cell,
+            data: BlobData::Owned(bytes),
+            temp_id,
+            git_id: true,
+            text_encoding: OnceCell::new(),
+        }
+    }
+
+    /// Create a `Blob` holding `bytes`, a re-encoded copy of this blob's
+    /// content, under this blob's id. The copy is UTF-8.
+    pub fn with_bytes(&self, bytes: Vec<u8>) -> Blob<'static> {
+        let temp_id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        Blob {
+            id: OnceCell::with_value(self.id()),
+            data: BlobData::Owned(bytes),
+            temp_id,
+            git_id: self.git_id,
+            text_encoding: OnceCell::with_value(None),
+        }
+    }
+
+    /// Detect UTF-16 or UTF-32 content once per blob; see [`TextEncoding::detect`].
+    #[inline]
+    pub fn text_encoding(&self) -> Option<(TextEncoding, usize)> {
+        *self.text_encoding.get_or_init(|| TextEncoding::detect(self.bytes()))
     }
 
     #[inline]
@@ -154,6 +227,41 @@ impl Blob<'_> {
     }
 }
 
+/// Maps `file` into memory, or returns `None` when it may be written to while
+/// it is scanned. Such files are read into memory instead.
+///
+/// Reading a page of a mapping past the end of a file that another process
+/// truncated raises `SIGBUS`, so only files that look settled are mapped:
+/// - files modified within [`MAP_QUIET_PERIOD`], such as active logs that log
+///   rotation may truncate, are read;
+/// - on Unix, a shared advisory lock is taken and held with the mapping, so
+///   files whose writer holds an exclusive lock are read and cooperating
+///   writers cannot lock them during the scan;
+/// - the size and modification time must not change while mapping.
+///
+/// A process that truncates a long-untouched file without locking it can still
+/// cause `SIGBUS`; advisory locks cannot rule that out.
+fn map_file(file: &File, before: &Metadata) -> Option<memmap2::Mmap> {
+    let modified = before.modified().ok()?;
+    let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+    if age < MAP_QUIET_PERIOD {
+        return None;
+    }
+    // Windows refuses to truncate a mapped file, and its locks are mandatory
+    #[cfg(unix)]
+    if file.try_lock_shared().is_err() {
+        return None;
+    }
+    // SAFETY: the mapping is read-only. `Mmap::map` is unsafe because the file
+    // may still be changed by other processes; see above for how that is limited.
+    let mmap = unsafe { memmap2::Mmap::map(file) }.ok()?;
+    let after = file.metadata().ok()?;
+    let unchanged = mmap.len() as u64 == before.len()
+        && after.len() == before.len()
+        && after.modified().ok() == Some(modified);
+    unchanged.then_some(mmap)
+}
+
 impl Drop for Blob<'_> {
     fn drop(&mut self) {
         // For owned data, clear the Vec. For memory-mapped data, the mmap will be unmapped
@@ -294,10 +402,10 @@ impl BlobId {
     /// Create a new BlobId computed from the given input.
     #[inline]
     pub fn new(input: &[u8]) -> Self {
-        const CHUNK: usize = 64 * 1024; // 64KB from start and end
+        const CHUNK: usize = SAMPLED_ID_THRESHOLD / 2; // 64KB from start and end
         let mut hasher = Sha1::new();
         write!(&mut hasher, "blob {}\0", input.len()).unwrap();
-        if input.len() <= CHUNK * 2 {
+        if input.len() <= SAMPLED_ID_THRESHOLD {
             hasher.update(input);
         } else {
             hasher.update(&input[..CHUNK]);
@@ -306,6 +414,28 @@ impl BlobId {
         BlobId(hasher.finalize().as_slice().try_into().expect("SHA-1 output size mismatch"))
     }
 
+    /// Computes the same id as [`BlobId::new`] for a file, reading only the
+    /// parts of it that the id samples.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        const CHUNK: usize = SAMPLED_ID_THRESHOLD / 2;
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len <= SAMPLED_ID_THRESHOLD as u64 {
+            let mut input = Vec::with_capacity(len as usize);
+            file.read_to_end(&mut input)?;
+            return Ok(Self::new(&input));
+        }
+        let mut hasher = Sha1::new();
+        write!(&mut hasher, "blob {len}\0").unwrap();
+        let mut buf = vec![0; CHUNK];
+        file.read_exact(&mut buf)?;
+        hasher.update(&buf);
+        file.seek(SeekFrom::End(-(CHUNK as i64)))?;
+        file.read_exact(&mut buf)?;
+        hasher.update(&buf);
+        Ok(BlobId(hasher.finalize().into()))
+    }
+
     #[inline]
     pub fn from_hex(v: &str) -> Result<Self> {
         Ok(BlobId(hex::decode(v)?.as_slice().try_into()?))
@@ -378,6 +508,29 @@ mod test {
         assert_eq!(BlobId::new(&vec![0; 0]).hex(), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
         assert_eq!(BlobId::new(&vec![0; 1024]).hex(), "06d7405020018ddf3cacee90fd4af10487da3d20");
     }
+
+    #[test]
+    fn content_id_covers_middle_of_large_blobs() {
+        let a = vec![0u8; 512 * 1024];
+        let mut b = a.clone();
+        b[256 * 1024] = 1;
+        let (a, b) = (Blob::from_bytes(a), Blob::from_bytes(b));
+        assert_eq!(a.id(), b.id());
+        assert_ne!(a.content_id(), b.content_id());
+        assert_eq!(a.content_id(), BlobId::compute_from_bytes(a.bytes()));
+    }
+
+    #[test]
+    fn re_encoded_copies_keep_the_id_kind() {
+        let file = Blob::from_bytes(vec![0u8; 512 * 1024]);
+        let copy = file.with_bytes(b"text".to_vec());
+        assert_eq!(copy.id(), file.id());
+        assert!(!copy.git_id);
+        assert_eq!(copy.text_encoding(), None);
+
+        let git = Blob::new(BlobId::new(b"text"), b"t\0e\0x\0t\0".to_vec());
+        assert!(git.with_bytes(b"text".to_vec()).git_id);
+    }
 }
 /// Metadata about a blob
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, schemars::JsonSchema)]
@@ -413,3 +566,77 @@ impl BlobMetadata {
         self.mime_essence.as_deref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_files_are_mapped_and_small_files_read() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let small = dir.path().join("small.txt");
+        let large = dir.path().join("large.log");
+        std::fs::write(&small, b"token=abc\n")?;
+        let contents = vec![b'a'; LARGE_FILE_THRESHOLD as usize + 1];
+        std::fs::write(&large, &contents)?;
+        settle(&large)?;
+
+        let blob = Blob::from_file(&small)?;
+        assert!(matches!(blob.data, BlobData::Owned(_)));
+        assert_eq!(blob.bytes(), b"token=abc\n");
+
+        let blob = Blob::from_file(&large)?;
+        assert!(matches!(blob.data, BlobData::Mapped(..)));
+        assert_eq!(blob.bytes(), contents.as_slice());
+        assert_eq!(blob.id(), BlobId::new(&contents));
+        assert_eq!(BlobId::from_file(&large)?, blob.id());
+        Ok(())
+    }
+
+    // Backdate a file past the quiet period so it may be mapped
+    fn settle(path: &Path) -> Result<()> {
+        let past = SystemTime::now() - MAP_QUIET_PERIOD * 2;
+        File::options().write(true).open(path)?.set_modified(past)?;
+        Ok(())
+    }
+
+    #[test]
+    fn files_that_may_be_written_to_are_read() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.log");
+        let contents = vec![b'a'; LARGE_FILE_THRESHOLD as usize + 1];
+        std::fs::write(&path, &contents)?;
+
+        // Just written, like an active log
+        let blob = Blob::from_file(&path)?;
+        assert!(matches!(blob.data, BlobData::Owned(_)));
+        assert_eq!(blob.bytes(), contents.as_slice());
+
+        settle(&path)?;
+        assert!(matches!(Blob::from_file(&path)?.data, BlobData::Mapped(..)));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn mapped_files_hold_a_shared_lock() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, vec![b'a'; LARGE_FILE_THRESHOLD as usize + 1])?;
+        settle(&path)?;
+
+        // Its writer holds an exclusive lock
+        let writer = File::open(&path)?;
+        writer.lock()?;
+        assert!(matches!(Blob::from_file(&path)?.data, BlobData::Owned(_)));
+        writer.unlock()?;
+
+        // A mapped file keeps writers from locking it until the blob is dropped
+        let blob = Blob::from_file(&path)?;
+        assert!(matches!(blob.data, BlobData::Mapped(..)));
+        assert!(writer.try_lock().is_err());
+        drop(blob);
+        assert!(writer.try_lock().is_ok());
+        Ok(())
+    }
+}
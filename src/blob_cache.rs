@@ -0,0 +1,563 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use rustc_hash::FxHashMap;
+use tracing::{debug, warn};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::{
+    blob::{Blob, BlobId, BlobMetadata},
+    cli::commands::scan::ScanArgs,
+    datastore::StoredMatch,
+    matcher::Match,
+    origin::{Origin, OriginSet},
+    rules::rule::Rule,
+    rules_database::RulesDatabase,
+    safe_list,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scanned_blobs (
+    ruleset      TEXT NOT NULL,
+    blob_id      TEXT NOT NULL,
+    mime_essence TEXT,
+    language     TEXT,
+    matches      TEXT,
+    allowlist_hits TEXT,
+    PRIMARY KEY (ruleset, blob_id)
+) WITHOUT ROWID;
+";
+
+const FLUSH_ITEMS: usize = 8192;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What an earlier scan recorded for a blob.
+pub enum CachedScan {
+    /// The blob was scanned and produced no findings.
+    Clean,
+    /// The blob was scanned and produced these findings.
+    Findings(BlobMetadata, Vec<Match>),
+}
+
+/// Which parts of a blob's location, besides its content, can change the
+/// findings it produces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LocationScopes {
+    /// A rule sets `include_paths`, `exclude_paths` or `languages`, or an
+    /// allowlist entry sets `path`
+    pub path: bool,
+    /// An allowlist entry sets `repository`
+    pub repository: bool,
+}
+
+impl LocationScopes {
+    /// The location scopes of a ruleset and the allowlist file in use.
+    pub fn of(rules_db: &RulesDatabase) -> Self {
+        let allowlist = safe_list::user_allowlist();
+        Self {
+            path: !rules_db.rule_scopes.is_empty()
+                || allowlist.as_ref().is_some_and(|a| a.has_path_scopes()),
+            repository: allowlist.is_some_and(|a| a.has_repository_scopes()),
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.path || self.repository
+    }
+}
+
+struct PendingBlob {
+    id: BlobId,
+    mime_essence: Option<String>,
+    language: Option<String>,
+    matches: Option<String>,
+    allowlist_hits: Option<String>,
+}
+
+/// A persistent record of blobs already scanned under a given ruleset.
+///
+/// Entries are keyed by the blob's exact content ID and a hash of the rules
+/// database plus the scan options that influence matching. When rule scopes or
+/// allowlist entries depend on where a blob is, its path and repository are part
+/// of the key as well (see `BlobCache::key`). A blob found in
+/// the cache is not scanned again; its recorded findings are replayed against
+/// its current origin instead, and the allowlist entries that suppressed
+/// matches in it are counted again. Entries written under any other ruleset are
+/// dropped when the cache is opened, since they can never be hit again.
+pub struct BlobCache {
+    conn: Mutex<Connection>,
+    ruleset: String,
+    rules: FxHashMap<String, Arc<Rule>>,
+    location: LocationScopes,
+    /// Blob IDs known to the cache, and whether they had findings or
+    /// allowlisted matches
+    known: FxHashMap<BlobId, bool>,
+    pending: Mutex<Vec<PendingBlob>>,
+    last_flush: Mutex<Instant>,
+    hits: AtomicUsize,
+}
+
+impl BlobCache {
+    pub fn open(
+        path: &Path,
+        rules_db: &RulesDatabase,
+        args: &ScanArgs,
+        location: LocationScopes,
+    ) -> Result<Self> {
+        let ruleset = ruleset_key(&rules_db.fingerprint(), args);
+        let rules = rules_db.rules().iter().map(|r| (r.id().to_string(), Arc::clone(r))).collect();
+        Self::open_with_ruleset(path, ruleset, rules, location)
+    }
+
+    fn open_with_ruleset(
+        path: &Path,
+        ruleset: String,
+        rules: FxHashMap<String, Arc<Rule>>,
+        location: LocationScopes,
+    ) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create blob cache directory {}", parent.display())
+            })?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open blob cache {}", path.display()))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.execute_batch(SCHEMA).context("Failed to initialize blob cache schema")?;
+
+        let stale =
+            conn.execute("DELETE FROM scanned_blobs WHERE ruleset != ?1", params![ruleset])?;
+        if stale > 0 {
+            debug!("Dropped {stale} blob cache entries recorded under a different ruleset");
+        }
+
+        let mut known = FxHashMap::default();
+        {
+            let mut stmt = conn.prepare(
+                "SELECT blob_id, matches IS NOT NULL OR allowlist_hits IS NOT NULL
+                 FROM scanned_blobs WHERE ruleset = ?1",
+            )?;
+            let mut rows = stmt.query(params![ruleset])?;
+            while let Some(row) = rows.next()? {
+                let hex: String = row.get(0)?;
+                let id = BlobId::from_hex(&hex)
+                    .with_context(|| format!("Invalid blob id {hex} in blob cache"))?;
+                known.insert(id, row.get(1)?);
+            }
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            ruleset,
+            rules,
+            location,
+            known,
+            pending: Mutex::new(Vec::new()),
+            last_flush: Mutex::new(Instant::now()),
+            hits: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.known.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.known.is_empty()
+    }
+
+    /// Number of lookups answered from the cache so far.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The key a blob is cached under: its exact content ID (see
+    /// `Blob::content_id`), combined with the parts of its location that
+    /// findings depend on.
+    pub fn key(&self, content_id: &BlobId, origin: &OriginSet) -> BlobId {
+        if !self.location.any() {
+            return *content_id;
+        }
+        let mut key = content_id.as_bytes().to_vec();
+        if self.location.path {
+            if let Some(path) = origin.iter().find_map(|o| o.blob_path()) {
+                key.extend_from_slice(path.to_string_lossy().as_bytes());
+            }
+        }
+        key.push(0);
+        if self.location.repository {
+            let repo = origin.iter().find_map(|o| match o {
+                Origin::GitRepo(e) => Some(e.repo_path.as_path()),
+                _ => None,
+            });
+            if let Some(repo) = repo {
+                key.extend_from_slice(repo.to_string_lossy().as_bytes());
+            }
+        }
+        BlobId::compute_from_bytes(&key)
+    }
+
+    /// Look up a blob scanned by an earlier run.
+    ///
+    /// `key` comes from `BlobCache::key`.
+    pub fn get(&self, key: &BlobId, blob: &Blob) -> Option<CachedScan> {
+        let hit = match self.known.get(key)? {
+            false => Some(CachedScan::Clean),
+            true => match self.load_entry(key, blob) {
+                Ok(found) => found,
+                Err(e) => {
+                    debug!("Ignoring unreadable blob cache entry {}: {e:#}", key.hex());
+                    None
+                }
+            },
+        };
+        if hit.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        hit
+    }
+
+    fn load_entry(&self, key: &BlobId, blob: &Blob) -> Result<Option<CachedScan>> {
+        type Row = (Option<String>, Option<String>, Option<String>, Option<String>);
+        let row: Option<Row> = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT mime_essence, language, matches, allowlist_hits FROM scanned_blobs
+                 WHERE ruleset = ?1 AND blob_id = ?2",
+                params![self.ruleset, key.hex()],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+            )
+            .optional()?;
+        let Some((mime_essence, language, json, allowlist_hits)) = row else {
+            return Ok(None);
+        };
+        let allowlist_hits: Vec<usize> = match allowlist_hits {
+            Some(hits) => serde_json::from_str(&hits)?,
+            None => Vec::new(),
+        };
+        let found = match json {
+            None => CachedScan::Clean,
+            Some(json) => {
+                let stored: Vec<(String, StoredMatch)> = serde_json::from_str(&json)?;
+                let mut matches = Vec::with_capacity(stored.len());
+                for (rule_id, m) in stored {
+                    // The ruleset hash covers every rule, so a missing one means a corrupt entry
+                    let Some(rule) = self.rules.get(&rule_id) else {
+                        return Ok(None);
+                    };
+                    matches.push(m.into_match(Arc::clone(rule)));
+                }
+                let md =
+                    BlobMetadata { id: blob.id(), num_bytes: blob.len(), mime_essence, language };
+                CachedScan::Findings(md, matches)
+            }
+        };
+        // The allowlist file is part of the ruleset hash, so entry indices still line up
+        if let Some(allowlist) = safe_list::user_allowlist() {
+            allowlist.count_hits(&allowlist_hits);
+        }
+        Ok(Some(found))
+    }
+
+    /// Record that a blob was scanned and produced no findings, along with the
+    /// allowlist entries that suppressed matches in it (see
+    /// `allowlist::record_hits`).
+    pub fn record_clean(&self, key: BlobId, allowlist_hits: &[usize]) {
+        self.push(PendingBlob {
+            id: key,
+            mime_essence: None,
+            language: None,
+            matches: None,
+            allowlist_hits: hits_json(allowlist_hits),
+        });
+    }
+
+    /// Record the findings a blob produced, along with the allowlist entries
+    /// that suppressed matches in it.
+    pub fn record_findings<'m>(
+        &self,
+        key: BlobId,
+        md: &BlobMetadata,
+        matches: impl IntoIterator<Item = &'m Match>,
+        allowlist_hits: &[usize],
+    ) {
+        let with_rules: Vec<_> = matches.into_iter().map(|m| (m.rule.id(), m)).collect();
+        match serde_json::to_string(&with_rules) {
+            Ok(json) => self.push(PendingBlob {
+                id: key,
+                mime_essence: md.mime_essence.clone(),
+                language: md.language.clone(),
+                matches: Some(json),
+                allowlist_hits: hits_json(allowlist_hits),
+            }),
+            Err(e) => debug!("Failed to serialize findings for blob cache: {e}"),
+        }
+    }
+
+    fn push(&self, entry: PendingBlob) {
+        let due = {
+            let mut pending = self.pending.lock().unwrap();
+            pending.push(entry);
+            pending.len() >= FLUSH_ITEMS
+        } || self.last_flush.lock().unwrap().elapsed() >= FLUSH_INTERVAL;
+        if due {
+            if let Err(e) = self.flush() {
+                warn!("Failed to write blob cache: {e:#}");
+            }
+        }
+    }
+
+    /// Write all buffered entries to disk.
+    pub fn flush(&self) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        *self.last_flush.lock().unwrap() = Instant::now();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let tx = conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO scanned_blobs
+                 (ruleset, blob_id, mime_essence, language, matches, allowlist_hits)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for entry in &pending {
+                insert.execute(params![
+                    self.ruleset,
+                    entry.id.hex(),
+                    entry.mime_essence,
+                    entry.language,
+                    entry.matches,
+                    entry.allowlist_hits,
+                ])?;
+            }
+        }
+        tx.commit().context("Failed to commit blob cache")?;
+        Ok(())
+    }
+}
+
+fn hits_json(hits: &[usize]) -> Option<String> {
+    (!hits.is_empty()).then(|| serde_json::to_string(hits).ok()).flatten()
+}
+
+/// Combine the rules fingerprint with the scan options that change which
+/// matches are produced or how they are recorded.
+fn ruleset_key(rules_fingerprint: &str, args: &ScanArgs) -> String {
+    let mut hasher = Xxh3::new();
+    hasher.update(rules_fingerprint.as_bytes());
+    let flags = [args.redact, args.no_inline_ignore];
+    hasher.update(&flags.map(u8::from));
+    for list in
+        [&args.extra_ignore_comments, &args.skip_regex, &args.skip_word, &args.disable_safe_rule]
+    {
+        hasher.update(&(list.len() as u64).to_le_bytes());
+        for item in list {
+            hasher.update(item.as_bytes());
+            hasher.update(b"\0");
+        }
+    }
+    for encoding in args.enabled_encodings() {
+        hasher.update(encoding.to_string().as_bytes());
+        hasher.update(b"\0");
+    }
+    for path in &args.safe_list {
+        hasher.update(&std::fs::read(path).unwrap_or_default());
+    }
+    // Allowlist entries expire, so the date matters as well as the file
+    if let Some(path) = &args.allowlist {
+        hasher.update(&std::fs::read(path).unwrap_or_default());
+        hasher.update(chrono::Utc::now().date_naive().to_string().as_bytes());
+    }
+    format!("{:x}", hasher.digest())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        location::{Location, OffsetSpan, SourcePoint, SourceSpan},
+        matcher::{SerializableCapture, SerializableCaptures},
+        rules::rule::{Confidence, RuleSyntax},
+    };
+    use smallvec::smallvec;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn test_rule() -> Arc<Rule> {
+        Arc::new(Rule::new(RuleSyntax {
+            name: "Test Rule".into(),
+            id: "test.rule".into(),
+            pattern: "secret_[a-z]+".into(),
+            min_entropy: 0.0,
+            confidence: Confidence::Medium,
+            visible: true,
+            examples: vec![],
+            negative_examples: vec![],
+            references: vec![],
+            validation: None,
+            depends_on_rule: vec![],
+            ..Default::default()
+        }))
+    }
+
+    fn rules_by_id(rule: &Arc<Rule>) -> FxHashMap<String, Arc<Rule>> {
+        [(rule.id().to_string(), Arc::clone(rule))].into_iter().collect()
+    }
+
+    fn sample_match(rule: &Arc<Rule>, blob_id: BlobId) -> Match {
+        Match {
+            location: Location {
+                offset_span: OffsetSpan { start: 0, end: 12 },
+                source_span: SourceSpan {
+                    start: SourcePoint { line: 1, column: 0 },
+                    end: SourcePoint { line: 1, column: 12 },
+                },
+            },
+            groups: SerializableCaptures {
+                captures: smallvec![SerializableCapture {
+                    name: None,
+                    match_number: 0,
+                    start: 0,
+                    end: 12,
+                    value: "secret_alpha",
+                }],
+            },
+            blob_id,
+            finding_fingerprint: 42,
+            rule: Arc::clone(rule),
+            validation_response_body: String::new(),
+            validation_response_status: 0,
+            validation_success: false,
+            calculated_entropy: 3.0,
+            visible: true,
+            is_base64: false,
+            encoding: None,
+            parts: Vec::new(),
+            paired_dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn replays_recorded_blobs_under_same_ruleset() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("cache.db");
+        let rule = test_rule();
+        let dirty_blob = Blob::from_bytes(b"secret_alpha".to_vec());
+        let clean_blob = Blob::from_bytes(b"nothing here".to_vec());
+        let (dirty, clean) = (dirty_blob.content_id(), clean_blob.content_id());
+        let md = BlobMetadata {
+            id: dirty,
+            num_bytes: 12,
+            mime_essence: Some("text/plain".into()),
+            language: None,
+        };
+
+        let cache = BlobCache::open_with_ruleset(
+            &path,
+            "rs1".into(),
+            rules_by_id(&rule),
+            LocationScopes::default(),
+        )?;
+        assert!(cache.get(&dirty, &dirty_blob).is_none());
+        cache.record_findings(dirty, &md, &[sample_match(&rule, dirty)], &[]);
+        cache.record_clean(clean, &[]);
+        cache.flush()?;
+        drop(cache);
+
+        let cache = BlobCache::open_with_ruleset(
+            &path,
+            "rs1".into(),
+            rules_by_id(&rule),
+            LocationScopes::default(),
+        )?;
+        assert_eq!(cache.len(), 2);
+        assert!(matches!(cache.get(&clean, &clean_blob), Some(CachedScan::Clean)));
+        match cache.get(&dirty, &dirty_blob) {
+            Some(CachedScan::Findings(md, matches)) => {
+                assert_eq!(md.mime_essence.as_deref(), Some("text/plain"));
+                assert_eq!(matches.len(), 1);
+                assert_eq!(matches[0].groups.captures[0].value, "secret_alpha");
+                assert_eq!(matches[0].rule.id(), "test.rule");
+            }
+            _ => panic!("expected cached findings"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn changing_ruleset_invalidates_entries() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("cache.db");
+        let rule = test_rule();
+        let blob = Blob::from_bytes(b"nothing here".to_vec());
+        let id = blob.content_id();
+
+        let cache = BlobCache::open_with_ruleset(
+            &path,
+            "rs1".into(),
+            rules_by_id(&rule),
+            LocationScopes::default(),
+        )?;
+        cache.record_clean(id, &[]);
+        cache.flush()?;
+        drop(cache);
+
+        let cache = BlobCache::open_with_ruleset(
+            &path,
+            "rs2".into(),
+            rules_by_id(&rule),
+            LocationScopes::default(),
+        )?;
+        assert!(cache.is_empty());
+        assert!(cache.get(&id, &blob).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn location_scopes_keep_copies_at_different_paths_apart() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("cache.db");
+        let rule = test_rule();
+        let blob = Blob::from_bytes(b"secret_alpha".to_vec());
+        let id = blob.content_id();
+        let origin = |p: &str| OriginSet::from(Origin::from_file(PathBuf::from(p)));
+        let (in_scope, out_of_scope) = (origin("prod/app.env"), origin("dev/app.env"));
+
+        let unscoped = BlobCache::open_with_ruleset(
+            &path,
+            "rs1".into(),
+            rules_by_id(&rule),
+            LocationScopes::default(),
+        )?;
+        assert_eq!(unscoped.key(&id, &in_scope), unscoped.key(&id, &out_of_scope));
+        drop(unscoped);
+
+        let scopes = LocationScopes { path: true, repository: false };
+        let cache = BlobCache::open_with_ruleset(&path, "rs1".into(), rules_by_id(&rule), scopes)?;
+        // The out-of-scope copy produced nothing; that must not hide the in-scope one
+        cache.record_clean(cache.key(&id, &out_of_scope), &[]);
+        cache.flush()?;
+        drop(cache);
+
+        let cache = BlobCache::open_with_ruleset(&path, "rs1".into(), rules_by_id(&rule), scopes)?;
+        assert!(matches!(
+            cache.get(&cache.key(&id, &out_of_scope), &blob),
+            Some(CachedScan::Clean)
+        ));
+        assert!(cache.get(&cache.key(&id, &in_scope), &blob).is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use rusqlite::params;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{
+    blob::BlobId,
+    datastore::{insert_matches, Datastore},
+    findings_store::{FindingsStore, FindingsStoreMessage},
+    origin::OriginSet,
+};
+
+/// Tables tracking finished work; findings themselves live in the regular datastore tables.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS completed_blobs (
+    key TEXT PRIMARY KEY
+);
+CREATE TABLE IF NOT EXISTS completed_repos (
+    key TEXT PRIMARY KEY
+);
+CREATE TABLE IF NOT EXISTS validations (
+    key    TEXT PRIMARY KEY,
+    result TEXT NOT NULL
+);
+";
+
+/// Pending work is written out once this many items are buffered...
+const FLUSH_ITEMS: usize = 4096;
+/// ...or once this much time has passed since the last write.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Outcome of validating one group of matches, as recorded in a checkpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredValidation {
+    pub success: bool,
+    pub status: u16,
+    pub body: String,
+}
+
+#[derive(Default)]
+struct Pending {
+    findings: Vec<FindingsStoreMessage>,
+    blobs: Vec<String>,
+    repos: Vec<String>,
+    validations: Vec<(String, String)>,
+}
+
+impl Pending {
+    fn len(&self) -> usize {
+        self.findings.len() + self.blobs.len() + self.repos.len() + self.validations.len()
+    }
+}
+
+/// Progress of a scan, persisted so an interrupted run can pick up where it stopped.
+///
+/// A checkpoint is a datastore with three extra tables recording which
+/// repositories, blobs and validation groups are finished. Work completed by
+/// an earlier run is loaded once when the checkpoint is opened; work completed
+/// during this run is buffered and written in batches. A blob is only marked
+/// finished in the same transaction that stores its findings, so a crash can
+/// never leave a blob marked done with its findings missing.
+pub struct Checkpoint {
+    db: Mutex<Datastore>,
+    clone_root: PathBuf,
+    completed_blobs: FxHashSet<String>,
+    completed_repos: FxHashSet<String>,
+    validations: FxHashMap<String, String>,
+    pending: Mutex<Pending>,
+    last_flush: Mutex<Instant>,
+}
+
+impl Checkpoint {
+    /// Open the checkpoint at `path`, creating it if this is the first run.
+    pub fn open(path: &Path, clone_root: PathBuf) -> Result<Self> {
+        let db = Datastore::create(path)?;
+        let conn = db.connection();
+        conn.execute_batch(SCHEMA).context("Failed to initialize checkpoint tables")?;
+
+        let completed_blobs = query_keys(conn, "SELECT key FROM completed_blobs")?;
+        let completed_repos = query_keys(conn, "SELECT key FROM completed_repos")?;
+        let mut validations = FxHashMap::default();
+        let mut stmt = conn.prepare("SELECT key, result FROM validations")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            validations.insert(row.get(0)?, row.get(1)?);
+        }
+        drop(rows);
+        drop(stmt);
+
+        Ok(Self {
+            db: Mutex::new(db),
+            clone_root,
+            completed_blobs,
+            completed_repos,
+            validations,
+            pending: Mutex::new(Pending::default()),
+            last_flush: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Merge the findings recorded by earlier runs into `store`.
+    pub fn load_into(&self, store: &mut FindingsStore) -> Result<()> {
+        self.db.lock().unwrap().load_into(store)
+    }
+
+    pub fn num_completed_blobs(&self) -> usize {
+        self.completed_blobs.len()
+    }
+
+    pub fn num_completed_repos(&self) -> usize {
+        self.completed_repos.len()
+    }
+
+    /// Key identifying a repository across runs.
+    ///
+    /// Clones live under a per-run directory, so paths below the clone root are
+    /// keyed relative to it; everything else is keyed by its own path.
+    pub fn repo_key(&self, path: &Path) -> String {
+        path.strip_prefix(&self.clone_root).unwrap_or(path).to_string_lossy().into_owned()
+    }
+
+    pub fn is_repo_complete(&self, path: &Path) -> bool {
+        self.completed_repos.contains(&self.repo_key(path))
+    }
+
+    pub fn record_repo(&self, path: &Path) {
+        let key = self.repo_key(path);
+        self.pending.lock().unwrap().repos.push(key);
+        self.maybe_flush();
+    }
+
+    /// Key identifying a blob across runs: its ID, plus its location when
+    /// `by_location` is set, because `--no-dedup` scans every copy separately
+    /// or because rule scopes or allowlist entries make findings depend on it.
+    pub fn blob_key(&self, id: &BlobId, origin: &OriginSet, by_location: bool) -> String {
+        match origin.first().full_path().filter(|_| by_location) {
+            Some(path) => format!("{}|{}", id.hex(), self.repo_key(&path)),
+            None => id.hex(),
+        }
+    }
+
+    pub fn is_blob_complete(&self, key: &str) -> bool {
+        self.completed_blobs.contains(key)
+    }
+
+    /// Mark a blob as scanned, together with the findings it produced.
+    pub fn record_blob(&self, key: String, findings: Vec<FindingsStoreMessage>) {
+        {
+            let mut pending = self.pending.lock().unwrap();
+            pending.blobs.push(key);
+            pending.findings.extend(findings);
+        }
+        self.maybe_flush();
+    }
+
+    /// Look up the result an earlier run recorded for a validation group.
+    pub fn validation<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let json = self.validations.get(key)?;
+        match serde_json::from_str(json) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                debug!("Ignoring unreadable checkpointed validation {key}: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn record_validation<T: Serialize>(&self, key: String, result: &T) {
+        match serde_json::to_string(result) {
+            Ok(json) => self.pending.lock().unwrap().validations.push((key, json)),
+            Err(e) => debug!("Failed to serialize validation result for {key}: {e}"),
+        }
+        self.maybe_flush();
+    }
+
+    fn maybe_flush(&self) {
+        let due = self.pending.lock().unwrap().len() >= FLUSH_ITEMS
+            || self.last_flush.lock().unwrap().elapsed() >= FLUSH_INTERVAL;
+        if due {
+            if let Err(e) = self.flush() {
+                warn!("Failed to write checkpoint: {e:#}");
+            }
+        }
+    }
+
+    /// Write all buffered progress to disk.
+    pub fn flush(&self) -> Result<()> {
+        // Holding the database lock while taking the buffer keeps batches in order.
+        let mut db = self.db.lock().unwrap();
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        *self.last_flush.lock().unwrap() = Instant::now();
+        if pending.len() == 0 {
+            return Ok(());
+        }
+
+        let tx = db.transaction()?;
+        insert_matches(&tx, &pending.findings)?;
+        {
+            let mut insert_blob =
+                tx.prepare("INSERT OR IGNORE INTO completed_blobs (key) VALUES (?1)")?;
+            for key in &pending.blobs {
+                insert_blob.execute([key])?;
+            }
+            let mut insert_repo =
+                tx.prepare("INSERT OR IGNORE INTO completed_repos (key) VALUES (?1)")?;
+            for key in &pending.repos {
+                insert_repo.execute([key])?;
+            }
+            let mut insert_validation =
+                tx.prepare("INSERT OR REPLACE INTO validations (key, result) VALUES (?1, ?2)")?;
+            for (key, json) in &pending.validations {
+                insert_validation.execute(params![key, json])?;
+            }
+        }
+        tx.commit().context("Failed to commit checkpoint")?;
+        Ok(())
+    }
+}
+
+fn query_keys(conn: &rusqlite::Connection, sql: &str) -> Result<FxHashSet<String>> {
+    let mut stmt = conn.prepare(sql)?;
+    let keys = stmt.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?;
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn completed_work_survives_reopen() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("resume.db");
+        let clone_root = dir.path().join("clones");
+
+        let cp = Checkpoint::open(&path, clone_root.clone())?;
+        assert!(!cp.is_blob_complete("abc"));
+        cp.record_blob("abc".into(), Vec::new());
+        cp.record_repo(&clone_root.join("github.com/org/repo"));
+        let result = StoredValidation { success: true, status: 200, body: "ok".into() };
+        cp.record_validation("rule|secret".into(), &result);
+        cp.flush()?;
+        drop(cp);
+
+        // A resumed run uses a fresh clone directory; repo keys must still line up.
+        let other_root = dir.path().join("other");
+        let cp = Checkpoint::open(&path, other_root.clone())?;
+        assert!(cp.is_blob_complete("abc"));
+        assert!(cp.is_repo_complete(&other_root.join("github.com/org/repo")));
+        assert!(!cp.is_repo_complete(&other_root.join("github.com/org/other")));
+        assert_eq!(cp.validation::<StoredValidation>("rule|secret"), Some(result));
+        assert_eq!(cp.num_completed_blobs(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn unflushed_progress_is_not_marked_complete() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("resume.db");
+        let cp = Checkpoint::open(&path, dir.path().to_path_buf())?;
+        cp.record_blob("abc".into(), Vec::new());
+        drop(cp);
+
+        let cp = Checkpoint::open(&path, dir.path().to_path_buf())?;
+        assert!(!cp.is_blob_complete("abc"));
+        Ok(())
+    }
+}
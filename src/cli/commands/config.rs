@@ -0,0 +1,14 @@
+use clap::{Args, Subcommand};
+
+/// `kingfisher config` command and subcommands
+#[derive(Args, Debug, Clone)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Print the scan options that result from merging all configuration files
+    Show,
+}
@@ -362,6 +362,11 @@ pub struct ContentFilteringArgs {
     /// If true, do NOT scan binary files
     #[arg(long = "no-binary", default_value_t = false)]
     pub no_binary: bool,
+
+    /// Instead of skipping files larger than `--max-file-size`, scan them in
+    /// chunks with constant memory. Encoded content is not decoded in them.
+    #[arg(long = "stream-large-files", default_value_t = false)]
+    pub stream_large_files: bool,
 }
 
 impl ContentFilteringArgs {
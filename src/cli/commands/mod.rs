@@ -1,10 +1,13 @@
 pub mod azure;
 pub mod bitbucket;
+pub mod config;
 pub mod gitea;
 pub mod github;
 pub mod gitlab;
 pub mod huggingface;
 pub mod inputs;
 pub mod output;
+pub mod report;
 pub mod rules;
 pub mod scan;
+pub mod validate;
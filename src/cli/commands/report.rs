@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::cli::commands::output::{OutputArgs, ReportOutputFormat};
+
+/// `kingfisher report` command and flags
+#[derive(Args, Debug, Clone)]
+pub struct ReportArgs {
+    /// Datastore written by a previous `kingfisher scan --datastore <PATH>`
+    #[arg(long, value_name = "PATH")]
+    pub datastore: PathBuf,
+
+    /// Only report findings from rules whose ID starts with this prefix (repeatable)
+    #[arg(long = "rule", value_name = "RULE")]
+    pub rule: Vec<String>,
+
+    /// Display only validated findings
+    #[arg(long, default_value_t = false)]
+    pub only_valid: bool,
+
+    /// Display every occurrence of a finding
+    #[arg(long, default_value_t = false)]
+    pub no_dedup: bool,
+
+    #[command(flatten)]
+    pub output_args: OutputArgs<ReportOutputFormat>,
+}
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use clap::{ArgAction, Args, Subcommand, ValueEnum, ValueHint};
 use strum::Display;
+use url::Url;
 
 use crate::cli::commands::output::OutputArgs;
 
@@ -17,12 +18,40 @@ pub struct RuleSpecifierArgs {
     #[arg(long, alias="rules", value_hint=ValueHint::AnyPath)]
     pub rules_path: Vec<PathBuf>,
 
+    /// Load rules from a pinned remote rule pack (tarball or git repository)
+    ///
+    /// Pin a tarball with `https://.../pack.tar.gz#sha256=<HEX>` or sign it for
+    /// `--rules-public-key`; pin a git repository with
+    /// `git+https://.../rules.git#<COMMIT>`. Verified packs are cached and reused
+    /// until `kingfisher rules update`. This option can be repeated.
+    #[arg(long, value_name = "URL")]
+    pub rules_url: Vec<Url>,
+
+    /// Ed25519 public key (hex or base64) that signs remote rule pack tarballs
+    ///
+    /// The detached signature is fetched from `<URL>.sig`. Git rule packs are
+    /// pinned by commit id and cannot be combined with a public key.
+    #[arg(long, value_name = "KEY")]
+    pub rules_public_key: Option<String>,
+
     /// Enable the ruleset with the given ID (e.g. `all`, `default`, or custom)
     ///
     /// Repeating this disables the default set unless `default` is explicitly included.
     #[arg(long, default_values_t=["all".to_string()])]
     pub rule: Vec<String>,
 
+    /// Only enable rules with the given tag (e.g. `cloud`, `ai`, `payment`, `pii`)
+    ///
+    /// This option can be repeated; a rule is enabled if it has any of the tags.
+    #[arg(long, value_name = "TAG")]
+    pub rule_tag: Vec<String>,
+
+    /// Disable rules with the given tag
+    ///
+    /// This option can be repeated and takes precedence over `--rule-tag`.
+    #[arg(long, value_name = "TAG")]
+    pub exclude_rule_tag: Vec<String>,
+
     /// Load built-in rules
     #[arg(long, default_value_t=true, action=ArgAction::Set)]
     pub load_builtins: bool,
@@ -41,6 +70,29 @@ pub enum RulesCommand {
 
     /// List available rules
     List(RulesListArgs),
+
+    /// Scan a labelled corpus and report per-rule precision and recall
+    Test(RulesTestArgs),
+
+    /// Generate a rule skeleton from a sample token
+    New(RulesNewArgs),
+
+    /// Rank rules by compile cost and scan time, flagging slow patterns
+    Lint(RulesLintArgs),
+
+    /// Fetch remote rule packs again and refresh the local cache
+    Update(RulesUpdateArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RulesUpdateArgs {
+    /// Rule pack to refresh (defaults to every cached pack); can be repeated
+    #[arg(long, value_name = "URL")]
+    pub rules_url: Vec<Url>,
+
+    /// Ed25519 public key (hex or base64) that signs remote rule pack tarballs
+    #[arg(long, value_name = "KEY")]
+    pub rules_public_key: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -51,6 +103,79 @@ pub struct RulesCheckArgs {
 
     #[command(flatten)]
     pub rules: RuleSpecifierArgs,
+
+    #[command(flatten)]
+    pub output_args: OutputArgs<RulesCheckOutputFormat>,
+}
+
+#[derive(Args, Debug)]
+pub struct RulesTestArgs {
+    /// Directory of fixture files to scan
+    #[arg(value_name = "CORPUS", value_hint = ValueHint::DirPath)]
+    pub corpus: PathBuf,
+
+    /// YAML file labelling the expected findings (defaults to `expectations.yml` in the corpus)
+    #[arg(long, short = 'e', value_hint = ValueHint::FilePath)]
+    pub expectations: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub rules: RuleSpecifierArgs,
+
+    #[command(flatten)]
+    pub output_args: OutputArgs<RulesTestOutputFormat>,
+}
+
+#[derive(Args, Debug)]
+pub struct RulesNewArgs {
+    /// Human-readable name of the rule (e.g. "Acme API Key")
+    #[arg(long)]
+    pub name: String,
+
+    /// A token the rule should detect; its shape and entropy drive the pattern
+    #[arg(long)]
+    pub sample: String,
+
+    /// Rule ID (defaults to `kingfisher.<provider>.1`, using the first word of the name)
+    #[arg(long)]
+    pub id: Option<String>,
+
+    /// Keyword required near the token (defaults to the first word of the name when the
+    /// sample has no distinctive prefix)
+    #[arg(long)]
+    pub keyword: Option<String>,
+
+    /// Endpoint that accepts the token as a Bearer credential; adds an HTTP validation block
+    #[arg(long, value_name = "URL")]
+    pub validate_url: Option<Url>,
+
+    /// Write the rule to the specified path (stdout if not given)
+    #[arg(long, short, value_hint = ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct RulesLintArgs {
+    /// Flag rules whose Vectorscan database takes longer than this to compile
+    #[arg(long, value_name = "MS", default_value_t = 250)]
+    pub compile_budget_ms: u64,
+
+    /// Flag rules that take longer than this to scan the synthetic haystack
+    #[arg(long, value_name = "MS", default_value_t = 100)]
+    pub scan_budget_ms: u64,
+
+    /// Flag rules whose compiled Vectorscan database is larger than this
+    #[arg(long, value_name = "KB", default_value_t = 512)]
+    pub db_size_budget_kb: u64,
+
+    /// Treat warnings as errors
+    #[arg(long, short = 'W')]
+    pub warnings_as_errors: bool,
+
+    #[command(flatten)]
+    pub rules: RuleSpecifierArgs,
+
+    #[command(flatten)]
+    pub output_args: OutputArgs<RulesLintOutputFormat>,
 }
 
 #[derive(Args, Debug)]
@@ -73,3 +198,39 @@ pub enum RulesListOutputFormat {
     /// Pretty-printed JSON
     Json,
 }
+
+// -----------------------------------------------------------------------------
+// Rules Check Output Format
+// -----------------------------------------------------------------------------
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RulesCheckOutputFormat {
+    /// A human-friendly text-based format
+    Pretty,
+    /// Pretty-printed JSON
+    Json,
+}
+
+// -----------------------------------------------------------------------------
+// Rules Test Output Format
+// -----------------------------------------------------------------------------
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RulesTestOutputFormat {
+    /// A human-friendly text-based format
+    Pretty,
+    /// Pretty-printed JSON
+    Json,
+}
+
+// -----------------------------------------------------------------------------
+// Rules Lint Output Format
+// -----------------------------------------------------------------------------
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RulesLintOutputFormat {
+    /// A human-friendly text-based format
+    Pretty,
+    /// Pretty-printed JSON
+    Json,
+}
@@ -12,6 +12,7 @@ use crate::{
         },
         global::RAM_GB,
     },
+    decoder::{enabled_encodings, Encoding},
     rules::rule::Confidence,
 };
 
@@ -77,6 +78,11 @@ pub struct ScanArgs {
     #[arg(long, default_value_t = false)]
     pub only_valid: bool,
 
+    /// Resolve `depends_on_rule` values from other files in the same repository
+    /// or scan when the dependency did not match in the same file
+    #[arg(long, default_value_t = false)]
+    pub cross_file_dependencies: bool,
+
     /// Override the default minimum entropy threshold
     #[arg(long, short = 'e')]
     pub min_entropy: Option<f32>,
@@ -93,10 +99,18 @@ pub struct ScanArgs {
     #[arg(long, short = 'r', default_value_t = false)]
     pub redact: bool,
 
-    /// Skip decoding Base64 blobs before scanning
-    #[arg(long, default_value_t = false)]
+    /// Hidden alias for `--no-decode base64`
+    #[arg(long, default_value_t = false, hide = true)]
     pub no_base64: bool,
 
+    /// Also decode and scan these encodings, in addition to Base64 (repeatable)
+    #[arg(long, value_name = "ENCODING", value_delimiter = ',')]
+    pub decode: Vec<Encoding>,
+
+    /// Turn off decoding of these encodings, including Base64 (repeatable)
+    #[arg(long, value_name = "ENCODING", value_delimiter = ',')]
+    pub no_decode: Vec<Encoding>,
+
     /// Timeout for Git repository scanning in seconds
     #[arg(long, default_value_t = 1800, value_name = "SECONDS")]
     pub git_repo_timeout: u64,
@@ -104,6 +118,20 @@ pub struct ScanArgs {
     #[command(flatten)]
     pub output_args: OutputArgs<ReportOutputFormat>,
 
+    /// Persist findings, rules and blob metadata to a SQLite datastore at this path
+    #[arg(long, value_name = "PATH")]
+    pub datastore: Option<PathBuf>,
+
+    /// Checkpoint progress to this file and, if it already exists, skip the repos,
+    /// blobs and validations an earlier interrupted run finished
+    #[arg(long, value_name = "CHECKPOINT")]
+    pub resume: Option<PathBuf>,
+
+    /// Remember blobs scanned under the current rules in this cache and skip
+    /// them on later scans, replaying their recorded findings instead
+    #[arg(long, value_name = "PATH")]
+    pub blob_cache: Option<PathBuf>,
+
     /// Baseline file to filter known secrets
     #[arg(long, value_name = "FILE")]
     pub baseline_file: Option<std::path::PathBuf>,
@@ -120,6 +148,25 @@ pub struct ScanArgs {
     #[arg(long = "skip-word", value_name = "WORD")]
     pub skip_word: Vec<String>,
 
+    /// Allowlist file of suppressions scoped by rule, path, repository or
+    /// secret hash, each with a reason, owner and expiry date
+    #[arg(long, value_name = "FILE")]
+    pub allowlist: Option<PathBuf>,
+
+    /// YAML file of extra safe-list rules, and built-in ones to disable, for
+    /// benign placeholder values (repeatable)
+    #[arg(long = "safe-list", value_name = "FILE")]
+    pub safe_list: Vec<PathBuf>,
+
+    /// Disable a built-in safe-list rule by id (repeatable)
+    #[arg(long = "disable-safe-rule", value_name = "ID")]
+    pub disable_safe_rule: Vec<String>,
+
+    /// List matches dropped by safe-list rules, with the reason, under
+    /// `suppressed` in the JSON scan summary. Disables `--blob-cache`
+    #[arg(long, default_value_t = false)]
+    pub report_suppressed: bool,
+
     /// AWS account IDs whose findings should skip live credential validation (repeatable)
     #[arg(long = "skip-aws-account", value_name = "ACCOUNT_ID", value_delimiter = ',')]
     pub skip_aws_account: Vec<String>,
@@ -137,6 +184,18 @@ pub struct ScanArgs {
     pub no_inline_ignore: bool,
 }
 
+impl ScanArgs {
+    /// The decoders this scan runs, with `--no-base64` expanded to
+    /// `--no-decode base64`.
+    pub fn enabled_encodings(&self) -> Vec<Encoding> {
+        let mut no_decode = self.no_decode.clone();
+        if self.no_base64 {
+            no_decode.push(Encoding::Base64);
+        }
+        enabled_encodings(&self.decode, &no_decode)
+    }
+}
+
 /// Confidence levels for findings
 #[derive(Copy, Clone, Debug, Display, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 #[strum(serialize_all = "kebab-case")]
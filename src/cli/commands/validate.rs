@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum, ValueHint};
+use strum::Display;
+
+use crate::cli::commands::rules::RuleSpecifierArgs;
+
+/// `kingfisher validate` command and flags
+#[derive(Args, Debug, Clone)]
+pub struct ValidateArgs {
+    /// JSON or JSONL report written by `kingfisher scan` (`-` reads standard input)
+    #[arg(value_name = "REPORT", value_hint = ValueHint::FilePath)]
+    pub report: PathBuf,
+
+    #[command(flatten)]
+    pub rules: RuleSpecifierArgs,
+
+    /// Number of concurrent validation requests
+    #[arg(long = "jobs", short = 'j', default_value_t = num_cpus::get())]
+    pub num_jobs: usize,
+
+    /// Output only findings whose validation status changed
+    #[arg(long, default_value_t = false)]
+    pub only_changed: bool,
+
+    /// Write output to the specified path (stdout if not given)
+    #[arg(long, short, value_hint = ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+
+    /// Output format (defaults to the format of the input report)
+    #[arg(long, short)]
+    pub format: Option<ValidateReportFormat>,
+}
+
+/// Report formats that `kingfisher validate` reads and writes
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq, ValueEnum)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ValidateReportFormat {
+    /// Pretty-printed JSON
+    Json,
+
+    /// JSON Lines (one JSON object per line)
+    Jsonl,
+}
+
+/// `kingfisher validate-secret` command and flags
+#[derive(Args, Debug, Clone)]
+pub struct ValidateSecretArgs {
+    /// Rule sources; pass the ID of the rule whose validation to run with
+    /// `--rule` (e.g. `--rule kingfisher.github.1`)
+    #[command(flatten)]
+    pub rules: RuleSpecifierArgs,
+
+    /// Secret to validate; without this, one secret per line is read from stdin
+    #[arg(long, value_name = "SECRET")]
+    pub value: Option<String>,
+
+    /// Value for a variable the rule's validation depends on (repeatable)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var)]
+    pub vars: Vec<(String, String)>,
+
+    /// Output format
+    #[arg(long, short, default_value = "pretty")]
+    pub format: ValidateSecretFormat,
+}
+
+/// Output formats for `kingfisher validate-secret`
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq, ValueEnum)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ValidateSecretFormat {
+    /// A human-friendly text-based format
+    Pretty,
+
+    /// JSON Lines (one JSON object per secret)
+    Jsonl,
+}
+
+fn parse_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_uppercase(), value.to_string()))
+        }
+        _ => Err(format!("expected NAME=VALUE, got `{s}`")),
+    }
+}
@@ -0,0 +1,424 @@
+//! Configuration files for `kingfisher scan`.
+//!
+//! Options can be kept in a `kingfisher.toml` (or `.kingfisher.toml`) at the
+//! root of a repository and in a user-level `~/.config/kingfisher/config.toml`.
+//! Keys under `[scan]` are the long names of `kingfisher scan` flags:
+//!
+//! ```toml
+//! [scan]
+//! rules-path = ["./rules"]
+//! exclude = ["vendor/**", "*.min.js"]
+//! confidence = "low"
+//! format = "sarif"
+//! no-validate = true
+//! ```
+//!
+//! Settings are applied by adding the equivalent flags to the command line, so
+//! every scan option is supported and values are validated exactly as if they
+//! had been typed. The project file overrides the user file key by key, and a
+//! flag given on the command line always wins over both.
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::{
+    builder::ValueParser,
+    parser::{ArgMatches, ValueSource},
+    Arg, ArgAction, CommandFactory,
+};
+
+use crate::cli::global::CommandLineArgs;
+
+/// File names searched for in the working directory and its parents.
+pub const PROJECT_CONFIG_NAMES: &[&str] = &["kingfisher.toml", ".kingfisher.toml"];
+
+/// A parsed configuration file.
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    pub path: PathBuf,
+    /// Options from the `[scan]` table.
+    pub scan: toml::Table,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::parse(path, &text)
+    }
+
+    pub fn parse(path: &Path, text: &str) -> Result<Self> {
+        let mut doc: toml::Table = toml::from_str(text)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        let scan = match doc.remove("scan") {
+            Some(toml::Value::Table(table)) => table,
+            Some(_) => bail!("{}: `scan` must be a table", path.display()),
+            None => toml::Table::new(),
+        };
+        if let Some(key) = doc.keys().next() {
+            bail!("{}: unknown key `{key}`; scan options belong under `[scan]`", path.display());
+        }
+        Ok(Self { path: path.to_path_buf(), scan })
+    }
+
+    /// Relative paths in the file are resolved against its directory.
+    fn base_dir(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new("."))
+    }
+}
+
+/// Location of the user-level configuration file.
+pub fn user_config_path() -> Option<PathBuf> {
+    let env_dir = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let base = env_dir("XDG_CONFIG_HOME");
+    #[cfg(windows)]
+    let base = base.or_else(|| env_dir("APPDATA"));
+    let base = base.or_else(|| env_dir("HOME").map(|home| home.join(".config")))?;
+    Some(base.join("kingfisher").join("config.toml"))
+}
+
+/// Find a project configuration file in `start` or one of its parents.
+///
+/// The search stops at the first directory containing `.git`, so a config file
+/// outside the current repository is never picked up.
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    for dir in start.ancestors() {
+        for name in PROJECT_CONFIG_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+/// Load the configuration files that apply, lowest precedence first.
+///
+/// An explicit `--config` path replaces discovery entirely.
+pub fn discover(explicit: Option<&Path>) -> Result<Vec<ConfigFile>> {
+    if let Some(path) = explicit {
+        return Ok(vec![ConfigFile::load(path)?]);
+    }
+    let mut files = Vec::new();
+    if let Some(path) = user_config_path().filter(|p| p.is_file()) {
+        files.push(ConfigFile::load(&path)?);
+    }
+    let cwd = std::env::current_dir().context("Failed to determine current directory")?;
+    if let Some(path) = find_project_config(&cwd) {
+        files.push(ConfigFile::load(&path)?);
+    }
+    Ok(files)
+}
+
+/// Add the options set by configuration files to a `kingfisher scan` command line.
+///
+/// Any other command line is returned unchanged.
+pub fn apply(argv: Vec<OsString>) -> Result<Vec<OsString>> {
+    let Some(matches) = pre_parse(&argv) else {
+        return Ok(argv);
+    };
+    if matches.subcommand_name() != Some("scan") || matches.get_flag("no_config") {
+        return Ok(argv);
+    }
+    let files = discover(matches.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+    apply_files(argv, &files)
+}
+
+/// Add the options set by `files` to a `kingfisher scan` command line.
+pub fn apply_files(argv: Vec<OsString>, files: &[ConfigFile]) -> Result<Vec<OsString>> {
+    let Some(matches) = pre_parse(&argv) else {
+        return Ok(argv);
+    };
+    let Some(scan_matches) = matches.subcommand_matches("scan") else {
+        return Ok(argv);
+    };
+    let tokens = config_tokens(files, Some(scan_matches))?;
+    if tokens.is_empty() {
+        return Ok(argv);
+    }
+
+    // Options must come before a `--` separator, if there is one.
+    let mut argv = argv;
+    let at = argv.iter().position(|a| a == "--").unwrap_or(argv.len());
+    argv.splice(at..at, tokens);
+    Ok(argv)
+}
+
+/// Parse leniently, so that options a config file may still supply (such as
+/// the inputs to scan) are not reported missing yet.
+fn pre_parse(argv: &[OsString]) -> Option<ArgMatches> {
+    CommandLineArgs::command().ignore_errors(true).try_get_matches_from(argv).ok()
+}
+
+fn scan_command() -> clap::Command {
+    CommandLineArgs::command()
+        .find_subcommand("scan")
+        .cloned()
+        .expect("`scan` subcommand is defined")
+}
+
+/// Look up the scan option a config key refers to, by long name or alias.
+/// Underscores and dashes are interchangeable.
+fn find_arg<'a>(cmd: &'a clap::Command, key: &str) -> Option<&'a Arg> {
+    let key = key.replace('_', "-");
+    cmd.get_arguments().find(|arg| {
+        arg.get_long().is_some()
+            && !matches!(arg.get_action(), ArgAction::Help | ArgAction::Version)
+            && (arg.get_long() == Some(key.as_str())
+                || arg.get_all_aliases().is_some_and(|aliases| aliases.contains(&key.as_str())))
+    })
+}
+
+/// Settings from all files keyed by argument ID; later files override earlier ones.
+fn merge<'a>(
+    cmd: &'a clap::Command,
+    files: &'a [ConfigFile],
+) -> Result<BTreeMap<String, (&'a Arg, &'a toml::Value, &'a ConfigFile)>> {
+    let mut merged = BTreeMap::new();
+    for file in files {
+        for (key, value) in &file.scan {
+            let Some(arg) = find_arg(cmd, key) else {
+                bail!("{}: unknown scan option `{key}`", file.path.display());
+            };
+            merged.insert(arg.get_id().to_string(), (arg, value, file));
+        }
+    }
+    Ok(merged)
+}
+
+/// Command-line tokens equivalent to the merged settings, skipping options
+/// that `cli` already sets on the command line.
+fn config_tokens(files: &[ConfigFile], cli: Option<&ArgMatches>) -> Result<Vec<OsString>> {
+    let cmd = scan_command();
+    let mut tokens = Vec::new();
+    for (id, (arg, value, file)) in merge(&cmd, files)? {
+        if cli.and_then(|m| m.value_source(&id)) == Some(ValueSource::CommandLine) {
+            continue;
+        }
+        push_tokens(arg, value, file, &mut tokens)
+            .with_context(|| format!("{}: invalid value for `{id}`", file.path.display()))?;
+    }
+    Ok(tokens)
+}
+
+fn push_tokens(
+    arg: &Arg,
+    value: &toml::Value,
+    file: &ConfigFile,
+    tokens: &mut Vec<OsString>,
+) -> Result<()> {
+    let flag = format!("--{}", arg.get_long().expect("config options have long names"));
+    match arg.get_action() {
+        ArgAction::SetTrue | ArgAction::SetFalse => match value {
+            toml::Value::Boolean(true) => tokens.push(flag.into()),
+            toml::Value::Boolean(false) => {}
+            _ => bail!("expected `true` or `false`"),
+        },
+        ArgAction::Count => match value.as_integer() {
+            Some(n) if n >= 0 => tokens.extend((0..n).map(|_| OsString::from(&flag))),
+            _ => bail!("expected a non-negative integer"),
+        },
+        _ => {
+            let is_path = arg.get_value_parser().type_id() == ValueParser::path_buf().type_id();
+            let values = match value {
+                toml::Value::Array(items) => items.iter().collect(),
+                other => vec![other],
+            };
+            for value in values {
+                let mut token = OsString::from(format!("{flag}="));
+                match value {
+                    toml::Value::String(s) if is_path => {
+                        token.push(file.base_dir().join(s).into_os_string())
+                    }
+                    toml::Value::String(s) => token.push(s),
+                    toml::Value::Integer(n) => token.push(n.to_string()),
+                    toml::Value::Float(f) => token.push(f.to_string()),
+                    toml::Value::Boolean(b) => token.push(b.to_string()),
+                    _ => bail!("expected a string, number, boolean or array of those"),
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Render the scan options that result from `files` as a TOML document,
+/// noting where each value comes from.
+pub fn render_effective(files: &[ConfigFile]) -> Result<String> {
+    // A placeholder input satisfies `scan`'s required arguments so the
+    // settings are validated just as a real scan would validate them.
+    let mut argv: Vec<OsString> = vec!["kingfisher".into(), "scan".into()];
+    argv.extend(config_tokens(files, None)?);
+    argv.push(".".into());
+    let matches = CommandLineArgs::command()
+        .try_get_matches_from(argv)
+        .context("Configuration is not valid for `kingfisher scan`")?;
+    let scan = matches.subcommand_matches("scan").expect("parsed a scan command");
+
+    let cmd = scan_command();
+    let merged = merge(&cmd, files)?;
+    let mut out = String::new();
+    if files.is_empty() {
+        writeln!(out, "# No configuration files found; showing defaults")?;
+    } else {
+        writeln!(out, "# Configuration files, lowest precedence first:")?;
+        for file in files {
+            writeln!(out, "#   {}", file.path.display())?;
+        }
+    }
+    writeln!(out, "[scan]")?;
+    for arg in cmd.get_arguments() {
+        let Some(long) = arg.get_long() else { continue };
+        if matches!(arg.get_action(), ArgAction::Help | ArgAction::Version) {
+            continue;
+        }
+        let id = arg.get_id().as_str();
+        let Some(raw) = scan.get_raw(id) else { continue };
+        let mut values: Vec<toml::Value> =
+            raw.map(|v| display_value(arg, &v.to_string_lossy())).collect();
+        let value = if values.len() == 1 && !matches!(arg.get_action(), ArgAction::Append) {
+            values.remove(0)
+        } else {
+            toml::Value::Array(values)
+        };
+        let source = match merged.get(id) {
+            Some((_, _, file)) => file.path.display().to_string(),
+            None => "default".to_string(),
+        };
+        writeln!(out, "{long} = {value}  # {source}")?;
+    }
+    Ok(out)
+}
+
+fn display_value(arg: &Arg, raw: &str) -> toml::Value {
+    let numeric =
+        !raw.is_empty() && raw.chars().all(|c| c.is_ascii_digit() || c == '.' || c == '-');
+    match arg.get_action() {
+        ArgAction::SetTrue | ArgAction::SetFalse => toml::Value::Boolean(raw == "true"),
+        _ if raw == "true" || raw == "false" => toml::Value::Boolean(raw == "true"),
+        _ if numeric => raw
+            .parse::<i64>()
+            .map(toml::Value::Integer)
+            .or_else(|_| raw.parse::<f64>().map(toml::Value::Float))
+            .unwrap_or_else(|_| toml::Value::String(raw.to_string())),
+        _ => toml::Value::String(raw.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::cli::{
+        commands::{
+            output::ReportOutputFormat,
+            scan::{ConfidenceLevel, ScanArgs},
+        },
+        global::Command,
+    };
+
+    fn argv(args: &[&str]) -> Vec<OsString> {
+        std::iter::once("kingfisher").chain(args.iter().copied()).map(OsString::from).collect()
+    }
+
+    fn scan_args(args: &[&str], files: &[ConfigFile]) -> Result<ScanArgs> {
+        let argv = apply_files(argv(args), files)?;
+        match CommandLineArgs::try_parse_from(argv)?.command {
+            Command::Scan(scan) => Ok(scan),
+            other => bail!("unexpected command {other:?}"),
+        }
+    }
+
+    #[test]
+    fn project_file_overrides_user_file_and_cli_overrides_both() -> Result<()> {
+        let user = ConfigFile::parse(
+            Path::new("/home/me/.config/kingfisher/config.toml"),
+            "[scan]\nconfidence = \"high\"\nskip_word = [\"example\"]\nformat = \"json\"\n",
+        )?;
+        let project = ConfigFile::parse(
+            Path::new("/repo/kingfisher.toml"),
+            "[scan]\nconfidence = \"low\"\nno-validate = true\nrules-path = \"rules\"\n",
+        )?;
+        let files = [user, project];
+
+        let args = scan_args(&["scan", "."], &files)?;
+        assert_eq!(args.confidence, ConfidenceLevel::Low);
+        assert!(args.no_validate);
+        assert_eq!(args.skip_word, vec!["example".to_string()]);
+        assert_eq!(args.rules.rules_path, vec![PathBuf::from("/repo/rules")]);
+        assert_eq!(args.output_args.format, ReportOutputFormat::Json);
+
+        let args = scan_args(&["scan", ".", "--confidence", "medium", "--format=sarif"], &files)?;
+        assert_eq!(args.confidence, ConfidenceLevel::Medium);
+        assert_eq!(args.output_args.format, ReportOutputFormat::Sarif);
+        Ok(())
+    }
+
+    #[test]
+    fn config_can_supply_scan_inputs() -> Result<()> {
+        let file = ConfigFile::parse(
+            Path::new("/repo/kingfisher.toml"),
+            "[scan]\ngit-url = [\"https://github.com/org/repo.git\"]\n",
+        )?;
+        let args = scan_args(&["scan"], &[file])?;
+        assert_eq!(args.input_specifier_args.git_url.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_and_mistyped_options_are_rejected() -> Result<()> {
+        let file = ConfigFile::parse(Path::new("k.toml"), "[scan]\nno-such-flag = 1\n")?;
+        let err = apply_files(argv(&["scan", "."]), &[file]).unwrap_err();
+        assert!(err.to_string().contains("no-such-flag"));
+
+        let file = ConfigFile::parse(Path::new("k.toml"), "[scan]\nno-validate = \"yes\"\n")?;
+        assert!(apply_files(argv(&["scan", "."]), &[file]).is_err());
+
+        assert!(ConfigFile::parse(Path::new("k.toml"), "confidence = \"low\"\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn project_config_search_stops_at_repository_root() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let repo = dir.path().join("repo");
+        let nested = repo.join("src/module");
+        std::fs::create_dir_all(&nested)?;
+        std::fs::write(dir.path().join("kingfisher.toml"), "")?;
+        assert_eq!(find_project_config(&nested), Some(dir.path().join("kingfisher.toml")));
+
+        std::fs::create_dir(repo.join(".git"))?;
+        assert_eq!(find_project_config(&nested), None);
+
+        std::fs::write(repo.join(".kingfisher.toml"), "")?;
+        assert_eq!(find_project_config(&nested), Some(repo.join(".kingfisher.toml")));
+        Ok(())
+    }
+
+    #[test]
+    fn render_effective_notes_sources() -> Result<()> {
+        let file = ConfigFile::parse(
+            Path::new("/repo/kingfisher.toml"),
+            "[scan]\nconfidence = \"low\"\nexclude = [\"vendor/**\"]\n",
+        )?;
+        let out = render_effective(&[file])?;
+        assert!(out.contains("confidence = \"low\"  # /repo/kingfisher.toml"));
+        assert!(out.contains("exclude = [\"vendor/**\"]  # /repo/kingfisher.toml"));
+        assert!(out.contains("no-validate = false  # default"));
+
+        let bad = ConfigFile::parse(Path::new("k.toml"), "[scan]\nconfidence = \"extreme\"\n")?;
+        assert!(render_effective(&[bad]).is_err());
+        Ok(())
+    }
+}
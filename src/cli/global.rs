@@ -1,14 +1,26 @@
-use std::io::IsTerminal;
+use std::{ffi::OsString, io::IsTerminal, path::PathBuf};
 
-use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use once_cell::sync::Lazy;
 use strum::Display;
 use sysinfo::{MemoryRefreshKind, RefreshKind, System};
 use tracing::Level;
 
-use crate::cli::commands::{
-    azure::AzureArgs, bitbucket::BitbucketArgs, gitea::GiteaArgs, github::GitHubArgs,
-    gitlab::GitLabArgs, huggingface::HuggingFaceArgs, rules::RulesArgs, scan::ScanArgs,
+use crate::cli::{
+    commands::{
+        azure::AzureArgs,
+        bitbucket::BitbucketArgs,
+        config::ConfigArgs,
+        gitea::GiteaArgs,
+        github::GitHubArgs,
+        gitlab::GitLabArgs,
+        huggingface::HuggingFaceArgs,
+        report::ReportArgs,
+        rules::RulesArgs,
+        scan::ScanArgs,
+        validate::{ValidateArgs, ValidateSecretArgs},
+    },
+    config,
 };
 
 #[deny(missing_docs)]
@@ -27,10 +39,16 @@ pub struct CommandLineArgs {
 impl CommandLineArgs {
     /// Parse command-line arguments.
     ///
-    /// Automatically respects `NO_COLOR` and maps `--quiet` into disabling progress bars.
+    /// Fills in scan options from configuration files, automatically respects
+    /// `NO_COLOR` and maps `--quiet` into disabling progress bars.
     pub fn parse_args() -> Self {
-        // Use standard `Parser::parse` for simplicity
-        let mut args = CommandLineArgs::parse();
+        let argv: Vec<OsString> = std::env::args_os().collect();
+        let argv = config::apply(argv).unwrap_or_else(|e| {
+            CommandLineArgs::command()
+                .error(clap::error::ErrorKind::InvalidValue, format!("{e:#}"))
+                .exit()
+        });
+        let mut args = CommandLineArgs::parse_from(argv);
 
         // Apply NO_COLOR environment variable
         if std::env::var("NO_COLOR").is_ok() {
@@ -85,10 +103,23 @@ pub enum Command {
     #[command(name = "huggingface")]
     HuggingFace(HuggingFaceArgs),
 
+    /// Re-render findings from a datastore without rescanning
+    Report(ReportArgs),
+
+    /// Re-validate the findings in a JSON or JSONL report without rescanning
+    Validate(ValidateArgs),
+
+    /// Validate a single secret against a rule's validation config
+    #[command(name = "validate-secret")]
+    ValidateSecret(ValidateSecretArgs),
+
     /// Manage rules
     #[command(alias = "rule")]
     Rules(RulesArgs),
 
+    /// Inspect configuration files
+    Config(ConfigArgs),
+
     /// Update the Kingfisher binary
     #[command(name = "self-update")]
     SelfUpdate,
@@ -133,6 +164,14 @@ pub struct GlobalArgs {
     #[arg(global = true, long = "user-agent-suffix", value_name = "SUFFIX")]
     pub user_agent_suffix: Option<String>,
 
+    /// Read scan options from this file instead of discovering `kingfisher.toml`
+    #[arg(global = true, long, value_name = "FILE", conflicts_with = "no_config")]
+    pub config: Option<PathBuf>,
+
+    /// Ignore configuration files
+    #[arg(global = true, long, default_value_t = false)]
+    pub no_config: bool,
+
     // Internal fields (not CLI arguments)
     #[clap(skip)]
     pub color: Mode,
@@ -150,6 +189,8 @@ impl Default for GlobalArgs {
             self_update: false,
             no_update_check: false,
             user_agent_suffix: None,
+            config: None,
+            no_config: false,
             color: Mode::Auto,
             progress: Mode::Auto,
         }
@@ -1,4 +1,5 @@
 pub mod commands;
+pub mod config;
 pub mod global;
 
 // re‑export the top‑level parser and subcommand enum so main.rs can see them:
@@ -2,6 +2,8 @@ use once_cell::sync::Lazy;
 use std::path::Path;
 use tokei::LanguageType;
 
+use crate::transcode::TextEncoding;
+
 // Precompute all (shebang_prefix_bytes, language) pairs once.
 // Sort longest-first so more specific shebangs win.
 static SHEBANG_PREFIXES: Lazy<Vec<(&'static [u8], LanguageType)>> = Lazy::new(|| {
@@ -92,10 +94,14 @@ impl ContentInspector {
         Some(mime.to_string())
     }
 
-    /// Detect UTF-8 encoding by attempting a lossless conversion.
+    /// Detect UTF-16 and UTF-32 text (see [`TextEncoding::detect`]), or
+    /// UTF-8 by attempting a lossless conversion.
     #[inline]
     #[must_use]
     pub fn guess_charset(&self, bytes: &[u8]) -> Option<String> {
+        if let Some((encoding, _)) = TextEncoding::detect(bytes) {
+            return Some(encoding.to_string());
+        }
         std::str::from_utf8(bytes).ok().map(|_| "UTF-8".to_string())
     }
     /// Guess programming language with broad coverage using `tokei`.
@@ -208,6 +214,8 @@ mod tests {
         let ins = ContentInspector::default();
         assert_eq!(ins.guess_charset("ok".as_bytes()), Some("UTF-8".into()));
         assert_eq!(ins.guess_charset(&[0xFF, 0xFE, 0xFD]), None);
+        let utf16: Vec<u8> = "password=hunter2".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(ins.guess_charset(&utf16), Some("UTF-16LE".into()));
     }
 
     #[test]
@@ -0,0 +1,537 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
+use smallvec::SmallVec;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::{
+    blob::{BlobId, BlobMetadata},
+    decoder::Encoding,
+    findings_store::{FindingsStore, FindingsStoreMessage},
+    location::Location,
+    matcher::{Match, MatchPart, PairedDependency, SerializableCapture, SerializableCaptures},
+    origin::{Origin, OriginSet},
+    rules::rule::{Rule, RuleSyntax},
+    util::intern,
+};
+
+/// Bump whenever the table layout changes in a way older binaries cannot read.
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS metadata (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS rules (
+    id     TEXT PRIMARY KEY,
+    syntax TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS blobs (
+    id           TEXT PRIMARY KEY,
+    num_bytes    INTEGER NOT NULL,
+    mime_essence TEXT,
+    language     TEXT
+);
+CREATE TABLE IF NOT EXISTS origins (
+    id      INTEGER PRIMARY KEY,
+    origins TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS matches (
+    id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id            TEXT NOT NULL REFERENCES rules(id),
+    blob_id            TEXT NOT NULL REFERENCES blobs(id),
+    origin_id          INTEGER NOT NULL REFERENCES origins(id),
+    validation_success INTEGER NOT NULL,
+    body               TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS matches_rule_id ON matches(rule_id);
+CREATE TABLE IF NOT EXISTS links (
+    kind  TEXT NOT NULL,
+    path  TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (kind, path)
+);
+";
+
+/// Link tables kept by `FindingsStore` that the reporter needs to rebuild
+/// display paths (Docker images, S3 buckets, Slack/Confluence permalinks...).
+const LINK_DOCKER: &str = "docker_image";
+const LINK_SLACK: &str = "slack";
+const LINK_CONFLUENCE: &str = "confluence";
+const LINK_S3: &str = "s3_bucket";
+const LINK_REPO: &str = "repo";
+
+/// Metadata key holding the Jira base URL used during the scan.
+pub const META_JIRA_URL: &str = "jira_url";
+/// Metadata key holding the scan's clone directory; downloaded artifacts are keyed by it.
+pub const META_CLONE_DIR: &str = "clone_dir";
+
+/// A durable, SQLite-backed copy of a scan's findings.
+///
+/// The datastore holds everything the reporter needs — rules, blob metadata,
+/// origins, matches and their validation results — so a report can be
+/// re-rendered in any format without rescanning.
+pub struct Datastore {
+    conn: Connection,
+}
+
+impl Datastore {
+    /// Open the datastore at `path`, creating it if it does not exist.
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create datastore directory {}", parent.display())
+            })?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open datastore {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// Open an existing datastore at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            bail!("Datastore {} does not exist", path.display());
+        }
+        Self::create(path)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        let version: i64 = conn.pragma_query_value(None, "user_version", |r| r.get(0))?;
+        if version > SCHEMA_VERSION {
+            bail!(
+                "Datastore schema version {version} is newer than this build supports \
+                 ({SCHEMA_VERSION}); upgrade Kingfisher to read it"
+            );
+        }
+        conn.execute_batch(SCHEMA).context("Failed to initialize datastore schema")?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Self { conn })
+    }
+
+    pub fn set_metadata(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO metadata (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    pub fn metadata(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM metadata WHERE key = ?1", [key], |r| r.get(0))
+            .optional()?)
+    }
+
+    /// Replace the contents of the datastore with the findings held in `store`.
+    ///
+    /// Everything is written inside a single transaction, so a crash part-way
+    /// through leaves the previous contents intact.
+    pub fn save_findings(&mut self, store: &FindingsStore) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute_batch(
+            "DELETE FROM matches; DELETE FROM origins; DELETE FROM blobs;
+             DELETE FROM rules; DELETE FROM links;",
+        )?;
+        {
+            let mut insert_rule =
+                tx.prepare("INSERT OR REPLACE INTO rules (id, syntax) VALUES (?1, ?2)")?;
+            for rule in store.get_rules()? {
+                insert_rule.execute(params![rule.id(), serde_json::to_string(rule.syntax())?])?;
+            }
+            insert_matches(&tx, store.get_matches().iter().map(|msg| &**msg))?;
+
+            let mut insert_link =
+                tx.prepare("INSERT OR REPLACE INTO links (kind, path, value) VALUES (?1, ?2, ?3)")?;
+            for (kind, links) in [
+                (LINK_DOCKER, store.docker_images()),
+                (LINK_SLACK, store.slack_links()),
+                (LINK_CONFLUENCE, store.confluence_links()),
+                (LINK_S3, store.s3_buckets()),
+                (LINK_REPO, store.repo_links()),
+            ] {
+                for (path, value) in links {
+                    insert_link.execute(params![kind, path.to_string_lossy(), value])?;
+                }
+            }
+        }
+        tx.commit().context("Failed to commit findings to datastore")?;
+        Ok(())
+    }
+
+    /// Start a transaction for callers that write their own tables alongside findings.
+    pub(crate) fn transaction(&mut self) -> Result<Transaction<'_>> {
+        Ok(self.conn.transaction()?)
+    }
+
+    pub(crate) fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Rebuild an in-memory `FindingsStore` from the datastore contents.
+    pub fn load_findings(&self, clone_dir: PathBuf) -> Result<FindingsStore> {
+        let mut store = FindingsStore::new(clone_dir);
+        let mut rules = self.stored_rules()?;
+        rules.sort_by(|a, b| a.id().cmp(b.id()));
+        store.record_rules(&rules);
+        self.load_into(&mut store)?;
+        Ok(store)
+    }
+
+    fn stored_rules(&self) -> Result<Vec<Arc<Rule>>> {
+        let mut rules = Vec::new();
+        let mut stmt = self.conn.prepare("SELECT id, syntax FROM rules")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: String = row.get(0)?;
+            let syntax: String = row.get(1)?;
+            let syntax: RuleSyntax = serde_json::from_str(&syntax)
+                .with_context(|| format!("Failed to decode stored rule {id}"))?;
+            rules.push(Arc::new(Rule::new(syntax)));
+        }
+        Ok(rules)
+    }
+
+    /// Merge the stored findings into `store`.
+    ///
+    /// Matches are re-attached to the rules already recorded in `store` when
+    /// their IDs line up, falling back to the rule definitions saved alongside them.
+    pub fn load_into(&self, store: &mut FindingsStore) -> Result<()> {
+        let mut rules: FxHashMap<String, Arc<Rule>> = FxHashMap::default();
+        for rule in self.stored_rules()?.into_iter().chain(store.get_rules()?) {
+            rules.insert(rule.id().to_string(), rule);
+        }
+
+        let mut blobs: FxHashMap<String, Arc<BlobMetadata>> = FxHashMap::default();
+        let mut stmt =
+            self.conn.prepare("SELECT id, num_bytes, mime_essence, language FROM blobs")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let hex: String = row.get(0)?;
+            let num_bytes: i64 = row.get(1)?;
+            let id = BlobId::from_hex(&hex)
+                .with_context(|| format!("Invalid blob id {hex} in datastore"))?;
+            let md = BlobMetadata {
+                id,
+                num_bytes: num_bytes as usize,
+                mime_essence: row.get(2)?,
+                language: row.get(3)?,
+            };
+            blobs.insert(hex, Arc::new(md));
+        }
+
+        let mut origins: FxHashMap<i64, Arc<OriginSet>> = FxHashMap::default();
+        let mut stmt = self.conn.prepare("SELECT id, origins FROM origins")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let json: String = row.get(1)?;
+            let list: Vec<Origin> = serde_json::from_str(&json)
+                .with_context(|| format!("Failed to decode stored origin {id}"))?;
+            let set = OriginSet::try_from_iter(list)
+                .with_context(|| format!("Stored origin {id} is empty"))?;
+            origins.insert(id, Arc::new(set));
+        }
+
+        let mut batch: Vec<FindingsStoreMessage> = Vec::new();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT rule_id, blob_id, origin_id, body FROM matches ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let rule_id: String = row.get(0)?;
+            let blob_id: String = row.get(1)?;
+            let origin_id: i64 = row.get(2)?;
+            let body: String = row.get(3)?;
+            let rule = rules
+                .get(&rule_id)
+                .with_context(|| format!("Stored match refers to unknown rule {rule_id}"))?;
+            let blob = blobs
+                .get(&blob_id)
+                .with_context(|| format!("Stored match refers to unknown blob {blob_id}"))?;
+            let origin = origins
+                .get(&origin_id)
+                .with_context(|| format!("Stored match refers to unknown origin {origin_id}"))?;
+            let stored: StoredMatch =
+                serde_json::from_str(&body).context("Failed to decode stored match")?;
+            batch.push((Arc::clone(origin), Arc::clone(blob), stored.into_match(Arc::clone(rule))));
+        }
+        store.record(batch, false);
+
+        let mut stmt = self.conn.prepare("SELECT kind, path, value FROM links")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let kind: String = row.get(0)?;
+            let path = PathBuf::from(row.get::<_, String>(1)?);
+            let value: String = row.get(2)?;
+            match kind.as_str() {
+                LINK_DOCKER => store.register_docker_image(path, value),
+                LINK_SLACK => store.register_slack_message(path, value),
+                LINK_CONFLUENCE => store.register_confluence_page(path, value),
+                LINK_S3 => store.register_s3_bucket(path, value),
+                LINK_REPO => store.register_repo_link(path, value),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Insert `msgs` along with the rules, blobs and origins they reference.
+///
+/// Origins are keyed by a hash of their serialized form, so appending the same
+/// origin from separate batches reuses a single row.
+pub(crate) fn insert_matches<'a>(
+    tx: &Transaction,
+    msgs: impl IntoIterator<Item = &'a FindingsStoreMessage>,
+) -> Result<()> {
+    let mut insert_rule = tx.prepare("INSERT OR IGNORE INTO rules (id, syntax) VALUES (?1, ?2)")?;
+    let mut insert_blob = tx.prepare(
+        "INSERT OR IGNORE INTO blobs (id, num_bytes, mime_essence, language)
+         VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let mut insert_origin =
+        tx.prepare("INSERT OR IGNORE INTO origins (id, origins) VALUES (?1, ?2)")?;
+    let mut insert_match = tx.prepare(
+        "INSERT INTO matches (rule_id, blob_id, origin_id, validation_success, body)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+
+    let mut rule_ids: FxHashSet<&str> = FxHashSet::default();
+    // The store interns origin sets, so pointer identity avoids re-serializing shared ones.
+    let mut origin_ids: FxHashMap<*const OriginSet, i64> = FxHashMap::default();
+    for (origin, blob_md, m) in msgs {
+        let origin_id = match origin_ids.get(&Arc::as_ptr(origin)) {
+            Some(id) => *id,
+            None => {
+                let json = serde_json::to_string(&**origin)?;
+                let id = xxh3_64(json.as_bytes()) as i64;
+                insert_origin.execute(params![id, json])?;
+                origin_ids.insert(Arc::as_ptr(origin), id);
+                id
+            }
+        };
+        insert_blob.execute(params![
+            blob_md.id.hex(),
+            blob_md.num_bytes as i64,
+            blob_md.mime_essence,
+            blob_md.language,
+        ])?;
+        if rule_ids.insert(m.rule.id()) {
+            insert_rule.execute(params![m.rule.id(), serde_json::to_string(m.rule.syntax())?])?;
+        }
+        insert_match.execute(params![
+            m.rule.id(),
+            m.blob_id.hex(),
+            origin_id,
+            m.validation_success,
+            serde_json::to_string(m)?,
+        ])?;
+    }
+    Ok(())
+}
+
+/// Deserializable mirror of `Match`; the rule is re-attached from the rules table.
+#[derive(Deserialize)]
+pub(crate) struct StoredMatch {
+    location: Location,
+    groups: StoredCaptures,
+    blob_id: BlobId,
+    finding_fingerprint: u64,
+    validation_response_body: String,
+    validation_response_status: u16,
+    validation_success: bool,
+    calculated_entropy: f32,
+    visible: bool,
+    #[serde(default)]
+    is_base64: bool,
+    #[serde(default)]
+    encoding: Option<Encoding>,
+    #[serde(default)]
+    parts: Vec<MatchPart>,
+    #[serde(default)]
+    paired_dependencies: Vec<PairedDependency>,
+}
+
+#[derive(Deserialize)]
+struct StoredCaptures {
+    captures: Vec<StoredCapture>,
+}
+
+#[derive(Deserialize)]
+struct StoredCapture {
+    name: Option<String>,
+    match_number: i32,
+    start: usize,
+    end: usize,
+    value: String,
+}
+
+impl StoredMatch {
+    pub(crate) fn into_match(self, rule: Arc<Rule>) -> Match {
+        let captures: SmallVec<[SerializableCapture; 2]> = self
+            .groups
+            .captures
+            .into_iter()
+            .map(|c| SerializableCapture {
+                name: c.name,
+                match_number: c.match_number,
+                start: c.start,
+                end: c.end,
+                value: intern(&c.value),
+            })
+            .collect();
+        Match {
+            location: self.location,
+            groups: SerializableCaptures { captures },
+            blob_id: self.blob_id,
+            finding_fingerprint: self.finding_fingerprint,
+            rule,
+            validation_response_body: self.validation_response_body,
+            validation_response_status: self.validation_response_status,
+            validation_success: self.validation_success,
+            calculated_entropy: self.calculated_entropy,
+            visible: self.visible,
+            is_base64: self.is_base64,
+            encoding: self.encoding.or(self.is_base64.then_some(Encoding::Base64)),
+            parts: self.parts,
+            paired_dependencies: self.paired_dependencies,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        location::{OffsetSpan, SourcePoint, SourceSpan},
+        rules::rule::Confidence,
+    };
+    use tempfile::tempdir;
+
+    fn sample_message(rule: &Arc<Rule>, path: &str, secret: &'static str) -> FindingsStoreMessage {
+        let blob_id = BlobId::new(secret.as_bytes());
+        let mut captures = SmallVec::new();
+        captures.push(SerializableCapture {
+            name: None,
+            match_number: 1,
+            start: 4,
+            end: 4 + secret.len(),
+            value: secret,
+        });
+        let m = Match {
+            location: Location {
+                offset_span: OffsetSpan { start: 4, end: 4 + secret.len() },
+                source_span: SourceSpan {
+                    start: SourcePoint { line: 3, column: 4 },
+                    end: SourcePoint { line: 3, column: 4 + secret.len() },
+                },
+            },
+            groups: SerializableCaptures { captures },
+            blob_id,
+            finding_fingerprint: 0xdead_beef_cafe_f00d,
+            rule: Arc::clone(rule),
+            validation_response_body: "ok".into(),
+            validation_response_status: 200,
+            validation_success: true,
+            calculated_entropy: 4.5,
+            visible: true,
+            is_base64: false,
+            encoding: None,
+            parts: Vec::new(),
+            paired_dependencies: Vec::new(),
+        };
+        (
+            Arc::new(OriginSet::single(Origin::from_file(PathBuf::from(path)))),
+            Arc::new(BlobMetadata {
+                id: blob_id,
+                num_bytes: 64,
+                mime_essence: Some("text/plain".into()),
+                language: None,
+            }),
+            m,
+        )
+    }
+
+    #[test]
+    fn findings_round_trip_through_datastore() -> Result<()> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("findings.db");
+        let rule = Arc::new(Rule::new(RuleSyntax {
+            name: "Test Rule".into(),
+            id: "test.rule".into(),
+            pattern: "secret_[a-z]+".into(),
+            min_entropy: 0.0,
+            confidence: Confidence::Medium,
+            visible: true,
+            examples: vec![],
+            negative_examples: vec![],
+            references: vec![],
+            validation: None,
+            depends_on_rule: vec![],
+            ..Default::default()
+        }));
+
+        let mut store = FindingsStore::new(dir.path().to_path_buf());
+        store.record_rules(&[Arc::clone(&rule)]);
+        store.record(
+            vec![
+                sample_message(&rule, "/src/a.txt", "secret_alpha"),
+                sample_message(&rule, "/src/b.txt", "secret_beta"),
+            ],
+            false,
+        );
+        store.register_s3_bucket(PathBuf::from("/tmp/s3"), "bucket".into());
+
+        let mut db = Datastore::create(&db_path)?;
+        db.save_findings(&store)?;
+        db.set_metadata(META_JIRA_URL, "https://jira.example.com")?;
+        drop(db);
+
+        let db = Datastore::open(&db_path)?;
+        assert_eq!(db.metadata(META_JIRA_URL)?.as_deref(), Some("https://jira.example.com"));
+        let loaded = db.load_findings(dir.path().to_path_buf())?;
+        assert_eq!(loaded.get_rules()?.len(), 1);
+        assert_eq!(loaded.get_matches().len(), 2);
+        assert_eq!(loaded.s3_buckets().get(Path::new("/tmp/s3")).unwrap(), "bucket");
+
+        let (origin, blob, m) = &*loaded.get_matches()[0];
+        assert_eq!(origin.first().full_path().unwrap(), Path::new("/src/a.txt"));
+        assert_eq!(blob.mime_essence.as_deref(), Some("text/plain"));
+        assert_eq!(m.rule.id(), "test.rule");
+        assert_eq!(m.groups.captures[0].value, "secret_alpha");
+        assert_eq!(m.finding_fingerprint, 0xdead_beef_cafe_f00d);
+        assert!(m.validation_success);
+        Ok(())
+    }
+
+    #[test]
+    fn saving_replaces_previous_contents() -> Result<()> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("findings.db");
+        let mut db = Datastore::create(&db_path)?;
+        db.save_findings(&FindingsStore::new(dir.path().to_path_buf()))?;
+        db.save_findings(&FindingsStore::new(dir.path().to_path_buf()))?;
+        let loaded = db.load_findings(dir.path().to_path_buf())?;
+        assert!(loaded.get_matches().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn open_requires_existing_file() {
+        let dir = tempdir().unwrap();
+        assert!(Datastore::open(&dir.path().join("missing.db")).is_err());
+    }
+}
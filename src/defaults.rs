@@ -3,7 +3,11 @@ use std::path::Path;
 use anyhow::Result;
 use include_dir::{include_dir, Dir};
 
-use crate::rules::{rule::Confidence, Rules};
+use crate::rules::{
+    rule::Confidence,
+    template::{ValidationTemplates, TEMPLATES_KEY},
+    Rules,
+};
 
 pub static DEFAULT_RULES_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/data");
 
@@ -25,6 +29,18 @@ pub fn get_builtin_rules(confidence: Option<Confidence>) -> Result<Rules> {
     let confidence = confidence.unwrap_or(Confidence::Medium);
     Rules::from_paths_and_contents(get_default_rule_files(), confidence)
 }
+/// Collect the validation templates declared by the default rule files, so
+/// custom rules can reference them.
+pub fn get_builtin_validation_templates() -> Result<ValidationTemplates> {
+    let mut templates = ValidationTemplates::default();
+    for (path, contents) in get_default_rule_files() {
+        if !contents.windows(TEMPLATES_KEY.len()).any(|w| w == TEMPLATES_KEY.as_bytes()) {
+            continue;
+        }
+        templates.collect(path, &serde_yaml::from_slice(contents)?)?;
+    }
+    Ok(templates.into_builtin())
+}
 #[cfg(test)]
 mod test {
     use super::*;
@@ -32,4 +48,62 @@ mod test {
     fn test_get_default_rules() {
         assert!(get_builtin_rules(None).unwrap().num_rules() >= 100);
     }
+    #[test]
+    fn builtin_rules_expand_validation_templates() {
+        use crate::rules::rule::Validation;
+
+        let rules = get_builtin_rules(None).unwrap();
+        let Some(Validation::Http(http)) = &rules.rules["kingfisher.airtable.1"].validation else {
+            panic!("expected kingfisher.airtable.1 to have HTTP validation");
+        };
+        assert_eq!(http.request.url, "https://api.airtable.com/v0/meta/whoami");
+        assert!(!get_builtin_validation_templates().unwrap().is_empty());
+    }
+    #[test]
+    fn builtin_rules_have_severity_and_tags() {
+        let rules = get_builtin_rules(Some(Confidence::Low)).unwrap();
+        for rule in rules.iter_rules() {
+            assert!(rule.severity.is_some(), "{} has no severity", rule.id);
+            assert!(!rule.tags.is_empty(), "{} has no tags", rule.id);
+        }
+        assert!(rules.iter_rules().any(|r| r.tags.iter().any(|t| t == "pii")));
+    }
+    #[test]
+    fn builtin_rule_examples_pass_their_checksums() {
+        let rules = get_builtin_rules(Some(Confidence::Low)).unwrap();
+        let mut checked = Vec::new();
+        for rule in rules.iter_rules() {
+            let Some(checksum) = &rule.checksum else {
+                continue;
+            };
+            let re = rule.as_regex().unwrap();
+            for example in &rule.examples {
+                let tokens: Vec<_> = re
+                    .captures_iter(example.as_bytes())
+                    .map(|c| c.get(1).unwrap_or_else(|| c.get(0).unwrap()).as_bytes())
+                    .collect();
+                assert!(!tokens.is_empty(), "{} does not match its example {example:?}", rule.id);
+                for token in tokens {
+                    assert!(
+                        checksum.verify(token),
+                        "{} example fails its checksum: {}",
+                        rule.id,
+                        String::from_utf8_lossy(token)
+                    );
+                }
+            }
+            checked.push(rule.id.as_str());
+        }
+        for id in [
+            "kingfisher.github.1",
+            "kingfisher.github.2",
+            "kingfisher.github.3",
+            "kingfisher.npm.1",
+            "kingfisher.pypi.1",
+            "kingfisher.slack.2",
+            "kingfisher.shopify.1",
+        ] {
+            assert!(checked.contains(&id), "{id} has no checksum");
+        }
+    }
 }
@@ -12,6 +12,7 @@ use xxhash_rust::xxh3::xxh3_64;
 
 use crate::{
     blob::{BlobId, BlobMetadata},
+    checkpoint::Checkpoint,
     finding_data,
     git_url::GitUrl,
     location::OffsetSpan,
@@ -58,6 +59,7 @@ pub struct FindingsStore {
     confluence_links: FxHashMap<PathBuf, String>,
     s3_buckets: FxHashMap<PathBuf, String>,
     repo_links: FxHashMap<PathBuf, String>,
+    checkpoint: Option<Arc<Checkpoint>>,
 }
 impl FindingsStore {
     pub fn new(clone_dir: PathBuf) -> Self {
@@ -80,6 +82,7 @@ impl FindingsStore {
             confluence_links: FxHashMap::default(),
             s3_buckets: FxHashMap::default(),
             repo_links: FxHashMap::default(),
+            checkpoint: None,
         }
     }
 
@@ -329,6 +332,15 @@ impl FindingsStore {
         &self.repo_links
     }
 
+    /// Attach the checkpoint that records progress for `--resume`.
+    pub fn set_checkpoint(&mut self, checkpoint: Arc<Checkpoint>) {
+        self.checkpoint = Some(checkpoint);
+    }
+
+    pub fn checkpoint(&self) -> Option<Arc<Checkpoint>> {
+        self.checkpoint.clone()
+    }
+
     pub fn register_s3_bucket(&mut self, dir: PathBuf, bucket: String) {
         self.s3_buckets.insert(dir, bucket);
     }
@@ -234,6 +234,22 @@ impl Git {
         self.run_cmd(cmd)
     }
 
+    /// Detach the working tree of a checkout clone at `commit`.
+    ///
+    /// * `repo_dir`: A clone made with `CloneMode::Checkout`.
+    /// * `commit`: A full commit id; the command fails if the clone does not contain it.
+    pub fn checkout_commit(&self, repo_dir: &Path, commit: &str) -> Result<(), GitError> {
+        let _span = debug_span!("git_checkout", "{} {commit}", repo_dir.display()).entered();
+        let mut cmd = self.git();
+        cmd.arg("-C");
+        cmd.arg(repo_dir);
+        cmd.arg("checkout");
+        cmd.arg("--quiet");
+        cmd.arg("--detach");
+        cmd.arg(commit);
+        self.run_cmd(cmd)
+    }
+
     /// Create a fresh clone of the specified repository in either bare or mirror mode.
     ///
     /// * `repo_url`: The remote repository URL.
@@ -1,13 +1,18 @@
+pub mod allowlist;
 pub mod azure;
 pub mod baseline;
 pub mod binary;
 pub mod bitbucket;
 pub mod blob;
+pub mod blob_cache;
 pub mod bstring_escape;
 pub mod bstring_table;
+pub mod checkpoint;
 pub mod cli;
 pub mod confluence;
 pub mod content_type;
+pub mod datastore;
+pub mod decoder;
 pub mod decompress;
 pub mod defaults;
 pub mod entropy;
@@ -31,8 +36,14 @@ pub mod matcher;
 pub mod origin;
 pub mod parser;
 pub mod reporter;
+pub mod revalidate;
+pub mod rule_check;
+pub mod rule_corpus;
+pub mod rule_lint;
 pub mod rule_loader;
+pub mod rule_pack;
 pub mod rule_profiling;
+pub mod rule_scaffold;
 pub mod rules;
 pub mod rules_database;
 pub mod s3;
@@ -41,6 +52,7 @@ pub mod scanner;
 pub mod scanner_pool;
 pub mod slack;
 pub mod snippet;
+pub mod transcode;
 pub mod update;
 pub mod util;
 pub mod validation;
@@ -70,12 +82,15 @@ struct EnumeratorConfig {
     repo_scan_timeout: Duration,
     exclude_globset: Option<std::sync::Arc<GlobSet>>,
     git_diff: Option<GitDiffConfig>,
+    checkpoint: Option<std::sync::Arc<checkpoint::Checkpoint>>,
 }
 
 pub enum FoundInput {
     File(FileResult),
     Directory(DirectoryResult),
     EnumeratorFile(EnumeratorFileResult),
+    /// A file over the size limit, to be scanned as a stream
+    LargeFile(LargeFileResult),
 }
 
 pub struct FileResult {
@@ -89,6 +104,11 @@ pub struct EnumeratorFileResult {
     pub path: PathBuf,
 }
 
+pub struct LargeFileResult {
+    pub path: PathBuf,
+    pub num_bytes: u64,
+}
+
 pub struct DirectoryResult {
     pub path: PathBuf,
 }
@@ -97,6 +117,7 @@ pub type Output = Sender<FoundInput>;
 
 struct VisitorBuilder<'t> {
     max_file_size: Option<u64>,
+    stream_large_files: bool,
     extract_archives: bool,
     extraction_depth: usize,
     output: &'t Output,
@@ -109,6 +130,7 @@ where
     fn build(&mut self) -> Box<dyn ignore::ParallelVisitor + 's> {
         Box::new(Visitor {
             max_file_size: self.max_file_size,
+            stream_large_files: self.stream_large_files,
             extract_archives: self.extract_archives,
             extraction_depth: self.extraction_depth,
             output: self.output,
@@ -118,6 +140,7 @@ where
 
 struct Visitor<'t> {
     max_file_size: Option<u64>,
+    stream_large_files: bool,
     extract_archives: bool,
     extraction_depth: usize,
     output: &'t Output,
@@ -163,7 +186,14 @@ impl<'t> ignore::ParallelVisitor for Visitor<'t> {
         if metadata.is_file() {
             let num_bytes = metadata.len();
             if self.file_too_big(num_bytes) {
-                debug!("Skipping {}: size {num_bytes} too big", path.display());
+                if self.stream_large_files {
+                    let _ = self.output.send(FoundInput::LargeFile(LargeFileResult {
+                        path: path.to_owned(),
+                        num_bytes,
+                    }));
+                } else {
+                    debug!("Skipping {}: size {num_bytes} too big", path.display());
+                }
             } else {
                 self.found_file(FileResult {
                     path: path.to_owned(),
@@ -188,6 +218,7 @@ pub struct FilesystemEnumerator {
     walk_builder: WalkBuilder,
     gitignore_builder: GitignoreBuilder,
     max_file_size: Option<u64>,
+    stream_large_files: bool,
     collect_git_metadata: bool,
     enumerate_git_history: bool,
     extract_archives: bool,
@@ -211,14 +242,17 @@ impl FilesystemEnumerator {
         }
 
         let max_file_size = args.content_filtering_args.max_file_size_bytes();
+        let stream_large_files = args.content_filtering_args.stream_large_files;
         builder.follow_links(Self::DEFAULT_FOLLOW_LINKS);
-        builder.max_filesize(max_file_size);
+        // Oversized files must reach the visitor to be streamed
+        builder.max_filesize(max_file_size.filter(|_| !stream_large_files));
         builder.standard_filters(false);
 
         Ok(Self {
             walk_builder: builder,
             gitignore_builder: GitignoreBuilder::new(""),
             max_file_size,
+            stream_large_files,
             collect_git_metadata: args.input_specifier_args.commit_metadata,
             enumerate_git_history: Self::DEFAULT_ENUMERATE_GIT_HISTORY,
             extract_archives: !args.content_filtering_args.no_extract_archives,
@@ -255,7 +289,7 @@ impl FilesystemEnumerator {
     }
 
     pub fn max_filesize(&mut self, max_filesize: Option<u64>) -> &mut Self {
-        self.walk_builder.max_filesize(max_filesize);
+        self.walk_builder.max_filesize(max_filesize.filter(|_| !self.stream_large_files));
         self.max_file_size = max_filesize;
         self
     }
@@ -321,6 +355,7 @@ impl FilesystemEnumerator {
     pub fn run(&self, output: Output) -> Result<()> {
         let mut visitor_builder = VisitorBuilder {
             max_file_size: self.max_file_size,
+            stream_large_files: self.stream_large_files,
             extract_archives: self.extract_archives,
             extraction_depth: self.extraction_depth,
             output: &output,
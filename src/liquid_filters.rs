@@ -7,7 +7,7 @@ use liquid_core::{
     FromFilterParameters, ParseFilter, Result, Runtime, Value, ValueView,
 };
 
-use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
 use rand::{distr::Alphanumeric, Rng};
 use sha1::Sha1;
 use sha2::{Digest, Sha256, Sha384};
@@ -349,6 +349,85 @@ static_filter!(
     }
 );
 
+// {{ value | url_decode }} – unlike the stdlib filter, `+` is left alone
+// because it is common in Base64 secrets
+static_filter!(
+    /// Percent-decode a URL component.
+    UrlDecodeFilter, "url_decode",
+    |input: &dyn ValueView| -> String {
+        percent_decode_str(&input.to_kstr()).decode_utf8_lossy().into_owned()
+    }
+);
+
+// {{ value | strip_quotes }}
+static_filter!(
+    /// Remove one pair of matching surrounding quotes (`"`, `'` or `` ` ``).
+    StripQuotesFilter, "strip_quotes",
+    |input: &dyn ValueView| -> String { strip_quotes(&input.to_kstr()).to_string() }
+);
+
+// {{ value | unescape }} – e.g. a PEM key embedded in a JSON string
+static_filter!(
+    /// Resolve backslash escapes (`\n`, `\t`, `\"`, `\uXXXX`, ...).
+    UnescapeFilter, "unescape",
+    |input: &dyn ValueView| -> String { unescape(&input.to_kstr()) }
+);
+
+// {{ value | join_lines }} – e.g. Base64 wrapped over several lines
+static_filter!(
+    /// Join lines, dropping line breaks and the whitespace around them.
+    JoinLinesFilter, "join_lines",
+    |input: &dyn ValueView| -> String {
+        input.to_kstr().lines().map(str::trim).collect::<String>()
+    }
+);
+
+fn strip_quotes(s: &str) -> &str {
+    for quote in ['"', '\'', '`'] {
+        if let Some(inner) = s.strip_prefix(quote).and_then(|rest| rest.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    s
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('u') => {
+                let hex: String = chars.clone().take(4).collect();
+                let decoded = (hex.len() == 4 && hex.chars().all(|h| h.is_ascii_hexdigit()))
+                    .then(|| u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32))
+                    .flatten();
+                match decoded {
+                    Some(decoded) => {
+                        out.push(decoded);
+                        chars.nth(3);
+                    }
+                    _ => out.push_str("\\u"),
+                }
+            }
+            Some(other @ ('"' | '\'' | '\\' | '/')) => out.push(other),
+            // Unknown escapes are kept as written
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
 // {{ "" | unix_timestamp }}
 static_filter!(
     /// Current Unix epoch seconds.
@@ -407,6 +486,10 @@ pub fn register_all(builder: liquid::ParserBuilder) -> liquid::ParserBuilder {
         .filter(Sha256Filter::default())
         .filter(UrlEncodeFilter::default())
         .filter(JsonEscapeFilter::default())
+        .filter(UrlDecodeFilter)
+        .filter(StripQuotesFilter)
+        .filter(UnescapeFilter)
+        .filter(JoinLinesFilter)
         .filter(UnixTimestampFilter::default())
         .filter(IsoTimestampFilter::default())
         .filter(IsoTimestampNoFracFilter::default())
@@ -580,6 +663,40 @@ mod tests {
         assert_eq!(render(r#"{{ "hello world" | replace: "world", "mars" }}"#), "hello mars");
     }
 
+    // -------------------------------------------------------------------------
+    // Capture normalization filters
+    // -------------------------------------------------------------------------
+    #[test]
+    fn url_decode_filter_keeps_plus() {
+        assert_eq!(render(r#"{{ "a%2Fb+c%3D%3D" | url_decode }}"#), "a/b+c==");
+    }
+
+    #[test]
+    fn strip_quotes_filter() {
+        assert_eq!(render(r#"{{ '"secret"' | strip_quotes }}"#), "secret");
+        assert_eq!(render(r#"{{ "'secret'" | strip_quotes }}"#), "secret");
+        assert_eq!(render(r#"{{ '"secret' | strip_quotes }}"#), "\"secret");
+    }
+
+    #[test]
+    fn unescape_filter() {
+        let parser = parser();
+        let template = parser.parse("{{ v | unescape }}").unwrap();
+        let globals = object!({ "v": r#"-----BEGIN KEY-----\nabc\u0041\"\q\n-----END KEY-----"# });
+        assert_eq!(
+            template.render(&globals).unwrap(),
+            "-----BEGIN KEY-----\nabcA\"\\q\n-----END KEY-----"
+        );
+    }
+
+    #[test]
+    fn join_lines_filter() {
+        let parser = parser();
+        let template = parser.parse("{{ v | join_lines }}").unwrap();
+        let globals = object!({ "v": "aGVs\r\n  bG8g\nd29y\nbGQ=\n" });
+        assert_eq!(template.render(&globals).unwrap(), "aGVsbG8gd29ybGQ=");
+    }
+
     // -------------------------------------------------------------------------
     // iso_timestamp_no_frac filter
     // -------------------------------------------------------------------------
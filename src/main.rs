@@ -28,6 +28,7 @@ static GLOBAL: System = System;
 
 use std::{
     io::{IsTerminal, Read},
+    path::PathBuf,
     sync::{Arc, Mutex},
 };
 
@@ -37,33 +38,37 @@ use kingfisher::{
     cli::{
         self,
         commands::{
+            config::ConfigCommand,
             github::{
                 GitCloneMode, GitHistoryMode, GitHubCommand, GitHubRepoType, GitHubReposCommand,
             },
             inputs::{ContentFilteringArgs, InputSpecifierArgs},
             output::{OutputArgs, ReportOutputFormat},
+            report::ReportArgs,
             rules::{
-                RuleSpecifierArgs, RulesCheckArgs, RulesCommand, RulesListArgs,
-                RulesListOutputFormat,
+                RuleSpecifierArgs, RulesCheckArgs, RulesCheckOutputFormat, RulesCommand,
+                RulesListArgs, RulesListOutputFormat,
             },
         },
         global::Command,
         CommandLineArgs, GlobalArgs,
     },
+    datastore::{Datastore, META_CLONE_DIR, META_JIRA_URL},
     findings_store,
     findings_store::FindingsStore,
     gitea, github, huggingface,
+    rule_check::{check_rules, IssueSeverity},
     rule_loader::RuleLoader,
-    rules_database::RulesDatabase,
+    rules::rule::Rule,
     scanner::{load_and_record_rules, run_scan},
     update::check_for_update,
-    validation::set_user_agent_suffix,
+    validation::{set_ignore_certs, set_user_agent_suffix},
 };
 use serde_json::json;
 use tempfile::TempDir;
 use term_size;
 use tokio::runtime::Builder;
-use tracing::{error, info, warn};
+use tracing::info;
 use tracing_core::metadata::LevelFilter;
 use tracing_subscriber::{
     self, fmt, prelude::__tracing_subscriber_SubscriberExt, registry, util::SubscriberInitExt,
@@ -84,6 +89,7 @@ fn main() -> anyhow::Result<()> {
     let args = CommandLineArgs::parse_args();
 
     set_user_agent_suffix(args.global_args.user_agent_suffix.clone());
+    set_ignore_certs(args.global_args.ignore_certs);
 
     // Determine the number of jobs, defaulting to the number of CPUs
     let num_jobs = match args.command {
@@ -96,6 +102,10 @@ fn main() -> anyhow::Result<()> {
         Command::Azure(_) => num_cpus::get(), // Default for Azure commands
         Command::HuggingFace(_) => num_cpus::get(), // Default for Hugging Face commands
         Command::Rules(_) => num_cpus::get(), // Default for Rules commands
+        Command::Report(_) => num_cpus::get(), // Default for Report commands
+        Command::Config(_) => 1,  // Config commands only read files
+        Command::Validate(ref validate_args) => validate_args.num_jobs,
+        Command::ValidateSecret(_) => num_cpus::get(), // Default for validate-secret
     };
 
     // Set up the Tokio runtime with the specified number of threads
@@ -228,13 +238,40 @@ async fn async_main(args: CommandLineArgs) -> Result<()> {
                     }
                     std::process::exit(exit_code);
                 }
+                Command::Report(report_args) => {
+                    run_report(&global_args, &report_args, temp_dir.path().to_path_buf())?;
+                }
+                Command::Validate(validate_args) => {
+                    kingfisher::revalidate::run_validate(&global_args, &validate_args).await?;
+                }
+                Command::ValidateSecret(validate_args) => {
+                    kingfisher::revalidate::run_validate_secret(&global_args, &validate_args)
+                        .await?;
+                }
                 Command::Rules(ref rule_args) => match &rule_args.command {
                     RulesCommand::Check(check_args) => {
                         run_rules_check(&check_args)?;
                     }
+                    RulesCommand::Lint(lint_args) => {
+                        kingfisher::rule_lint::run_rules_lint(lint_args)?;
+                    }
                     RulesCommand::List(list_args) => {
                         run_rules_list(&list_args)?;
                     }
+                    RulesCommand::New(new_args) => {
+                        kingfisher::rule_scaffold::run_rules_new(new_args)?;
+                    }
+                    RulesCommand::Test(test_args) => {
+                        kingfisher::rule_corpus::run_rules_test(&global_args, test_args).await?;
+                    }
+                    RulesCommand::Update(update_args) => {
+                        kingfisher::rule_pack::run_rules_update(update_args)?;
+                    }
+                },
+                Command::Config(config_args) => match config_args.command {
+                    ConfigCommand::Show => {
+                        run_config_show(&global_args)?;
+                    }
                 },
                 Command::GitHub(github_args) => match github_args.command {
                     GitHubCommand::Repos(repos_command) => match repos_command {
@@ -372,6 +409,10 @@ fn create_default_scan_args() -> cli::commands::scan::ScanArgs {
             rules_path: Vec::new(),
             rule: vec!["all".into()],
             load_builtins: true,
+            rule_tag: vec![],
+            exclude_rule_tag: vec![],
+            rules_url: vec![],
+            rules_public_key: None,
         },
         input_specifier_args: InputSpecifierArgs {
             path_inputs: Vec::new(),
@@ -457,9 +498,11 @@ fn create_default_scan_args() -> cli::commands::scan::ScanArgs {
             extraction_depth: 2,
             exclude: Vec::new(), // Exclude patterns
             no_binary: true,
+            stream_large_files: false,
         },
         confidence: ConfidenceLevel::Medium,
         no_validate: true,
+        cross_file_dependencies: false,
         rule_stats: false,
         only_valid: false,
         min_entropy: None,
@@ -470,114 +513,122 @@ fn create_default_scan_args() -> cli::commands::scan::ScanArgs {
         manage_baseline: false,
         skip_regex: Vec::new(),
         skip_word: Vec::new(),
+        allowlist: None,
+        safe_list: Vec::new(),
+        disable_safe_rule: Vec::new(),
+        report_suppressed: false,
+        decode: Vec::new(),
+        no_decode: Vec::new(),
         skip_aws_account: Vec::new(),
         skip_aws_account_file: None,
         output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+        datastore: None,
+        resume: None,
+        blob_cache: None,
         no_base64: false,
         no_inline_ignore: false,
     }
 }
+/// Run the report command against a datastore from a previous scan
+pub fn run_report(global_args: &GlobalArgs, args: &ReportArgs, clone_dir: PathBuf) -> Result<()> {
+    let db = Datastore::open(&args.datastore)?;
+    // Reuse the original clone directory so Jira issue paths still resolve to their URLs
+    let clone_dir = db.metadata(META_CLONE_DIR)?.map(PathBuf::from).unwrap_or(clone_dir);
+    let mut store = db.load_findings(clone_dir)?;
+    if !args.rule.is_empty() {
+        let kept = store
+            .get_matches()
+            .iter()
+            .filter(|msg| {
+                let (_, _, m) = &***msg;
+                args.rule.iter().any(|prefix| m.rule.id().starts_with(prefix.as_str()))
+            })
+            .cloned()
+            .collect();
+        store.replace_matches(kept);
+    }
+
+    let mut scan_args = create_default_scan_args();
+    scan_args.only_valid = args.only_valid;
+    scan_args.no_dedup = args.no_dedup;
+    scan_args.output_args = args.output_args.clone();
+    scan_args.input_specifier_args.jira_url = db
+        .metadata(META_JIRA_URL)?
+        .map(|url| Url::parse(&url))
+        .transpose()
+        .context("Invalid Jira URL stored in datastore")?;
+    kingfisher::reporter::run(global_args, Arc::new(Mutex::new(store)), &scan_args)
+}
+
+/// Print the effective configuration merged from all config files
+pub fn run_config_show(global_args: &GlobalArgs) -> Result<()> {
+    let files = if global_args.no_config {
+        Vec::new()
+    } else {
+        cli::config::discover(global_args.config.as_deref())?
+    };
+    print!("{}", cli::config::render_effective(&files)?);
+    Ok(())
+}
+
 /// Run the rules check command
 pub fn run_rules_check(args: &RulesCheckArgs) -> Result<()> {
-    let mut num_errors = 0;
-    let mut num_warnings = 0;
     // Load and check rules
     let loader = RuleLoader::from_rule_specifiers(&args.rules);
     let loaded = loader.load(&create_default_scan_args())?;
     let resolved = loaded.resolve_enabled_rules()?;
-    let rules_db = RulesDatabase::from_rules(resolved.into_iter().cloned().collect())?;
-
-    // Check each rule
-    for (rule_index, rule) in rules_db.rules().iter().enumerate() {
-        let rule_syntax = rule.syntax();
-        // Basic rule validation checks
-        if rule.name().len() < 3 {
-            warn!("Rule '{}' has a very short name", rule.name());
-            num_warnings += 1;
-        }
-        if rule.syntax().pattern.len() < 5 {
-            warn!("Rule '{}' has a very short pattern", rule.name());
-            num_warnings += 1;
-        }
-        if rule.syntax().examples.is_empty() {
-            warn!("Rule '{}' has no examples", rule.name());
-            num_warnings += 1;
-            continue;
-        }
-        // Check regex compilation
-        if let Err(e) = rule.syntax().as_regex() {
-            error!("Rule '{}' has invalid regex: {}", rule.name(), e);
-            num_errors += 1;
-            continue;
-        }
-        // Test each example against both vectorscan and regex
-        for (example_index, example) in rule_syntax.examples.iter().enumerate() {
-            // Create a test blob from the example
-            // let blob = Blob::new(BlobId::new(example.as_bytes()),
-            // example.as_bytes().to_vec()); let origin = OriginSet::new(
-            //     Origin::from_file(PathBuf::from("test_example")),
-            //     Vec::new(),
-            // );
-            // // Check vectorscan match
-            // let vectorscan_matched = match matcher.scan_blob(&blob, &origin, None)? {
-            //     ScanResult::New(matches) => !matches.is_empty(),
-            //     _ => false,
-            // };
-            // Check regex match
-            // Get the regex using the public method
-            let re =
-                rules_db.get_regex_by_rule_id(rule.id()).expect("Failed to get regex for rule");
-            let regex_matched = re.is_match(example.as_bytes());
-            if !regex_matched {
-                // ||!vectorscan_matched  {
-                println!("\nTesting rule {} - {}", rule_index + 1, rule_syntax.name);
-                println!("  Processing example {}", example_index + 1);
-                println!("    [!] Mismatch detected for example: {}", example);
-                // if !vectorscan_matched {
-                //     println!("    Vectorscan match: {}", vectorscan_matched);
-                //     num_errors += 1;
-                // }
-                if !regex_matched {
-                    println!("    Regex match: {}", regex_matched);
-                    num_errors += 1;
+    let report = check_rules(resolved.into_iter().cloned().collect())?;
+
+    let mut writer = args.output_args.get_writer()?;
+    match args.output_args.format {
+        RulesCheckOutputFormat::Pretty => {
+            for issue in &report.issues {
+                let label = match issue.severity {
+                    IssueSeverity::Error => "[!] Error",
+                    IssueSeverity::Warning => "[-] Warning",
+                };
+                writeln!(
+                    writer,
+                    "{label}: {} ({}): {}",
+                    issue.rule_name, issue.rule_id, issue.message
+                )?;
+                if let Some(example) = &issue.example {
+                    writeln!(writer, "    Example: {example}")?;
+                }
+                if let Some(outcome) = issue.outcome {
+                    writeln!(
+                        writer,
+                        "    Vectorscan match: {}, Regex match: {}, Finding: {}",
+                        outcome.vectorscan, outcome.regex, outcome.finding
+                    )?;
                 }
             }
-
-            // // Report any mismatches
-            // if !vectorscan_matched || !regex_matched {
-            //     error!("Rule '{}' example {} failed validation:",
-            // rule.name(), example_index + 1);     println!("
-            // Example text: {}", example);
-
-            //     if !vectorscan_matched {
-            //         error!("  - Vectorscan pattern did not match example");
-            //         num_errors += 1;
-            //     }
-
-            //     if !regex_matched {
-            //         error!("  - Regex pattern did not match example");
-            //         num_errors += 1;
-            //     }
-            // }
+            // Print summary
+            if report.errors > 0 || report.warnings > 0 {
+                writeln!(writer, "\nCheck Summary:")?;
+                writeln!(writer, "  Rules checked: {}", report.rules_checked)?;
+                writeln!(writer, "  Examples checked: {}", report.examples_checked)?;
+                writeln!(writer, "  Errors: {}", report.errors)?;
+                writeln!(writer, "  Warnings: {}", report.warnings)?;
+                writeln!(writer, "\nError types include:")?;
+                writeln!(writer, "  - Examples that don't produce a finding")?;
+                writeln!(writer, "  - Negative examples that produce a finding")?;
+                writeln!(writer, "\nWarning types include:")?;
+                writeln!(writer, "  - Rules with very short names")?;
+                writeln!(writer, "  - Rules with very short patterns")?;
+                writeln!(writer, "  - Rules without examples")?;
+            } else {
+                writeln!(writer, "\nAll rules passed validation successfully!")?;
+            }
+        }
+        RulesCheckOutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, &report)?;
+            writeln!(writer)?;
         }
     }
-    // Print summary
-    if num_errors > 0 || num_warnings > 0 {
-        println!("\nCheck Summary:");
-        println!("  Errors: {}", num_errors);
-        println!("  Warnings: {}", num_warnings);
-        println!("\nError types include:");
-        println!("  - Invalid regex patterns");
-        println!("  - Examples that don't match their patterns");
-        println!("\nWarning types include:");
-        println!("  - Rules with very short names");
-        println!("  - Rules with very short patterns");
-        println!("  - Rules without examples");
-    } else {
-        println!("\nAll rules passed validation successfully!");
-    }
+    writer.flush()?;
     // Exit with error if there are errors or if warnings are treated as errors
-    if num_errors > 0 || (args.warnings_as_errors && num_warnings > 0) {
+    if report.errors > 0 || (args.warnings_as_errors && report.warnings > 0) {
         std::process::exit(1);
     }
     Ok(())
@@ -602,8 +653,19 @@ pub fn run_rules_list(args: &RulesListArgs) -> Result<()> {
                 .max()
                 .unwrap_or(0)
                 .max(10); // "Confidence" header
-                          // Calculate pattern width based on terminal width
-            let reserved_width = max_name_width + max_id_width + max_conf_width + 10;
+            let severity_of = |r: &Rule| r.severity().map_or(String::new(), |s| s.to_string());
+            // "Severity" and "Tags" headers
+            let max_sev_width =
+                resolved.iter().map(|r| severity_of(r).len()).max().unwrap_or(0).max(8);
+            let max_tags_width =
+                resolved.iter().map(|r| r.tags().join(",").len()).max().unwrap_or(0).max(4);
+            // Calculate pattern width based on terminal width
+            let reserved_width = max_name_width
+                + max_id_width
+                + max_conf_width
+                + max_sev_width
+                + max_tags_width
+                + 16;
             let pattern_width = term_width.saturating_sub(reserved_width);
             // Format pattern on a single line
             let format_pattern = |pattern: &str| {
@@ -622,22 +684,28 @@ pub fn run_rules_list(args: &RulesListArgs) -> Result<()> {
             // Print header
             writeln!(
                 writer,
-                "\n{:name_width$} │ {:id_width$} │ {:conf_width$} │ Pattern",
+                "\n{:name_width$} │ {:id_width$} │ {:conf_width$} │ {:sev_width$} │ {:tags_width$} │ Pattern",
                 "Rule",
                 "ID",
                 "Confidence",
+                "Severity",
+                "Tags",
                 name_width = max_name_width,
                 id_width = max_id_width,
-                conf_width = max_conf_width
+                conf_width = max_conf_width,
+                sev_width = max_sev_width,
+                tags_width = max_tags_width
             )?;
             // Print separator
             writeln!(
                 writer,
-                "{0:─<name_width$} ┼ {0:─<id_width$} ┼ {0:─<conf_width$} ┼ {0:─<pattern_width$}",
+                "{0:─<name_width$} ┼ {0:─<id_width$} ┼ {0:─<conf_width$} ┼ {0:─<sev_width$} ┼ {0:─<tags_width$} ┼ {0:─<pattern_width$}",
                 "",
                 name_width = max_name_width,
                 id_width = max_id_width,
                 conf_width = max_conf_width,
+                sev_width = max_sev_width,
+                tags_width = max_tags_width,
                 pattern_width = pattern_width
             )?;
             // Print each rule
@@ -645,14 +713,18 @@ pub fn run_rules_list(args: &RulesListArgs) -> Result<()> {
                 let formatted_pattern = format_pattern(&rule.syntax().pattern);
                 writeln!(
                     writer,
-                    "{:name_width$} │ {:id_width$} │ {:conf_width$} │ {}",
+                    "{:name_width$} │ {:id_width$} │ {:conf_width$} │ {:sev_width$} │ {:tags_width$} │ {}",
                     rule.name(),
                     rule.id(),
                     format!("{:?}", rule.confidence()),
+                    severity_of(rule),
+                    rule.tags().join(","),
                     formatted_pattern,
                     name_width = max_name_width,
                     id_width = max_id_width,
-                    conf_width = max_conf_width
+                    conf_width = max_conf_width,
+                    sev_width = max_sev_width,
+                    tags_width = max_tags_width
                 )?;
             }
             writeln!(writer)?;
@@ -667,6 +739,8 @@ pub fn run_rules_list(args: &RulesListArgs) -> Result<()> {
                         "id": rule.id(),
                         "pattern": rule.syntax().pattern,
                         "confidence": rule.confidence(),
+                        "severity": rule.severity(),
+                        "tags": rule.tags(),
                         "examples": rule.syntax().examples,
                         "visible": rule.visible(),
                     })
@@ -8,17 +8,24 @@ use tree_sitter::{Parser as TreeSitterParser, Query, QueryCursor};
 use tree_sitter_bash;
 use tree_sitter_c;
 use tree_sitter_c_sharp;
+use tree_sitter_containerfile;
 use tree_sitter_cpp;
 use tree_sitter_css;
+use tree_sitter_elixir;
 use tree_sitter_go;
+use tree_sitter_hcl;
 use tree_sitter_html;
 use tree_sitter_java;
 use tree_sitter_javascript;
+use tree_sitter_kotlin_ng;
+use tree_sitter_lua;
 use tree_sitter_php;
 use tree_sitter_python;
 use tree_sitter_regex;
 use tree_sitter_ruby;
 use tree_sitter_rust;
+use tree_sitter_scala;
+use tree_sitter_swift;
 use tree_sitter_toml_ng;
 use tree_sitter_typescript;
 use tree_sitter_yaml;
@@ -37,15 +44,22 @@ pub enum Language {
     CSharp,
     Cpp,
     Css,
+    Dockerfile,
+    Elixir,
     Go,
+    Hcl,
     Html,
     Java,
     JavaScript,
+    Kotlin,
+    Lua,
     Php,
     Python,
     Regex,
     Ruby,
     Rust,
+    Scala,
+    Swift,
     Toml,
     TypeScript,
     Yaml,
@@ -65,15 +79,22 @@ impl Language {
             Language::CSharp => "c_sharp",
             Language::Cpp => "cpp",
             Language::Css => "css",
+            Language::Dockerfile => "dockerfile",
+            Language::Elixir => "elixir",
             Language::Go => "go",
+            Language::Hcl => "hcl",
             Language::Html => "html",
             Language::Java => "java",
             Language::JavaScript => "javascript",
+            Language::Kotlin => "kotlin",
+            Language::Lua => "lua",
             Language::Php => "php",
             Language::Python => "python",
             Language::Regex => "regex",
             Language::Ruby => "ruby",
             Language::Rust => "rust",
+            Language::Scala => "scala",
+            Language::Swift => "swift",
             Language::Toml => "toml",
             Language::TypeScript => "typescript",
             Language::Yaml => "yaml",
@@ -87,15 +108,22 @@ impl Language {
             Language::CSharp => Ok(tree_sitter_c_sharp::LANGUAGE.into()),
             Language::Cpp => Ok(tree_sitter_cpp::LANGUAGE.into()),
             Language::Css => Ok(tree_sitter_css::LANGUAGE.into()),
+            Language::Dockerfile => Ok(tree_sitter_containerfile::LANGUAGE.into()),
+            Language::Elixir => Ok(tree_sitter_elixir::LANGUAGE.into()),
             Language::Go => Ok(tree_sitter_go::LANGUAGE.into()),
+            Language::Hcl => Ok(tree_sitter_hcl::LANGUAGE.into()),
             Language::Html => Ok(tree_sitter_html::LANGUAGE.into()),
             Language::Java => Ok(tree_sitter_java::LANGUAGE.into()),
             Language::JavaScript => Ok(tree_sitter_javascript::LANGUAGE.into()),
+            Language::Kotlin => Ok(tree_sitter_kotlin_ng::LANGUAGE.into()),
+            Language::Lua => Ok(tree_sitter_lua::LANGUAGE.into()),
             Language::Php => Ok(tree_sitter_php::LANGUAGE_PHP.into()),
             Language::Python => Ok(tree_sitter_python::LANGUAGE.into()),
             Language::Regex => Ok(tree_sitter_regex::LANGUAGE.into()),
             Language::Ruby => Ok(tree_sitter_ruby::LANGUAGE.into()),
             Language::Rust => Ok(tree_sitter_rust::LANGUAGE.into()),
+            Language::Scala => Ok(tree_sitter_scala::LANGUAGE.into()),
+            Language::Swift => Ok(tree_sitter_swift::LANGUAGE.into()),
             Language::Toml => Ok(tree_sitter_toml_ng::LANGUAGE.into()),
             Language::TypeScript => Ok(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
             Language::Yaml => Ok(tree_sitter_yaml::LANGUAGE.into()),
@@ -113,14 +141,21 @@ impl FromStr for Language {
             "csharp" | "c_sharp" => Ok(Language::CSharp),
             "cpp" => Ok(Language::Cpp),
             "css" => Ok(Language::Css),
+            "dockerfile" | "containerfile" => Ok(Language::Dockerfile),
+            "elixir" | "ex" | "exs" => Ok(Language::Elixir),
             "go" => Ok(Language::Go),
+            "hcl" | "tf" | "terraform" => Ok(Language::Hcl),
             "html" => Ok(Language::Html),
             "java" => Ok(Language::Java),
             "javascript" | "js" => Ok(Language::JavaScript),
+            "kotlin" | "kt" | "kts" => Ok(Language::Kotlin),
+            "lua" => Ok(Language::Lua),
             "php" => Ok(Language::Php),
             "python" | "py" => Ok(Language::Python),
             "ruby" => Ok(Language::Ruby),
             "rust" | "rs" => Ok(Language::Rust),
+            "scala" | "sc" => Ok(Language::Scala),
+            "swift" => Ok(Language::Swift),
             "toml" => Ok(Language::Toml),
             "typescript" | "ts" => Ok(Language::TypeScript),
             "yaml" | "yml" => Ok(Language::Yaml),
@@ -306,3 +341,30 @@ impl Checker {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elixir_and_swift_queries_find_assignments() -> Result<()> {
+        for (language, queries, source) in [
+            (
+                Language::Elixir,
+                queries::elixir::get_elixir_queries(),
+                &b"defmodule A do\n  @api_key \"abc123\"\n  password = \"s3cr3t\"\nend\n"[..],
+            ),
+            (
+                Language::Swift,
+                queries::swift::get_swift_queries(),
+                &b"let password = \"s3cr3t\"\nvar headers = [\"api_key\": \"abc123\"]\n"[..],
+            ),
+        ] {
+            let results = Checker { language, rules: queries }.check(source)?;
+            let texts: Vec<&str> = results.iter().map(|r| r.text.as_str()).collect();
+            assert!(texts.contains(&"password = \"s3cr3t\""), "{texts:?}");
+            assert_eq!(texts.len(), 2, "{texts:?}");
+        }
+        Ok(())
+    }
+}
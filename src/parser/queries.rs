@@ -71,6 +71,22 @@ pub mod yaml {
         queries
     }
 }
+pub mod dockerfile {
+    use super::*;
+    pub fn get_dockerfile_queries() -> FxHashMap<String, String> {
+        let mut queries = FxHashMap::default();
+        queries.insert("combined_dockerfile_query".to_string(), QUERIES_DOCKERFILE.to_string());
+        queries
+    }
+}
+pub mod elixir {
+    use super::*;
+    pub fn get_elixir_queries() -> FxHashMap<String, String> {
+        let mut queries = FxHashMap::default();
+        queries.insert("combined_elixir_query".to_string(), QUERIES_ELIXIR.to_string());
+        queries
+    }
+}
 pub mod go {
     use super::*;
     pub fn get_go_queries() -> FxHashMap<String, String> {
@@ -79,6 +95,14 @@ pub mod go {
         queries
     }
 }
+pub mod hcl {
+    use super::*;
+    pub fn get_hcl_queries() -> FxHashMap<String, String> {
+        let mut queries = FxHashMap::default();
+        queries.insert("combined_hcl_query".to_string(), QUERIES_HCL.to_string());
+        queries
+    }
+}
 pub mod html {
     use super::*;
     pub fn get_html_queries() -> FxHashMap<String, String> {
@@ -111,6 +135,14 @@ pub mod kotlin {
         queries
     }
 }
+pub mod lua {
+    use super::*;
+    pub fn get_lua_queries() -> FxHashMap<String, String> {
+        let mut queries = FxHashMap::default();
+        queries.insert("combined_lua_query".to_string(), QUERIES_LUA.to_string());
+        queries
+    }
+}
 pub mod php {
     use super::*;
     pub fn get_php_queries() -> FxHashMap<String, String> {
@@ -127,6 +159,22 @@ pub mod python {
         queries
     }
 }
+pub mod scala {
+    use super::*;
+    pub fn get_scala_queries() -> FxHashMap<String, String> {
+        let mut queries = FxHashMap::default();
+        queries.insert("combined_scala_query".to_string(), QUERIES_SCALA.to_string());
+        queries
+    }
+}
+pub mod swift {
+    use super::*;
+    pub fn get_swift_queries() -> FxHashMap<String, String> {
+        let mut queries = FxHashMap::default();
+        queries.insert("combined_swift_query".to_string(), QUERIES_SWIFT.to_string());
+        queries
+    }
+}
 pub mod toml {
     use super::*;
     pub fn get_toml_queries() -> FxHashMap<String, String> {
@@ -860,80 +908,50 @@ pub const QUERIES_JAVASCRIPT: &str = r#"
     )
 "#;
 pub const QUERIES_KOTLIN: &str = r#"
-    ; Query 1: Matches property declarations with string literals
-    (property_declaration
-        (variable_declaration
-            (simple_identifier) @key
-        ) 
-        (string_literal) @val
-    )
-
-    ; Query 2: Matches property declarations with call expressions and string literals
+    ; Query 1: Matches val and var declarations initialised with a literal
     (property_declaration
         (variable_declaration
-            (simple_identifier) @key
-        ) 
-        (call_expression
-            (navigation_expression
-                (string_literal) @val
-            )
+            (identifier) @key
         )
+        [(string_literal)(multiline_string_literal)(number_literal)] @val
     )
 
-    ; Query 3: Matches property declarations with call expressions and value arguments
+    ; Query 2: Matches val and var declarations initialised by a call with a string argument
     (property_declaration
         (variable_declaration
-            (simple_identifier) @key
-        ) 
+            (identifier) @key
+        )
         (call_expression
-            (call_suffix
-                (value_arguments
-                    (value_argument) @val
+            (value_arguments
+                .
+                (value_argument
+                    .
+                    (string_literal) @val
                 )
             )
         )
     )
 
-    ; Query 4: Matches assignments with string literals
+    ; Query 3: Matches assignments to variables, properties and indexed entries
     (assignment
-        (directly_assignable_expression
-            (simple_identifier) @key
-        )
-        (string_literal) @val
+        left: [(identifier)(navigation_expression)(index_expression)] @key
+        right: [(string_literal)(multiline_string_literal)(number_literal)] @val
     )
 
-    ; Query 5: Matches property declarations with property delegates and string literals
-    (property_declaration
-        (variable_declaration
-            (simple_identifier) @key
-        ) 
-        (property_delegate
-            (_
-                (call_suffix
-                    (_
-                        (_
-                            (_
-                                (string_literal) @val
-                            )
-                        )
-                    )
-                )
-            )
-        )
+    ; Query 4: Matches named call arguments
+    (value_argument
+        (identifier) @key
+        .
+        (string_literal) @val
     )
 
-    ; Query 6: Matches secondary constructor assignments with string literals
-    (secondary_constructor
-        (statements
-            (assignment
-                (directly_assignable_expression
-                    (navigation_suffix
-                        (simple_identifier) @key
-                    )
-                )
-                (string_literal) @val
-            )
-        )
+    ; Query 5: Matches `"key" to "value"` pairs in map builders
+    (infix_expression
+        (string_literal) @key
+        .
+        (identifier)
+        .
+        (string_literal) @val
     )
 "#;
 pub const QUERIES_PHP: &str = r#"
@@ -1010,6 +1028,40 @@ pub const QUERIES_PHP: &str = r#"
         )
     )
 "#;
+pub const QUERIES_ELIXIR: &str = r#"
+    ; Query 1: Matches match-operator bindings
+    (binary_operator
+        left: (identifier) @key
+        operator: "="
+        right: [(string)(integer)] @val
+    )
+
+    ; Query 2: Matches map entries with string or atom keys
+    (map_content
+        (binary_operator
+            left: [(string)(atom)] @key
+            operator: "=>"
+            right: [(string)(integer)] @val
+        )
+    )
+
+    ; Query 3: Matches keyword list and map pairs
+    (pair
+        key: (keyword) @key
+        value: [(string)(integer)] @val
+    )
+
+    ; Query 4: Matches module attributes
+    (unary_operator
+        operator: "@"
+        operand: (call
+            target: (identifier) @key
+            (arguments
+                [(string)(integer)] @val
+            )
+        )
+    )
+"#;
 pub const QUERIES_PYTHON: &str = r#"
     ; Query 1: Matches assignments to object attributes
     (assignment
@@ -1236,3 +1288,129 @@ pub const QUERIES_TYPESCRIPT: &str = r#"
         )
     )
 "#;
+pub const QUERIES_SWIFT: &str = r#"
+    ; Query 1: Matches let and var declarations initialised with a literal
+    (property_declaration
+        name: (pattern
+            bound_identifier: (simple_identifier) @key
+        )
+        value: [(line_string_literal)(multi_line_string_literal)(integer_literal)] @val
+    )
+
+    ; Query 2: Matches assignments to variables and properties
+    (assignment
+        target: (directly_assignable_expression) @key
+        result: [(line_string_literal)(multi_line_string_literal)(integer_literal)] @val
+    )
+
+    ; Query 3: Matches dictionary literal entries
+    (dictionary_literal
+        key: (line_string_literal) @key
+        .
+        value: [(line_string_literal)(integer_literal)] @val
+    )
+
+    ; Query 4: Matches labelled call arguments
+    (value_argument
+        name: (value_argument_label) @key
+        value: (line_string_literal) @val
+    )
+"#;
+pub const QUERIES_SCALA: &str = r#"
+    ; Query 1: Matches val and var definitions initialised with a literal
+    (val_definition
+        pattern: (identifier) @key
+        value: [(string)(integer_literal)] @val
+    )
+    (var_definition
+        pattern: (identifier) @key
+        value: [(string)(integer_literal)] @val
+    )
+
+    ; Query 2: Matches val and def definitions initialised by a call with a string argument
+    (val_definition
+        pattern: (identifier) @key
+        value: (call_expression
+            arguments: (arguments
+                .
+                (string) @val
+            )
+        )
+    )
+    (function_definition
+        name: (identifier) @key
+        body: (call_expression
+            arguments: (arguments
+                .
+                (string) @val
+            )
+        )
+    )
+
+    ; Query 3: Matches assignments and named arguments
+    (assignment_expression
+        left: [(identifier)(field_expression)] @key
+        right: [(string)(integer_literal)] @val
+    )
+
+    ; Query 4: Matches `"key" -> "value"` pairs in map literals
+    (infix_expression
+        left: (string) @key
+        right: (string) @val
+    )
+"#;
+pub const QUERIES_LUA: &str = r#"
+    ; Query 1: Matches local and global assignments
+    (assignment_statement
+        (variable_list
+            .
+            name: (_) @key
+        )
+        (expression_list
+            .
+            value: [(string)(number)] @val
+        )
+    )
+
+    ; Query 2: Matches table constructor fields
+    (field
+        name: (_) @key
+        value: [(string)(number)] @val
+    )
+"#;
+pub const QUERIES_HCL: &str = r#"
+    ; Query 1: Matches block attributes set to a literal
+    (attribute
+        (identifier) @key
+        (expression
+            (literal_value) @val
+        )
+    )
+
+    ; Query 2: Matches object elements in maps such as `tags` or `environment`
+    (object_elem
+        key: (expression) @key
+        val: (expression
+            (literal_value) @val
+        )
+    )
+"#;
+pub const QUERIES_DOCKERFILE: &str = r#"
+    ; Query 1: Matches ENV variables
+    (env_pair
+        name: (_) @key
+        value: (_) @val
+    )
+
+    ; Query 2: Matches ARG defaults
+    (arg_pair
+        name: (_) @key
+        default: (_) @val
+    )
+
+    ; Query 3: Matches LABEL values
+    (label_pair
+        key: (_) @key
+        value: (_) @val
+    )
+"#;
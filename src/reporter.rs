@@ -7,7 +7,7 @@ use anyhow::Result;
 use http::StatusCode;
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
@@ -15,10 +15,11 @@ use crate::{
     bstring_escape::Escaped,
     cli,
     cli::global::GlobalArgs,
+    decoder::Encoding,
     finding_data, findings_store,
     matcher::Match,
     origin::{Origin, OriginSet},
-    rules::rule::Confidence,
+    rules::rule::{Confidence, Rule},
 };
 mod bson_format;
 mod json_format;
@@ -427,21 +428,6 @@ impl DetailsReporter {
         )
         .to_string();
 
-        let validation_status = if rm.validation_success {
-            "Active Credential".to_string()
-        } else if rm.validation_response_status == StatusCode::CONTINUE.as_u16() {
-            "Not Attempted".to_string()
-        } else {
-            "Inactive Credential".to_string()
-        };
-
-        const MAX_RESPONSE_LENGTH: usize = 512;
-        let truncated_body: String =
-            rm.validation_response_body.chars().take(MAX_RESPONSE_LENGTH).collect();
-        let ellipsis =
-            if rm.validation_response_body.len() > MAX_RESPONSE_LENGTH { "..." } else { "" };
-        let response_body = format!("{}{}", truncated_body, ellipsis);
-
         let git_metadata_val = rm
             .origin
             .iter()
@@ -478,8 +464,15 @@ impl DetailsReporter {
                 snippet,
                 fingerprint: rm.m.finding_fingerprint.to_string(),
                 confidence: rm.m.rule.confidence().to_string(),
+                severity: rm.m.rule.severity().map(|s| s.to_string()),
+                tags: rm.m.rule.tags().to_vec(),
                 entropy: format!("{:.2}", rm.m.calculated_entropy),
-                validation: ValidationInfo { status: validation_status, response: response_body },
+                validation: ValidationInfo::for_rule(
+                    &rm.m.rule,
+                    rm.validation_success,
+                    rm.validation_response_status,
+                    &rm.validation_response_body,
+                ),
                 language: rm
                     .blob_metadata
                     .language
@@ -489,8 +482,40 @@ impl DetailsReporter {
                 column_start: source_span.start.column as u32,
                 column_end: source_span.end.column as u32,
                 path: file_path,
-                encoding: if rm.m.is_base64 { Some("base64".to_string()) } else { None },
+                encoding: rm
+                    .m
+                    .encoding
+                    .or(rm.m.is_base64.then_some(Encoding::Base64))
+                    .map(|e| e.to_string()),
                 git_metadata: git_metadata_val,
+                parts: rm
+                    .m
+                    .parts
+                    .iter()
+                    .map(|part| FindingPart {
+                        rule_id: part.rule_id.clone(),
+                        snippet: Escaped(part.value.as_bytes()).to_string(),
+                        line: part.location.source_span.start.line as u32,
+                        column_start: part.location.source_span.start.column as u32,
+                        column_end: part.location.source_span.end.column as u32,
+                    })
+                    .collect(),
+                paired_dependencies: rm
+                    .m
+                    .paired_dependencies
+                    .iter()
+                    .map(|dep| FindingDependency {
+                        variable: dep.variable.clone(),
+                        path: dep.path.clone(),
+                        part: FindingPart {
+                            rule_id: dep.rule_id.clone(),
+                            snippet: Escaped(dep.value.as_bytes()).to_string(),
+                            line: dep.location.source_span.start.line as u32,
+                            column_start: dep.location.source_span.start.column as u32,
+                            column_end: dep.location.source_span.end.column as u32,
+                        },
+                    })
+                    .collect(),
             },
         }
     }
@@ -648,29 +673,72 @@ pub struct ReportMatch {
     pub validation_success: bool,
 }
 
-#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct FindingReporterRecord {
     pub rule: RuleMetadata,
     pub finding: FindingRecordData,
 }
 
-#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct RuleMetadata {
     pub name: String,
     pub id: String,
 }
 
-#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct ValidationInfo {
     pub status: String,
     pub response: String,
+
+    /// Status from the report this finding was re-validated from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<String>,
+
+    /// Whether re-validation changed the status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed: Option<bool>,
+}
+
+impl ValidationInfo {
+    pub fn new(success: bool, status: u16, body: &str) -> Self {
+        let status = if success {
+            "Active Credential".to_string()
+        } else if status == StatusCode::CONTINUE.as_u16() {
+            "Not Attempted".to_string()
+        } else {
+            "Inactive Credential".to_string()
+        };
+
+        const MAX_RESPONSE_LENGTH: usize = 512;
+        let truncated_body: String = body.chars().take(MAX_RESPONSE_LENGTH).collect();
+        let ellipsis = if body.len() > MAX_RESPONSE_LENGTH { "..." } else { "" };
+        let response = format!("{}{}", truncated_body, ellipsis);
+
+        Self { status, response, previous_status: None, changed: None }
+    }
+
+    /// Like [`ValidationInfo::new`], but findings of rules with an offline
+    /// checksum are reported as structurally valid when no network validation
+    /// ran. Matches with a bad checksum are dropped by the matcher, so every
+    /// such finding has passed the check.
+    pub fn for_rule(rule: &Rule, success: bool, status: u16, body: &str) -> Self {
+        let mut info = Self::new(success, status, body);
+        if status == StatusCode::CONTINUE.as_u16() && rule.syntax().checksum.is_some() {
+            info.status = "Structurally Valid".to_string();
+        }
+        info
+    }
 }
 
-#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct FindingRecordData {
     pub snippet: String,
     pub fingerprint: String,
     pub confidence: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub entropy: String,
     pub validation: ValidationInfo,
     pub language: String,
@@ -682,6 +750,30 @@ pub struct FindingRecordData {
     pub encoding: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub git_metadata: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<FindingPart>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paired_dependencies: Vec<FindingDependency>,
+}
+
+/// One required part of a multi-part finding (see the rule `requires` clause).
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct FindingPart {
+    pub rule_id: String,
+    pub snippet: String,
+    pub line: u32,
+    pub column_start: u32,
+    pub column_end: u32,
+}
+
+/// A dependency value that validation took from another file
+/// (`--cross-file-dependencies`).
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct FindingDependency {
+    pub variable: String,
+    pub path: String,
+    #[serde(flatten)]
+    pub part: FindingPart,
 }
 
 #[cfg(test)]
@@ -787,9 +879,11 @@ mod tests {
                 no_extract_archives: false,
                 extraction_depth: 2,
                 no_binary: false,
+                stream_large_files: false,
             },
             confidence: ConfidenceLevel::Medium,
             no_validate: false,
+            cross_file_dependencies: false,
             only_valid: false,
             min_entropy: None,
             rule_stats: false,
@@ -798,10 +892,19 @@ mod tests {
             no_base64: false,
             git_repo_timeout: 1_800,
             output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+            datastore: None,
+            resume: None,
+            blob_cache: None,
             baseline_file: None,
             manage_baseline: false,
             skip_regex: Vec::new(),
             skip_word: Vec::new(),
+            allowlist: None,
+            safe_list: Vec::new(),
+            disable_safe_rule: Vec::new(),
+            report_suppressed: false,
+            decode: Vec::new(),
+            no_decode: Vec::new(),
             skip_aws_account: Vec::new(),
             skip_aws_account_file: None,
             no_inline_ignore: false,
@@ -838,6 +941,7 @@ mod tests {
             references: vec![],
             validation: None,
             depends_on_rule: vec![],
+            ..Default::default()
         }));
 
         let blob_id = BlobId::new(b"blob-data");
@@ -870,6 +974,9 @@ mod tests {
                 calculated_entropy: 5.29,
                 visible: true,
                 is_base64: false,
+                encoding: None,
+                parts: Vec::new(),
+                paired_dependencies: Vec::new(),
             },
             comment: None,
             match_confidence: Confidence::Medium,
@@ -929,6 +1036,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn checksum_rules_report_structurally_valid_when_not_validated() {
+        let rule = Rule::new(
+            serde_yaml::from_str::<RuleSyntax>(
+                "name: n\nid: test.1\npattern: x\nchecksum: {algorithm: crc32-base62, skip: 4}\n",
+            )
+            .unwrap(),
+        );
+        let continue_status = StatusCode::CONTINUE.as_u16();
+        assert_eq!(
+            ValidationInfo::for_rule(&rule, false, continue_status, "").status,
+            "Structurally Valid"
+        );
+        assert_eq!(ValidationInfo::for_rule(&rule, false, 401, "").status, "Inactive Credential");
+        assert_eq!(ValidationInfo::for_rule(&rule, true, 200, "").status, "Active Credential");
+    }
+
     use super::build_git_urls;
 
     #[test]
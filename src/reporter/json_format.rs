@@ -70,6 +70,10 @@ mod tests {
                 rules_path: Vec::new(),
                 rule: vec!["all".into()],
                 load_builtins: true,
+                rule_tag: vec![],
+                exclude_rule_tag: vec![],
+                rules_url: vec![],
+                rules_public_key: None,
             },
             input_specifier_args: InputSpecifierArgs {
                 // local path / git URL inputs
@@ -161,19 +165,30 @@ mod tests {
                 extraction_depth: 2,
                 exclude: Vec::new(), // Exclude patterns
                 no_binary: true,
+                stream_large_files: false,
             },
             confidence: ConfidenceLevel::Medium,
             no_validate: false,
+            cross_file_dependencies: false,
             rule_stats: false,
             only_valid: false,
             min_entropy: None,
             redact: false,
             git_repo_timeout: 1800, // 30 minutes
             output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+            datastore: None,
+            resume: None,
+            blob_cache: None,
             baseline_file: None,
             manage_baseline: false,
             skip_regex: Vec::new(),
             skip_word: Vec::new(),
+            allowlist: None,
+            safe_list: Vec::new(),
+            disable_safe_rule: Vec::new(),
+            report_suppressed: false,
+            decode: Vec::new(),
+            no_decode: Vec::new(),
             skip_aws_account: Vec::new(),
             skip_aws_account_file: None,
             no_base64: false,
@@ -194,6 +209,7 @@ mod tests {
             references: vec![],
             validation: None,
             depends_on_rule: vec![],
+            ..Default::default()
         };
         let rule = Arc::new(Rule::new(syntax));
         Match {
@@ -222,6 +238,9 @@ mod tests {
             calculated_entropy: 4.5,
             visible: true,
             is_base64: false,
+            encoding: None,
+            parts: Vec::new(),
+            paired_dependencies: Vec::new(),
         }
     }
 
@@ -103,8 +103,34 @@ impl<'a> Display for PrettyFindingRecord<'a> {
         if let Some(enc) = &finding.encoding {
             writeln!(f, " |Encoding.....: {}", enc)?;
         }
+        for part in &finding.parts {
+            writeln!(
+                f,
+                " |__Part........: {} ({}, line {})",
+                style_fn(&part.snippet),
+                part.rule_id,
+                part.line
+            )?;
+        }
+        for dep in &finding.paired_dependencies {
+            writeln!(
+                f,
+                " |__Paired......: {}={} ({}, {}:{})",
+                dep.variable,
+                style_fn(&dep.part.snippet),
+                dep.part.rule_id,
+                dep.path,
+                dep.part.line
+            )?;
+        }
         writeln!(f, " |Fingerprint...: {}", finding.fingerprint)?;
         writeln!(f, " |Confidence....: {}", finding.confidence)?;
+        if let Some(severity) = &finding.severity {
+            writeln!(f, " |Severity......: {}", severity)?;
+        }
+        if !finding.tags.is_empty() {
+            writeln!(f, " |Tags..........: {}", finding.tags.join(", "))?;
+        }
         writeln!(f, " |Entropy.......: {}", finding.entropy)?;
         if is_active {
             writeln!(
@@ -24,6 +24,12 @@ impl DetailsReporter {
         let mut props = BTreeMap::new();
         props.insert("validation_status".to_string(), serde_json::json!(finding.validation.status));
         props.insert("entropy".to_string(), serde_json::json!(finding.entropy));
+        if let Some(severity) = &finding.severity {
+            props.insert("severity".to_string(), serde_json::json!(severity));
+        }
+        if !finding.tags.is_empty() {
+            props.insert("tags".to_string(), serde_json::json!(finding.tags));
+        }
         if let Some(git) = &finding.git_metadata {
             props.insert("git_metadata".to_string(), git.clone());
         }
@@ -44,15 +50,48 @@ impl DetailsReporter {
             .text(format!("Rule {} matched {}", record.rule.name, finding.path))
             .build()?;
 
-        let result = sarif::ResultBuilder::default()
+        let mut result = sarif::ResultBuilder::default();
+        result
             .rule_id(&record.rule.name)
             .message(message)
             .kind(sarif::ResultKind::Review.to_string())
             .locations(vec![location])
             .level(sarif::ResultLevel::Warning.to_string())
-            .partial_fingerprints([("fingerprint".to_string(), finding.fingerprint.clone())])
+            .partial_fingerprints([("fingerprint".to_string(), finding.fingerprint.clone())]);
+        let related = finding
+            .parts
+            .iter()
+            .map(|part| (finding.path.as_str(), part))
+            .chain(finding.paired_dependencies.iter().map(|dep| (dep.path.as_str(), &dep.part)))
+            .map(|(path, part)| self.part_to_sarif_location(path, part))
+            .collect::<Result<Vec<_>>>()?;
+        if !related.is_empty() {
+            result.related_locations(related);
+        }
+        Ok(result.build()?)
+    }
+
+    /// A required part of a multi-part finding, or a dependency value paired from
+    /// another file, reported as a related location.
+    fn part_to_sarif_location(&self, path: &str, part: &FindingPart) -> Result<sarif::Location> {
+        let region = sarif::RegionBuilder::default()
+            .start_line(part.line as i64)
+            .start_column(part.column_start as i64)
+            .end_line(part.line as i64)
+            .end_column(part.column_end as i64)
+            .snippet(sarif::ArtifactContentBuilder::default().text(part.snippet.clone()).build()?)
             .build()?;
-        Ok(result)
+        Ok(sarif::LocationBuilder::default()
+            .physical_location(
+                sarif::PhysicalLocationBuilder::default()
+                    .artifact_location(
+                        sarif::ArtifactLocationBuilder::default().uri(path.to_string()).build()?,
+                    )
+                    .region(region)
+                    .build()?,
+            )
+            .message(sarif::MessageBuilder::default().text(part.rule_id.clone()).build()?)
+            .build()?)
     }
 
     pub fn sarif_format<W: std::io::Write>(
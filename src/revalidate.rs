@@ -0,0 +1,508 @@
+//! Validation outside of a scan: re-validating the findings in an earlier
+//! JSON or JSONL report, and validating secrets given on the command line.
+//!
+//! Each report record is turned back into a match: the snippet is the secret,
+//! the rule is looked up by ID, and findings reported for the same file are
+//! grouped into one blob so that rules depending on each other still see
+//! their companions. The matches then go through the regular validation
+//! phase, and the records are written out again with their new status.
+
+use std::{
+    io::{IsTerminal, Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use crossbeam_skiplist::SkipMap;
+use http::StatusCode;
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use smallvec::smallvec;
+use tracing::{info, warn};
+
+use crate::{
+    blob::{BlobId, BlobMetadata},
+    cli::{
+        commands::validate::{
+            ValidateArgs, ValidateReportFormat, ValidateSecretArgs, ValidateSecretFormat,
+        },
+        global::GlobalArgs,
+    },
+    decoder::Encoding,
+    findings_store::{FindingsStore, FindingsStoreMessage},
+    liquid_filters::register_all,
+    location::{Location, OffsetSpan, SourcePoint, SourceSpan},
+    matcher::{Match, OwnedBlobMatch, SerializableCapture, SerializableCaptures},
+    origin::{Origin, OriginSet},
+    reporter::{FindingReporterRecord, RuleMetadata, ValidationInfo},
+    rule_loader::RuleLoader,
+    rules::rule::{Confidence, Rule},
+    scanner::run_secret_validation,
+    util::{get_writer_for_file_or_stdout, intern},
+    validation::validate_single_match,
+};
+
+/// Reports only record lines and columns, so offsets are synthesized from
+/// them; this keeps findings on nearby lines close together.
+const LINE_STRIDE: usize = 100_000;
+
+/// Counts from one re-validation run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RevalidationSummary {
+    pub validated: usize,
+    pub changed: usize,
+    /// Findings left as reported because their rule is not loaded.
+    pub unknown_rule: usize,
+    /// Findings left as reported because the report was written with `--redact`.
+    pub redacted: usize,
+}
+
+/// `kingfisher validate`
+pub async fn run_validate(global_args: &GlobalArgs, args: &ValidateArgs) -> Result<()> {
+    let text = if args.report.as_os_str() == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).context("Failed to read report from stdin")?;
+        text
+    } else {
+        std::fs::read_to_string(&args.report)
+            .with_context(|| format!("Failed to read report {}", args.report.display()))?
+    };
+    let (mut records, input_format) = parse_report(&text)?;
+
+    let loaded =
+        RuleLoader::from_rule_specifiers(&args.rules).load_with_confidence(Confidence::Low)?;
+    let rules: Vec<Arc<Rule>> =
+        loaded.resolve_enabled_rules()?.into_iter().map(|r| Arc::new(r.clone())).collect();
+
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(global_args.ignore_certs)
+        .timeout(Duration::from_secs(30))
+        .build()?;
+    let summary = revalidate_records(&mut records, &rules, &client, args.num_jobs).await?;
+
+    info!(
+        "Re-validated {} findings; {} changed status since the report was written",
+        summary.validated, summary.changed
+    );
+    if summary.unknown_rule > 0 {
+        warn!("Skipped {} findings whose rules are not loaded", summary.unknown_rule);
+    }
+    if summary.redacted > 0 {
+        warn!("Skipped {} findings with redacted secrets", summary.redacted);
+    }
+
+    if args.only_changed {
+        records.retain(|r| r.finding.validation.changed == Some(true));
+    }
+    let writer = get_writer_for_file_or_stdout(args.output.as_ref())?;
+    write_report(writer, &records, args.format.unwrap_or(input_format))
+}
+
+/// Parse a report written with `--format json` or `--format jsonl`.
+pub fn parse_report(text: &str) -> Result<(Vec<FindingReporterRecord>, ValidateReportFormat)> {
+    let trimmed = text.trim_start();
+    if trimmed.is_empty() {
+        // `--format json` writes nothing at all when there are no findings
+        return Ok((Vec::new(), ValidateReportFormat::Json));
+    }
+    if trimmed.starts_with('[') {
+        let records = serde_json::from_str(text).context("Failed to parse JSON report")?;
+        return Ok((records, ValidateReportFormat::Json));
+    }
+    let records = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Failed to parse JSONL report at line {}", i + 1))
+        })
+        .collect::<Result<_>>()?;
+    Ok((records, ValidateReportFormat::Jsonl))
+}
+
+pub fn write_report<W: Write>(
+    mut writer: W,
+    records: &[FindingReporterRecord],
+    format: ValidateReportFormat,
+) -> Result<()> {
+    match format {
+        ValidateReportFormat::Json => {
+            if !records.is_empty() {
+                serde_json::to_writer_pretty(&mut writer, records)?;
+                writeln!(writer)?;
+            }
+        }
+        ValidateReportFormat::Jsonl => {
+            for record in records {
+                serde_json::to_writer(&mut writer, record)?;
+                writeln!(writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate every record again and update its status in place.
+///
+/// Records that were re-validated get `previous_status` and `changed` set;
+/// records that could not be (unknown rule, redacted secret) are left as is.
+pub async fn revalidate_records(
+    records: &mut [FindingReporterRecord],
+    rules: &[Arc<Rule>],
+    client: &reqwest::Client,
+    num_jobs: usize,
+) -> Result<RevalidationSummary> {
+    let rules_by_id: FxHashMap<&str, &Arc<Rule>> = rules.iter().map(|r| (r.id(), r)).collect();
+    let mut summary = RevalidationSummary::default();
+
+    let mut messages = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let Some(rule) = rules_by_id.get(record.rule.id.as_str()) else {
+            summary.unknown_rule += 1;
+            continue;
+        };
+        if record.finding.snippet.starts_with("[REDACTED:") {
+            summary.redacted += 1;
+            continue;
+        }
+        messages.push(Arc::new(to_store_message(index, record, Arc::clone(rule))));
+    }
+    if messages.is_empty() {
+        return Ok(summary);
+    }
+
+    let temp_dir = tempfile::tempdir().context("Failed to create temporary directory")?;
+    let mut store = FindingsStore::new(temp_dir.path().to_path_buf());
+    store.record_rules(rules);
+    store.replace_matches(messages);
+    let datastore = Arc::new(Mutex::new(store));
+
+    let parser = register_all(liquid::ParserBuilder::with_stdlib()).build()?;
+    let cache = Arc::new(SkipMap::new());
+    run_secret_validation(Arc::clone(&datastore), &parser, client, &cache, num_jobs, false).await?;
+
+    let ds = datastore.lock().unwrap();
+    for msg in ds.get_matches() {
+        let m = &msg.2;
+        // The fingerprint of a rebuilt match is the index of its record
+        let index = m.finding_fingerprint as usize;
+        let Some(record) = records.get_mut(index) else {
+            bail!("Validation returned an unknown finding");
+        };
+        let mut validation = ValidationInfo::for_rule(
+            &m.rule,
+            m.validation_success,
+            m.validation_response_status,
+            &m.validation_response_body,
+        );
+        let previous = std::mem::take(&mut record.finding.validation.status);
+        validation.changed = Some(validation.status != previous);
+        validation.previous_status = Some(previous);
+        summary.validated += 1;
+        if validation.changed == Some(true) {
+            summary.changed += 1;
+        }
+        record.finding.validation = validation;
+    }
+    Ok(summary)
+}
+
+fn to_store_message(
+    index: usize,
+    record: &FindingReporterRecord,
+    rule: Arc<Rule>,
+) -> FindingsStoreMessage {
+    let finding = &record.finding;
+
+    // Findings from the same file (and commit) share a blob, as they did when scanned
+    let commit = finding
+        .git_metadata
+        .as_ref()
+        .and_then(|git| git.pointer("/commit/id"))
+        .and_then(|id| id.as_str())
+        .unwrap_or_default();
+    let blob_id = BlobId::compute_from_bytes(format!("{commit}:{}", finding.path).as_bytes());
+
+    let line = finding.line as usize;
+    let start = line * LINE_STRIDE + finding.column_start as usize;
+    let groups = rebuild_captures(&rule, &finding.snippet, start);
+    let location = Location {
+        offset_span: OffsetSpan { start, end: start + finding.snippet.len() },
+        source_span: SourceSpan {
+            start: SourcePoint { line, column: finding.column_start as usize },
+            end: SourcePoint { line, column: finding.column_end as usize },
+        },
+    };
+
+    let origin = OriginSet::new(Origin::from_file(PathBuf::from(&finding.path)), Vec::new());
+    let blob_metadata = BlobMetadata {
+        id: blob_id,
+        num_bytes: 0,
+        mime_essence: None,
+        language: Some(finding.language.clone()),
+    };
+    let encoding = finding.encoding.as_deref().and_then(|e| e.parse::<Encoding>().ok());
+    let m = Match {
+        location,
+        groups,
+        blob_id,
+        finding_fingerprint: index as u64,
+        rule,
+        validation_response_body: String::new(),
+        validation_response_status: StatusCode::CONTINUE.as_u16(),
+        validation_success: false,
+        calculated_entropy: finding.entropy.parse().unwrap_or_default(),
+        visible: true,
+        is_base64: encoding == Some(Encoding::Base64),
+        encoding,
+        parts: Vec::new(),
+        paired_dependencies: Vec::new(),
+    };
+    (Arc::new(origin), Arc::new(blob_metadata), m)
+}
+
+/// Result of `kingfisher validate-secret` for one secret.
+#[derive(Serialize, Debug, Clone)]
+pub struct SecretValidationRecord {
+    pub rule: RuleMetadata,
+    pub secret: String,
+    pub status_code: u16,
+    pub validation: ValidationInfo,
+}
+
+/// `kingfisher validate-secret`
+pub async fn run_validate_secret(
+    global_args: &GlobalArgs,
+    args: &ValidateSecretArgs,
+) -> Result<()> {
+    let rule_id = match args.rules.rule.as_slice() {
+        [id] if id != "all" => id,
+        _ => bail!("Pass the ID of the rule to validate with exactly one --rule"),
+    };
+    let loaded =
+        RuleLoader::from_rule_specifiers(&args.rules).load_with_confidence(Confidence::Low)?;
+    let Some(rule) = loaded.iter_rules().find(|r| r.id() == rule_id) else {
+        bail!("Unknown rule `{}`; run `kingfisher rules list` to see rule IDs", rule_id);
+    };
+    let rule = Arc::new(rule.clone());
+
+    let secrets: Vec<String> = match &args.value {
+        Some(value) => vec![value.clone()],
+        None => {
+            let stdin = std::io::stdin();
+            if stdin.is_terminal() {
+                bail!("Pass the secret with --value or pipe one secret per line on stdin");
+            }
+            let mut text = String::new();
+            stdin.lock().read_to_string(&mut text).context("Failed to read secrets from stdin")?;
+            text.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect()
+        }
+    };
+
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(global_args.ignore_certs)
+        .timeout(Duration::from_secs(30))
+        .build()?;
+    let mut writer = std::io::stdout().lock();
+    for secret in secrets {
+        let om = validate_secret(&rule, &secret, &args.vars, &client).await?;
+        let record = SecretValidationRecord {
+            rule: RuleMetadata { name: rule.name().to_string(), id: rule.id().to_string() },
+            secret,
+            status_code: om.validation_response_status.as_u16(),
+            validation: ValidationInfo::new(
+                om.validation_success,
+                om.validation_response_status.as_u16(),
+                &om.validation_response_body,
+            ),
+        };
+        match args.format {
+            ValidateSecretFormat::Pretty => {
+                writeln!(writer, "{} => [{}]", record.rule.name, record.rule.id)?;
+                writeln!(writer, " |Secret........: {}", record.secret)?;
+                writeln!(
+                    writer,
+                    " |Validation....: {} ({})",
+                    record.validation.status, record.status_code
+                )?;
+                if !record.validation.response.is_empty() {
+                    writeln!(writer, " |__Response....: {}", record.validation.response)?;
+                }
+                writeln!(writer)?;
+            }
+            ValidateSecretFormat::Jsonl => {
+                serde_json::to_writer(&mut writer, &record)?;
+                writeln!(writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run `rule`'s validation against a single secret.
+///
+/// `vars` supplies the variables the rule's `depends_on_rule` entries would
+/// normally take from other findings in the same file; each one is required.
+pub async fn validate_secret(
+    rule: &Arc<Rule>,
+    secret: &str,
+    vars: &[(String, String)],
+    client: &reqwest::Client,
+) -> Result<OwnedBlobMatch> {
+    let syntax = rule.syntax();
+    if syntax.validation.is_none() {
+        bail!("Rule `{}` has no validation configured", rule.id());
+    }
+
+    let mut dependent_variables: FxHashMap<String, Vec<(String, OffsetSpan)>> =
+        FxHashMap::default();
+    for (name, value) in vars {
+        let name = name.to_uppercase();
+        if !syntax.depends_on_rule.iter().flatten().any(|d| d.variable.to_uppercase() == name) {
+            warn!("Rule `{}` does not use variable {name}; ignoring it", rule.id());
+        }
+        dependent_variables
+            .entry(name)
+            .or_default()
+            .push((value.clone(), OffsetSpan { start: 0, end: value.len() }));
+    }
+    for dep in syntax.depends_on_rule.iter().flatten() {
+        if !dependent_variables.contains_key(&dep.variable.to_uppercase()) {
+            bail!(
+                "Rule `{}` needs --var {}=<value> (normally taken from a `{}` finding)",
+                rule.id(),
+                dep.variable.to_uppercase(),
+                dep.rule_id
+            );
+        }
+    }
+
+    let mut om = OwnedBlobMatch {
+        rule: Arc::clone(rule),
+        blob_id: BlobId::compute_from_bytes(secret.as_bytes()),
+        finding_fingerprint: 0,
+        matching_input_offset_span: OffsetSpan { start: 0, end: secret.len() },
+        captures: rebuild_captures(rule, secret, 0),
+        validation_response_body: String::new(),
+        validation_response_status: StatusCode::CONTINUE,
+        validation_success: false,
+        calculated_entropy: 0.0,
+        encoding: None,
+        parts: Vec::new(),
+        visible: rule.visible(),
+        paired_dependencies: Vec::new(),
+    };
+    let parser = register_all(liquid::ParserBuilder::with_stdlib()).build()?;
+    let cache = Arc::new(SkipMap::new());
+    validate_single_match(
+        &mut om,
+        &parser,
+        client,
+        &dependent_variables,
+        &FxHashMap::default(),
+        &cache,
+    )
+    .await;
+    Ok(om)
+}
+
+/// Rebuild the capture groups of a finding from its reported snippet.
+///
+/// The snippet is the rule's first capture, or the whole match for rules with
+/// named groups, so running the rule's pattern over it usually recovers every
+/// group. When it doesn't reproduce the snippet, the snippet becomes `TOKEN`.
+pub fn rebuild_captures(rule: &Rule, snippet: &str, offset: usize) -> SerializableCaptures {
+    if let Ok(re) = rule.syntax().as_regex() {
+        if let Some(caps) = re.captures(snippet.as_bytes()) {
+            let mut groups =
+                SerializableCaptures::from_captures(&caps, snippet.as_bytes(), &re, false, None);
+            let reproduced = groups
+                .captures
+                .get(1)
+                .or_else(|| groups.captures.first())
+                .is_some_and(|c| c.value == snippet);
+            if reproduced {
+                for c in groups.captures.iter_mut() {
+                    c.start += offset;
+                    c.end += offset;
+                }
+                return groups;
+            }
+        }
+    }
+    let capture = |match_number| SerializableCapture {
+        name: None,
+        match_number,
+        start: offset,
+        end: offset + snippet.len(),
+        value: intern(snippet),
+    };
+    SerializableCaptures { captures: smallvec![capture(0), capture(1)] }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::rule::RuleSyntax;
+
+    fn rule(pattern: &str) -> Rule {
+        Rule::new(RuleSyntax {
+            name: "Test Rule".into(),
+            id: "test.rule".into(),
+            pattern: pattern.into(),
+            min_entropy: 0.0,
+            confidence: Confidence::Medium,
+            visible: true,
+            examples: vec![],
+            negative_examples: vec![],
+            references: vec![],
+            validation: None,
+            depends_on_rule: vec![],
+            ..Default::default()
+        })
+    }
+
+    fn named(groups: &SerializableCaptures, name: &str) -> Option<(&'static str, usize)> {
+        groups.captures.iter().find(|c| c.name.as_deref() == Some(name)).map(|c| (c.value, c.start))
+    }
+
+    #[test]
+    fn named_groups_are_recovered_from_whole_match_snippet() {
+        let rule = rule(r"(?P<USER>u_[a-z]+):(?P<PASS>p_[a-z]+)");
+        let groups = rebuild_captures(&rule, "u_alice:p_secret", 10);
+        assert_eq!(named(&groups, "USER"), Some(("u_alice", 10)));
+        assert_eq!(named(&groups, "PASS"), Some(("p_secret", 18)));
+    }
+
+    #[test]
+    fn unmatched_snippet_becomes_token() {
+        let rule = rule(r"api_key\s*=\s*([a-z0-9]{8})");
+        let groups = rebuild_captures(&rule, "abcd1234", 0);
+        assert_eq!(groups.captures.len(), 2);
+        assert!(groups.captures.iter().all(|c| c.name.is_none() && c.value == "abcd1234"));
+    }
+
+    #[test]
+    fn parses_json_and_jsonl_reports() -> Result<()> {
+        let record = r#"{"rule":{"name":"Test","id":"test.1"},"finding":{"snippet":"abc","fingerprint":"1","confidence":"medium","entropy":"1.58","validation":{"status":"Not Attempted","response":""},"language":"Unknown","line":3,"column_start":1,"column_end":4,"path":"a.txt"}}"#;
+
+        let (records, format) = parse_report(&format!("[{record}]"))?;
+        assert_eq!((records.len(), format), (1, ValidateReportFormat::Json));
+
+        let (records, format) = parse_report(&format!("{record}\n\n{record}\n"))?;
+        assert_eq!((records.len(), format), (2, ValidateReportFormat::Jsonl));
+        assert_eq!(records[1].finding.line, 3);
+
+        let mut out = Vec::new();
+        write_report(&mut out, &records, ValidateReportFormat::Jsonl)?;
+        let text = String::from_utf8(out)?;
+        assert!(!text.contains("previous_status"));
+        assert_eq!(parse_report(&text)?.0.len(), 2);
+
+        assert!(parse_report("{not json}\n").is_err());
+        Ok(())
+    }
+}
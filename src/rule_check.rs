@@ -0,0 +1,285 @@
+//! Checks behind `kingfisher rules check`.
+//!
+//! Every `examples` and `negative_examples` entry is scanned with the same
+//! [`Matcher`] pipeline a real scan uses (Vectorscan prefilter, regex, entropy,
+//! safe-list and inline-ignore filtering), so a rule only passes if its
+//! examples would actually be reported.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{
+    blob::{Blob, BlobIdMap},
+    matcher::{Matcher, ScanResult},
+    origin::{Origin, OriginSet},
+    rules::rule::Rule,
+    rules_database::RulesDatabase,
+    scanner_pool::ScannerPool,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    ShortName,
+    ShortPattern,
+    NoExamples,
+    ExampleNotReported,
+    NegativeExampleReported,
+}
+
+/// Which stages of the pipeline matched a single example.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExampleOutcome {
+    /// The Vectorscan prefilter fired for the rule.
+    pub vectorscan: bool,
+    /// The rule's regex matched the example.
+    pub regex: bool,
+    /// The full pipeline reported a finding for the rule.
+    pub finding: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RuleCheckIssue {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub severity: IssueSeverity,
+    pub kind: IssueKind,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<ExampleOutcome>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RulesCheckReport {
+    pub rules_checked: usize,
+    pub examples_checked: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<RuleCheckIssue>,
+}
+
+impl RulesCheckReport {
+    fn push(
+        &mut self,
+        rule: &Rule,
+        severity: IssueSeverity,
+        kind: IssueKind,
+        message: String,
+        example: Option<(&str, ExampleOutcome)>,
+    ) {
+        match severity {
+            IssueSeverity::Error => self.errors += 1,
+            IssueSeverity::Warning => self.warnings += 1,
+        }
+        self.issues.push(RuleCheckIssue {
+            rule_id: rule.id().to_string(),
+            rule_name: rule.name().to_string(),
+            severity,
+            kind,
+            message,
+            example: example.map(|(e, _)| e.to_string()),
+            outcome: example.map(|(_, o)| o),
+        });
+    }
+}
+
+/// Check the given rules and their examples.
+///
+/// Path and language scoping is ignored: examples are not tied to a file.
+pub fn check_rules(rules: Vec<Rule>) -> Result<RulesCheckReport> {
+    let rules = rules.into_iter().map(without_scope).collect();
+    let rules_db = RulesDatabase::from_rules(rules)?;
+    let seen = BlobIdMap::new();
+    let scanner_pool = Arc::new(ScannerPool::new(Arc::new(rules_db.vsdb.clone())));
+    let mut matcher = Matcher::new(&rules_db, scanner_pool, &seen, None, false, None, &[], false)?;
+
+    let mut report = RulesCheckReport::default();
+    for (rule_index, rule) in rules_db.rules().iter().enumerate() {
+        let syntax = rule.syntax();
+        report.rules_checked += 1;
+        if rule.name().len() < 3 {
+            report.push(
+                rule,
+                IssueSeverity::Warning,
+                IssueKind::ShortName,
+                "Rule has a very short name".into(),
+                None,
+            );
+        }
+        if syntax.pattern.len() < 5 {
+            report.push(
+                rule,
+                IssueSeverity::Warning,
+                IssueKind::ShortPattern,
+                "Rule has a very short pattern".into(),
+                None,
+            );
+        }
+        if syntax.examples.is_empty() {
+            report.push(
+                rule,
+                IssueSeverity::Warning,
+                IssueKind::NoExamples,
+                "Rule has no examples".into(),
+                None,
+            );
+        }
+        for example in &syntax.examples {
+            report.examples_checked += 1;
+            let outcome = check_example(&mut matcher, &rules_db, rule_index, example)?;
+            if !outcome.finding {
+                let message = if !outcome.vectorscan {
+                    "Example was not matched by the Vectorscan prefilter"
+                } else if !outcome.regex {
+                    "Example matched Vectorscan but not the rule's regex"
+                } else {
+                    "Example matched but was dropped by entropy, checksum, safe-list or inline-ignore filtering"
+                };
+                report.push(
+                    rule,
+                    IssueSeverity::Error,
+                    IssueKind::ExampleNotReported,
+                    message.into(),
+                    Some((example, outcome)),
+                );
+            }
+        }
+        for example in &syntax.negative_examples {
+            report.examples_checked += 1;
+            let outcome = check_example(&mut matcher, &rules_db, rule_index, example)?;
+            if outcome.finding {
+                report.push(
+                    rule,
+                    IssueSeverity::Error,
+                    IssueKind::NegativeExampleReported,
+                    "Negative example produced a finding".into(),
+                    Some((example, outcome)),
+                );
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Drops a rule's path and language scoping so it can be run against snippets
+/// that are not tied to a file.
+pub(crate) fn without_scope(rule: Rule) -> Rule {
+    let mut syntax = rule.syntax;
+    syntax.include_paths.clear();
+    syntax.exclude_paths.clear();
+    syntax.languages.clear();
+    Rule::new(syntax)
+}
+
+fn check_example(
+    matcher: &mut Matcher<'_>,
+    rules_db: &RulesDatabase,
+    rule_index: usize,
+    example: &str,
+) -> Result<ExampleOutcome> {
+    let bytes = example.as_bytes();
+    let vectorscan = matcher.prefilter_rule_ids(bytes)?.contains(&rule_index);
+    let regex = rules_db.anchored_regexes[rule_index].is_match(bytes);
+
+    let rule_id = rules_db.rules[rule_index].id();
+    let blob = Blob::from_bytes(bytes.to_vec());
+    let origin = OriginSet::from(Origin::from_file(PathBuf::from("example")));
+    let finding = match matcher.scan_blob(&blob, &origin, None, false, true, false)? {
+        ScanResult::New(matches) => matches.iter().any(|m| m.rule.id() == rule_id),
+        _ => false,
+    };
+    Ok(ExampleOutcome { vectorscan, regex, finding })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::rule::RuleSyntax;
+
+    fn rule(yaml: &str) -> Rule {
+        Rule::new(serde_yaml::from_str::<RuleSyntax>(yaml).unwrap())
+    }
+
+    #[test]
+    fn reports_missed_examples_and_matched_negative_examples() -> Result<()> {
+        let rules = vec![
+            rule(
+                r#"
+name: Good rule
+id: test.good.1
+pattern: 'goodtoken_([a-z0-9]{12})'
+min_entropy: 2.0
+examples: ['goodtoken_a1b2c3d4e5f6']
+negative_examples: ['goodtoken_aaaa']
+"#,
+            ),
+            rule(
+                r#"
+name: Bad rule
+id: test.bad.1
+pattern: 'badtoken_([a-z0-9]{12})'
+min_entropy: 2.0
+examples: ['badtoken_aaaaaaaaaaaa', 'nothing here']
+negative_examples: ['badtoken_q8w7e6r5t4y3']
+"#,
+            ),
+        ];
+        let report = check_rules(rules)?;
+        assert_eq!(report.rules_checked, 2);
+        assert_eq!(report.examples_checked, 6);
+        assert_eq!(report.errors, 3);
+
+        let kinds: Vec<_> = report
+            .issues
+            .iter()
+            .filter(|i| i.rule_id == "test.bad.1")
+            .map(|i| (i.kind, i.outcome.unwrap()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    IssueKind::ExampleNotReported,
+                    ExampleOutcome { vectorscan: true, regex: true, finding: false }
+                ),
+                (
+                    IssueKind::ExampleNotReported,
+                    ExampleOutcome { vectorscan: false, regex: false, finding: false }
+                ),
+                (
+                    IssueKind::NegativeExampleReported,
+                    ExampleOutcome { vectorscan: true, regex: true, finding: true }
+                ),
+            ]
+        );
+        assert!(report.issues.iter().all(|i| i.rule_id != "test.good.1"));
+        Ok(())
+    }
+
+    #[test]
+    fn scoped_rules_are_checked_without_their_scope() -> Result<()> {
+        let report = check_rules(vec![rule(
+            r#"
+name: Scoped rule
+id: test.scoped.1
+pattern: 'scopedtoken_([a-z0-9]{12})'
+include_paths: ['*.tfvars']
+languages: [HCL]
+examples: ['scopedtoken_a1b2c3d4e5f6']
+"#,
+        )])?;
+        assert_eq!(report.errors, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,418 @@
+//! Labelled-corpus testing behind `kingfisher rules test`.
+//!
+//! A corpus is a directory of fixture files plus an expectations file that
+//! labels findings as real (`expected: yes`) or false positives
+//! (`expected: no`). The corpus is scanned with the regular scan engine and
+//! every finding is compared with the labels. Findings that carry no label are
+//! counted as false positives, so every finding in the corpus must be
+//! accounted for.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use tempfile::TempDir;
+
+use crate::{
+    cli::{
+        commands::rules::{RulesTestArgs, RulesTestOutputFormat},
+        global::{Command, Mode},
+        CommandLineArgs, GlobalArgs,
+    },
+    findings_store::FindingsStore,
+    scanner::{load_and_record_rules, run_async_scan},
+};
+
+/// Name of the expectations file looked up in the corpus when `--expectations`
+/// is not given.
+pub const DEFAULT_EXPECTATIONS_FILE: &str = "expectations.yml";
+
+/// A labelled location in the corpus.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Expectation {
+    /// Path of the fixture, relative to the corpus directory.
+    pub path: PathBuf,
+    /// ID of the rule the label applies to.
+    pub rule: String,
+    /// 1-based line of the finding; any line in the file when omitted.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Whether the rule should report a finding here.
+    #[serde(deserialize_with = "deserialize_expected")]
+    pub expected: bool,
+}
+
+#[derive(Deserialize)]
+struct ExpectationsFile {
+    expectations: Vec<Expectation>,
+}
+
+fn deserialize_expected<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Bool(bool),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Bool(b) => Ok(b),
+        Raw::Text(s) => match s.to_ascii_lowercase().as_str() {
+            "yes" | "true" => Ok(true),
+            "no" | "false" => Ok(false),
+            other => Err(D::Error::custom(format!("expected yes or no, found `{other}`"))),
+        },
+    }
+}
+
+/// Load the expectations listed in a YAML file.
+pub fn load_expectations(path: &Path) -> Result<Vec<Expectation>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read expectations file {}", path.display()))?;
+    let file: ExpectationsFile = serde_yaml::from_str(&contents)
+        .with_context(|| format!("Failed to parse expectations file {}", path.display()))?;
+    Ok(file.expectations)
+}
+
+/// A finding reported while scanning the corpus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CorpusFinding {
+    /// Path of the file, relative to the corpus directory.
+    pub path: PathBuf,
+    pub rule_id: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl CorpusFinding {
+    fn satisfies(&self, expectation: &Expectation) -> bool {
+        self.rule_id == expectation.rule
+            && self.path == expectation.path
+            && expectation.line.is_none_or(|l| (self.start_line..=self.end_line).contains(&l))
+    }
+}
+
+/// Per-rule (or total) counts and the metrics derived from them.
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct CorpusStats {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+    pub true_negatives: usize,
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+}
+
+impl CorpusStats {
+    fn finish(&mut self) {
+        let ratio = |num: usize, den: usize| (den > 0).then(|| num as f64 / den as f64);
+        self.precision = ratio(self.true_positives, self.true_positives + self.false_positives);
+        self.recall = ratio(self.true_positives, self.true_positives + self.false_negatives);
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// A finding labelled `expected: yes` was not reported.
+    FalseNegative,
+    /// A finding labelled `expected: no` was reported.
+    FalsePositive,
+    /// A finding was reported that has no label.
+    Unlabelled,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct CorpusFailure {
+    pub kind: FailureKind,
+    pub rule_id: String,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CorpusReport {
+    pub findings: usize,
+    pub expectations: usize,
+    pub rules: BTreeMap<String, CorpusStats>,
+    pub total: CorpusStats,
+    pub failures: Vec<CorpusFailure>,
+}
+
+impl CorpusReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Compare the findings of a corpus scan with its expectations.
+pub fn evaluate(expectations: &[Expectation], findings: &[CorpusFinding]) -> CorpusReport {
+    let mut report = CorpusReport {
+        findings: findings.len(),
+        expectations: expectations.len(),
+        ..Default::default()
+    };
+    let mut labelled = vec![false; findings.len()];
+
+    for expectation in expectations {
+        let mut hit = false;
+        for (finding, seen) in findings.iter().zip(labelled.iter_mut()) {
+            if finding.satisfies(expectation) {
+                *seen = true;
+                hit = true;
+            }
+        }
+        let stats = report.rules.entry(expectation.rule.clone()).or_default();
+        let failure = match (expectation.expected, hit) {
+            (true, true) => {
+                stats.true_positives += 1;
+                None
+            }
+            (true, false) => {
+                stats.false_negatives += 1;
+                Some(FailureKind::FalseNegative)
+            }
+            (false, true) => {
+                stats.false_positives += 1;
+                Some(FailureKind::FalsePositive)
+            }
+            (false, false) => {
+                stats.true_negatives += 1;
+                None
+            }
+        };
+        if let Some(kind) = failure {
+            report.failures.push(CorpusFailure {
+                kind,
+                rule_id: expectation.rule.clone(),
+                path: expectation.path.clone(),
+                line: expectation.line,
+            });
+        }
+    }
+
+    for finding in findings.iter().zip(&labelled).filter(|(_, seen)| !**seen).map(|(f, _)| f) {
+        report.rules.entry(finding.rule_id.clone()).or_default().false_positives += 1;
+        report.failures.push(CorpusFailure {
+            kind: FailureKind::Unlabelled,
+            rule_id: finding.rule_id.clone(),
+            path: finding.path.clone(),
+            line: Some(finding.start_line),
+        });
+    }
+
+    for stats in report.rules.values_mut() {
+        stats.finish();
+        report.total.true_positives += stats.true_positives;
+        report.total.false_positives += stats.false_positives;
+        report.total.false_negatives += stats.false_negatives;
+        report.total.true_negatives += stats.true_negatives;
+    }
+    report.total.finish();
+    report
+}
+
+/// Scan `corpus` with the full scan engine and return its findings.
+pub async fn scan_corpus(
+    global_args: &GlobalArgs,
+    args: &RulesTestArgs,
+    expectations_path: &Path,
+) -> Result<Vec<CorpusFinding>> {
+    let work = TempDir::new().context("Failed to create temporary directory")?;
+    let report_path = work.path().join("report.json");
+    let argv: Vec<std::ffi::OsString> = vec![
+        "kingfisher".into(),
+        "scan".into(),
+        args.corpus.clone().into(),
+        "--confidence=low".into(),
+        "--no-validate".into(),
+        "--no-dedup".into(),
+        "--git-history=none".into(),
+        "--format=json".into(),
+        "--output".into(),
+        report_path.into(),
+    ];
+    let mut scan_args = match CommandLineArgs::try_parse_from(argv)?.command {
+        Command::Scan(scan_args) => scan_args,
+        _ => unreachable!("parsed a scan command"),
+    };
+    scan_args.rules = args.rules.clone();
+
+    let global_args = GlobalArgs { quiet: true, progress: Mode::Never, ..global_args.clone() };
+    let datastore = Arc::new(Mutex::new(FindingsStore::new(work.path().join("clones"))));
+    let rules_db = load_and_record_rules(&scan_args, &datastore)?;
+    run_async_scan(&global_args, &scan_args, Arc::clone(&datastore), &rules_db).await?;
+
+    let ds = datastore.lock().unwrap();
+    let mut findings = Vec::new();
+    for entry in ds.get_matches() {
+        let (origin, _, m) = entry.as_ref();
+        let Some(path) = origin.iter().find_map(|o| o.blob_path()) else {
+            continue;
+        };
+        if path == expectations_path {
+            continue;
+        }
+        let path = path.strip_prefix(&args.corpus).unwrap_or(path).to_path_buf();
+        findings.push(CorpusFinding {
+            path,
+            rule_id: m.rule.id().to_string(),
+            start_line: m.location.source_span.start.line,
+            end_line: m.location.source_span.end.line,
+        });
+    }
+    Ok(findings)
+}
+
+/// Run the `rules test` command, exiting non-zero when the corpus regresses.
+pub async fn run_rules_test(global_args: &GlobalArgs, args: &RulesTestArgs) -> Result<()> {
+    if !args.corpus.is_dir() {
+        bail!("Corpus {} is not a directory", args.corpus.display());
+    }
+    let expectations_path =
+        args.expectations.clone().unwrap_or_else(|| args.corpus.join(DEFAULT_EXPECTATIONS_FILE));
+    let expectations = load_expectations(&expectations_path)?;
+    let findings = scan_corpus(global_args, args, &expectations_path).await?;
+    let report = evaluate(&expectations, &findings);
+
+    let mut writer = args.output_args.get_writer()?;
+    match args.output_args.format {
+        RulesTestOutputFormat::Pretty => write_pretty(&mut writer, &report)?,
+        RulesTestOutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, &report)?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()?;
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn write_pretty<W: Write>(writer: &mut W, report: &CorpusReport) -> Result<()> {
+    let percent = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.1}%", v * 100.0));
+    let id_width = report.rules.keys().map(|id| id.len()).max().unwrap_or(0).max(5);
+    writeln!(
+        writer,
+        "{:id_width$}  {:>5}  {:>5}  {:>5}  {:>9}  {:>7}",
+        "Rule", "TP", "FP", "FN", "Precision", "Recall"
+    )?;
+    let rows = report.rules.iter().map(|(id, stats)| (id.as_str(), stats));
+    for (id, stats) in rows.chain(std::iter::once(("Total", &report.total))) {
+        writeln!(
+            writer,
+            "{:id_width$}  {:>5}  {:>5}  {:>5}  {:>9}  {:>7}",
+            id,
+            stats.true_positives,
+            stats.false_positives,
+            stats.false_negatives,
+            percent(stats.precision),
+            percent(stats.recall),
+        )?;
+    }
+    if report.passed() {
+        writeln!(writer, "\nAll {} expectations met.", report.expectations)?;
+        return Ok(());
+    }
+    writeln!(writer, "\nFailures:")?;
+    for failure in &report.failures {
+        let location = match failure.line {
+            Some(line) => format!("{}:{}", failure.path.display(), line),
+            None => failure.path.display().to_string(),
+        };
+        let (label, reason) = match failure.kind {
+            FailureKind::FalseNegative => ("FN", "expected finding was not reported"),
+            FailureKind::FalsePositive => ("FP", "finding labelled as a false positive"),
+            FailureKind::Unlabelled => ("FP", "finding has no label"),
+        };
+        writeln!(writer, "  [{label}] {}  {location}  {reason}", failure.rule_id)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(path: &str, rule: &str, line: usize) -> CorpusFinding {
+        CorpusFinding { path: path.into(), rule_id: rule.into(), start_line: line, end_line: line }
+    }
+
+    #[test]
+    fn expectations_accept_yes_and_no() -> Result<()> {
+        let file: ExpectationsFile = serde_yaml::from_str(
+            "expectations:\n  - {path: a.env, rule: r.1, line: 3, expected: yes}\n  - {path: b.env, rule: r.1, expected: false}\n",
+        )?;
+        assert_eq!(file.expectations.len(), 2);
+        assert!(file.expectations[0].expected);
+        assert_eq!(file.expectations[0].line, Some(3));
+        assert!(!file.expectations[1].expected);
+        assert_eq!(file.expectations[1].line, None);
+        assert!(serde_yaml::from_str::<ExpectationsFile>(
+            "expectations:\n  - {path: a, rule: r, expected: maybe}\n"
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_counts_per_rule_outcomes() {
+        let expectations: Vec<Expectation> = serde_yaml::from_str::<ExpectationsFile>(
+            r#"
+expectations:
+  - {path: prod.env, rule: r.1, line: 1, expected: yes}
+  - {path: prod.env, rule: r.1, line: 9, expected: yes}
+  - {path: test.env, rule: r.1, expected: no}
+  - {path: docs.md, rule: r.2, expected: no}
+"#,
+        )
+        .unwrap()
+        .expectations;
+        let findings = vec![
+            finding("prod.env", "r.1", 1),
+            finding("test.env", "r.1", 4),
+            finding("notes.txt", "r.2", 2),
+        ];
+        let report = evaluate(&expectations, &findings);
+
+        let r1 = &report.rules["r.1"];
+        assert_eq!((r1.true_positives, r1.false_positives, r1.false_negatives), (1, 1, 1));
+        assert_eq!(r1.precision, Some(0.5));
+        assert_eq!(r1.recall, Some(0.5));
+        let r2 = &report.rules["r.2"];
+        assert_eq!((r2.true_positives, r2.false_positives, r2.true_negatives), (0, 1, 1));
+        assert_eq!(r2.precision, Some(0.0));
+        assert_eq!(r2.recall, None);
+
+        assert_eq!(report.total.true_positives, 1);
+        assert_eq!(report.total.false_positives, 2);
+        let kinds: Vec<_> = report.failures.iter().map(|f| f.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![FailureKind::FalseNegative, FailureKind::FalsePositive, FailureKind::Unlabelled]
+        );
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn evaluate_passes_when_all_labels_hold() {
+        let expectations = vec![Expectation {
+            path: "a.env".into(),
+            rule: "r.1".into(),
+            line: Some(2),
+            expected: true,
+        }];
+        let report = evaluate(&expectations, &[finding("a.env", "r.1", 2)]);
+        assert!(report.passed());
+        assert_eq!(report.total.precision, Some(1.0));
+        assert_eq!(report.total.recall, Some(1.0));
+    }
+}
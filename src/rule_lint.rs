@@ -0,0 +1,504 @@
+//! Performance linting behind `kingfisher rules lint`.
+//!
+//! Each rule is compiled into its own Vectorscan database and scanned against a
+//! synthetic haystack built to be awkward for it: near-misses of its own
+//! examples, long runs of a single byte, noisy printable text and blank lines.
+//! Compile time, database size and scan time are checked against budgets, and
+//! the pattern itself is inspected for constructs that are known to be costly,
+//! such as wide `(?:.|[\n\r]){0,N}` gaps. Rules are ranked so the most
+//! expensive ones come first.
+
+use std::{
+    io::Write,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use regex_syntax::{
+    hir::{Class, Hir, HirKind},
+    ParserBuilder,
+};
+use serde::Serialize;
+
+use crate::{
+    blob::{Blob, BlobIdMap},
+    cli::commands::rules::{RulesLintArgs, RulesLintOutputFormat},
+    matcher::Matcher,
+    origin::{Origin, OriginSet},
+    rule_check::{without_scope, IssueSeverity},
+    rule_loader::RuleLoader,
+    rules::rule::{Confidence, Rule},
+    rules_database::RulesDatabase,
+    scanner_pool::ScannerPool,
+};
+
+/// Size of each section of the adversarial haystack.
+const HAYSTACK_SECTION_LEN: usize = 64 * 1024;
+
+/// A repeated class matching at least this many distinct bytes is treated as a wildcard.
+const WIDE_CLASS_BYTES: usize = 128;
+
+/// Bounded wildcard gaps longer than this are flagged.
+const WIDE_GAP_LIMIT: u32 = 32;
+
+/// Counted repetitions with an upper bound above this are flagged.
+const LARGE_REPEAT_LIMIT: u32 = 256;
+
+/// Prefilter hits on the haystack above this mean most of the work falls to the regex.
+const PREFILTER_HIT_LIMIT: usize = 1_000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintKind {
+    CompileFailed,
+    CompileTime,
+    DatabaseSize,
+    ScanTime,
+    NoisyPrefilter,
+    WideGap,
+    UnboundedWildcard,
+    NestedQuantifier,
+    LargeRepetition,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LintIssue {
+    pub severity: IssueSeverity,
+    pub kind: LintKind,
+    pub message: String,
+}
+
+/// Measurements and issues for a single rule.
+#[derive(Clone, Debug, Serialize)]
+pub struct RuleLintResult {
+    pub rule_id: String,
+    pub rule_name: String,
+    /// Time taken to compile the rule on its own, in milliseconds.
+    pub compile_ms: f64,
+    /// Size of the rule's Vectorscan database, when it compiled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_bytes: Option<usize>,
+    /// Time taken by the Vectorscan prefilter alone over the haystack, in milliseconds.
+    pub prefilter_ms: f64,
+    /// Time taken by the full matcher pipeline over the haystack, in milliseconds.
+    pub scan_ms: f64,
+    /// Number of raw Vectorscan matches on the haystack.
+    pub prefilter_hits: usize,
+    pub issues: Vec<LintIssue>,
+}
+
+impl RuleLintResult {
+    fn push(&mut self, severity: IssueSeverity, kind: LintKind, message: String) {
+        self.issues.push(LintIssue { severity, kind, message });
+    }
+
+    fn count(&self, severity: IssueSeverity) -> usize {
+        self.issues.iter().filter(|i| i.severity == severity).count()
+    }
+
+    fn cost_ms(&self) -> f64 {
+        self.compile_ms + self.scan_ms
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RulesLintReport {
+    pub rules_linted: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    /// Rules ordered from most to least expensive; rules with errors come first.
+    pub rules: Vec<RuleLintResult>,
+}
+
+/// Limits a rule must stay within to pass the lint.
+#[derive(Copy, Clone, Debug)]
+pub struct LintBudgets {
+    pub compile: Duration,
+    pub scan: Duration,
+    pub database_bytes: usize,
+}
+
+impl From<&RulesLintArgs> for LintBudgets {
+    fn from(args: &RulesLintArgs) -> Self {
+        Self {
+            compile: Duration::from_millis(args.compile_budget_ms),
+            scan: Duration::from_millis(args.scan_budget_ms),
+            database_bytes: args.db_size_budget_kb.saturating_mul(1024) as usize,
+        }
+    }
+}
+
+/// Lint the given rules, returning them ranked by cost.
+pub fn lint_rules(rules: Vec<Rule>, budgets: &LintBudgets) -> Result<RulesLintReport> {
+    let mut report = RulesLintReport::default();
+    for rule in rules {
+        let result = lint_rule(without_scope(rule), budgets)?;
+        report.rules_linted += 1;
+        report.errors += result.count(IssueSeverity::Error);
+        report.warnings += result.count(IssueSeverity::Warning);
+        report.rules.push(result);
+    }
+    report.rules.sort_by(|a, b| {
+        b.count(IssueSeverity::Error)
+            .cmp(&a.count(IssueSeverity::Error))
+            .then_with(|| b.cost_ms().total_cmp(&a.cost_ms()))
+    });
+    Ok(report)
+}
+
+fn lint_rule(rule: Rule, budgets: &LintBudgets) -> Result<RuleLintResult> {
+    let haystack = adversarial_haystack(&rule);
+    let mut result = RuleLintResult {
+        rule_id: rule.id().to_string(),
+        rule_name: rule.name().to_string(),
+        compile_ms: 0.0,
+        database_bytes: None,
+        prefilter_ms: 0.0,
+        scan_ms: 0.0,
+        prefilter_hits: 0,
+        issues: Vec::new(),
+    };
+    for (kind, message) in pattern_issues(&rule.syntax().uncommented_pattern()) {
+        result.push(IssueSeverity::Warning, kind, message);
+    }
+
+    let start = Instant::now();
+    let compiled = RulesDatabase::compile_rules_individually(vec![Arc::new(rule)]);
+    let compile_time = start.elapsed();
+    result.compile_ms = millis(compile_time);
+    let rules_db = match compiled {
+        Ok(db) => db,
+        Err(e) => {
+            result.push(IssueSeverity::Error, LintKind::CompileFailed, format!("{e:#}"));
+            return Ok(result);
+        }
+    };
+    if compile_time > budgets.compile {
+        result.push(
+            IssueSeverity::Error,
+            LintKind::CompileTime,
+            format!(
+                "Compiling took {:.1}ms, over the {}ms budget",
+                result.compile_ms,
+                budgets.compile.as_millis()
+            ),
+        );
+    }
+    result.database_bytes = rules_db.vsdb.size().ok();
+    if let Some(size) = result.database_bytes.filter(|&size| size > budgets.database_bytes) {
+        result.push(
+            IssueSeverity::Error,
+            LintKind::DatabaseSize,
+            format!(
+                "Vectorscan database is {} KiB, over the {} KiB budget",
+                size / 1024,
+                budgets.database_bytes / 1024
+            ),
+        );
+    }
+
+    let seen = BlobIdMap::new();
+    let scanner_pool = Arc::new(ScannerPool::new(Arc::new(rules_db.vsdb.clone())));
+    let mut matcher = Matcher::new(&rules_db, scanner_pool, &seen, None, false, None, &[], false)?;
+
+    let start = Instant::now();
+    result.prefilter_hits = matcher.prefilter_hit_count(&haystack)?;
+    result.prefilter_ms = millis(start.elapsed());
+
+    let blob = Blob::from_bytes(haystack);
+    let origin = OriginSet::from(Origin::from_file(PathBuf::from("haystack")));
+    let start = Instant::now();
+    matcher.scan_blob(&blob, &origin, None, false, true, true)?;
+    let scan_time = start.elapsed();
+    result.scan_ms = millis(scan_time);
+
+    if result.prefilter_hits > PREFILTER_HIT_LIMIT {
+        result.push(
+            IssueSeverity::Warning,
+            LintKind::NoisyPrefilter,
+            format!(
+                "Vectorscan fired {} times on the haystack; each hit is re-checked with the regex",
+                result.prefilter_hits
+            ),
+        );
+    }
+    if scan_time > budgets.scan {
+        result.push(
+            IssueSeverity::Error,
+            LintKind::ScanTime,
+            format!(
+                "Scanning the haystack took {:.1}ms ({:.1}ms in the regex stage), over the {}ms budget",
+                result.scan_ms,
+                (result.scan_ms - result.prefilter_ms).max(0.0),
+                budgets.scan.as_millis()
+            ),
+        );
+    }
+    Ok(result)
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Builds a haystack that is cheap to produce but expensive for slow patterns.
+fn adversarial_haystack(rule: &Rule) -> Vec<u8> {
+    let mut haystack = Vec::with_capacity(HAYSTACK_SECTION_LEN * 4);
+
+    // Near-misses: each example minus its final character, so prefixes and
+    // keywords match but the token never completes.
+    let near_misses: Vec<&str> = rule
+        .syntax()
+        .examples
+        .iter()
+        .filter_map(|e| e.char_indices().last().map(|(i, _)| &e[..i]))
+        .filter(|e| !e.is_empty())
+        .collect();
+    let near_misses = if near_misses.is_empty() { "a".to_string() } else { near_misses.join(" ") };
+    while haystack.len() < HAYSTACK_SECTION_LEN {
+        haystack.extend_from_slice(near_misses.as_bytes());
+        haystack.push(b' ');
+    }
+
+    haystack.extend(std::iter::repeat_n(b'a', HAYSTACK_SECTION_LEN));
+
+    // Deterministic printable noise with the occasional newline.
+    let mut state: u32 = 0x9E37_79B9;
+    for _ in 0..HAYSTACK_SECTION_LEN {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let byte = match state % 97 {
+            0 => b'\n',
+            n => b' ' + (n % 95) as u8,
+        };
+        haystack.push(byte);
+    }
+
+    haystack.extend(std::iter::repeat_n(b'\n', HAYSTACK_SECTION_LEN));
+    haystack
+}
+
+/// Inspects a pattern for constructs that are expensive in Vectorscan or the regex engine.
+fn pattern_issues(pattern: &str) -> Vec<(LintKind, String)> {
+    let mut issues = Vec::new();
+    // Parse the way rules are compiled: byte-oriented, without Unicode classes.
+    let parsed = ParserBuilder::new().unicode(false).utf8(false).build().parse(pattern);
+    if let Ok(hir) = parsed {
+        collect_pattern_issues(&hir, &mut issues);
+    }
+    issues
+}
+
+fn collect_pattern_issues(hir: &Hir, issues: &mut Vec<(LintKind, String)>) {
+    match hir.kind() {
+        HirKind::Repetition(rep) => {
+            let repeats = rep.max.is_none_or(|max| max > 1);
+            let wide = byte_width(&rep.sub).is_some_and(|w| w >= WIDE_CLASS_BYTES);
+            match rep.max {
+                None if wide => issues.push((
+                    LintKind::UnboundedWildcard,
+                    "Unbounded repetition of a wildcard class can match across the whole input"
+                        .into(),
+                )),
+                Some(max) if wide && max > WIDE_GAP_LIMIT => issues.push((
+                    LintKind::WideGap,
+                    format!(
+                        "Wildcard gap of up to {max} bytes inflates the Vectorscan database; \
+                         keep gaps at {WIDE_GAP_LIMIT} or below"
+                    ),
+                )),
+                Some(max) if !wide && max > LARGE_REPEAT_LIMIT => issues.push((
+                    LintKind::LargeRepetition,
+                    format!("Counted repetition of up to {max} is expensive to compile"),
+                )),
+                _ => {}
+            }
+            if repeats && contains_repetition(&rep.sub) {
+                issues.push((
+                    LintKind::NestedQuantifier,
+                    "Nested quantifiers can cause heavy backtracking in the regex stage".into(),
+                ));
+            }
+            collect_pattern_issues(&rep.sub, issues);
+        }
+        HirKind::Capture(capture) => collect_pattern_issues(&capture.sub, issues),
+        HirKind::Concat(subs) | HirKind::Alternation(subs) => {
+            for sub in subs {
+                collect_pattern_issues(sub, issues);
+            }
+        }
+        HirKind::Empty | HirKind::Literal(_) | HirKind::Class(_) | HirKind::Look(_) => {}
+    }
+}
+
+/// Number of distinct bytes a single-byte expression can match, if it is one.
+fn byte_width(hir: &Hir) -> Option<usize> {
+    match hir.kind() {
+        HirKind::Literal(lit) if lit.0.len() == 1 => Some(1),
+        HirKind::Class(Class::Bytes(class)) => Some(
+            class.ranges().iter().map(|r| usize::from(r.end()) - usize::from(r.start()) + 1).sum(),
+        ),
+        HirKind::Class(Class::Unicode(class)) => {
+            Some(class.ranges().iter().map(|r| r.end() as usize - r.start() as usize + 1).sum())
+        }
+        HirKind::Capture(capture) => byte_width(&capture.sub),
+        HirKind::Alternation(subs) => subs.iter().map(byte_width).sum(),
+        _ => None,
+    }
+}
+
+fn contains_repetition(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Repetition(rep) => rep.max.is_none_or(|max| max > 1),
+        HirKind::Capture(capture) => contains_repetition(&capture.sub),
+        HirKind::Concat(subs) | HirKind::Alternation(subs) => subs.iter().any(contains_repetition),
+        _ => false,
+    }
+}
+
+/// Run the `rules lint` command, exiting non-zero when a rule fails its budgets.
+pub fn run_rules_lint(args: &RulesLintArgs) -> Result<()> {
+    let loader = RuleLoader::from_rule_specifiers(&args.rules);
+    let loaded = loader.load_with_confidence(Confidence::Low)?;
+    let resolved = loaded.resolve_enabled_rules()?;
+    let report = lint_rules(resolved.into_iter().cloned().collect(), &LintBudgets::from(args))?;
+
+    let mut writer = args.output_args.get_writer()?;
+    match args.output_args.format {
+        RulesLintOutputFormat::Pretty => write_pretty(&mut writer, &report)?,
+        RulesLintOutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, &report)?;
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()?;
+    if report.errors > 0 || (args.warnings_as_errors && report.warnings > 0) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn write_pretty<W: Write>(writer: &mut W, report: &RulesLintReport) -> Result<()> {
+    let id_width = report.rules.iter().map(|r| r.rule_id.len()).max().unwrap_or(0).max(4);
+    writeln!(
+        writer,
+        "{:>4}  {:id_width$}  {:>10}  {:>9}  {:>10}  {:>7}  {:>6}",
+        "Rank", "Rule", "Compile", "DB size", "Scan", "Hits", "Issues"
+    )?;
+    for (rank, rule) in report.rules.iter().enumerate() {
+        let size = rule.database_bytes.map_or("-".to_string(), |b| format!("{} KiB", b / 1024));
+        writeln!(
+            writer,
+            "{:>4}  {:id_width$}  {:>8.1}ms  {:>9}  {:>8.1}ms  {:>7}  {:>6}",
+            rank + 1,
+            rule.rule_id,
+            rule.compile_ms,
+            size,
+            rule.scan_ms,
+            rule.prefilter_hits,
+            rule.issues.len()
+        )?;
+    }
+    for rule in report.rules.iter().filter(|r| !r.issues.is_empty()) {
+        writeln!(writer, "\n{} ({})", rule.rule_name, rule.rule_id)?;
+        for issue in &rule.issues {
+            let label = match issue.severity {
+                IssueSeverity::Error => "[!] Error",
+                IssueSeverity::Warning => "[-] Warning",
+            };
+            writeln!(writer, "  {label}: {}", issue.message)?;
+        }
+    }
+    writeln!(
+        writer,
+        "\nLinted {} rules: {} errors, {} warnings",
+        report.rules_linted, report.errors, report.warnings
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::rule::RuleSyntax;
+
+    fn rule(yaml: &str) -> Rule {
+        Rule::new(serde_yaml::from_str::<RuleSyntax>(yaml).unwrap())
+    }
+
+    fn kinds(pattern: &str) -> Vec<LintKind> {
+        pattern_issues(pattern).into_iter().map(|(kind, _)| kind).collect()
+    }
+
+    const NO_BUDGETS: LintBudgets =
+        LintBudgets { compile: Duration::MAX, scan: Duration::MAX, database_bytes: usize::MAX };
+
+    #[test]
+    fn flags_costly_pattern_constructs() {
+        assert!(kinds(r"acme_[a-z0-9]{32}").is_empty());
+        assert!(kinds(r"(?xi)acme(?:.|[\n\r]){0,16}?([a-z0-9]{32})").is_empty());
+        assert_eq!(kinds(r"(?xi)acme(?:.|[\n\r]){0,96}?([a-z0-9]{32})"), vec![LintKind::WideGap]);
+        assert_eq!(kinds(r"acme.*([a-z0-9]{32})"), vec![LintKind::UnboundedWildcard]);
+        assert_eq!(kinds(r"acme_(?:[a-z]+-)+"), vec![LintKind::NestedQuantifier]);
+        assert_eq!(kinds(r"acme_[a-z0-9]{16,1024}"), vec![LintKind::LargeRepetition]);
+    }
+
+    #[test]
+    fn ranks_rules_and_reports_compile_failures() -> Result<()> {
+        let rules = vec![
+            rule(
+                r#"
+name: Simple rule
+id: test.simple.1
+pattern: 'simpletoken_([a-z0-9]{12})'
+examples: ['simpletoken_a1b2c3d4e5f6']
+"#,
+            ),
+            rule(
+                r#"
+name: Broken rule
+id: test.broken.1
+pattern: 'brokentoken_([a-z0-9]{12}'
+"#,
+            ),
+            rule(
+                r#"
+name: Wide rule
+id: test.wide.1
+pattern: '(?xi)widetoken(?:.|[\n\r]){0,200}?([a-z0-9]{12})'
+"#,
+            ),
+        ];
+        let report = lint_rules(rules, &NO_BUDGETS)?;
+        assert_eq!(report.rules_linted, 3);
+        assert_eq!(report.errors, 1);
+        assert_eq!(report.warnings, 1);
+
+        assert_eq!(report.rules[0].rule_id, "test.broken.1");
+        assert_eq!(report.rules[0].issues[0].kind, LintKind::CompileFailed);
+        assert!(report.rules[0].database_bytes.is_none());
+
+        let wide = report.rules.iter().find(|r| r.rule_id == "test.wide.1").unwrap();
+        assert_eq!(wide.issues[0].kind, LintKind::WideGap);
+        assert!(wide.database_bytes.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn budgets_turn_measurements_into_errors() -> Result<()> {
+        let budgets =
+            LintBudgets { compile: Duration::ZERO, scan: Duration::ZERO, database_bytes: 0 };
+        let report = lint_rules(
+            vec![rule(
+                "name: Simple rule\nid: test.simple.1\npattern: 'simpletoken_([a-z0-9]{12})'\n",
+            )],
+            &budgets,
+        )?;
+        let kinds: Vec<_> = report.rules[0].issues.iter().map(|i| i.kind).collect();
+        assert_eq!(kinds, vec![LintKind::CompileTime, LintKind::DatabaseSize, LintKind::ScanTime]);
+        Ok(())
+    }
+}
@@ -6,11 +6,13 @@ use std::{
 use anyhow::{bail, Context, Result};
 use thiserror::Error;
 use tracing::{debug, error, info, trace};
+use url::Url;
 
 use crate::{
     cli,
     cli::commands::rules::RuleSpecifierArgs,
-    defaults::get_builtin_rules,
+    defaults::{get_builtin_rules, get_builtin_validation_templates},
+    rule_pack::RulePackCache,
     rules::{
         rule::{Confidence, Rule},
         Rules,
@@ -27,11 +29,25 @@ pub enum RuleLoaderError {
 
     #[error("Unknown rule: `{0}`")]
     UnknownRule(String),
+
+    #[error("No enabled rules match the rule tag filters")]
+    NoRulesForTags,
+
+    #[error("Failed to load rule pack from {0}")]
+    RulePackLoadError(String),
+
+    #[error("Rule `{0}` requires rule `{1}`, which is not loaded")]
+    UnknownRequiredRule(String, String),
 }
 pub struct RuleLoader {
     load_builtins: bool,
     additional_load_paths: Vec<PathBuf>,
     enabled_rule_ids: Option<Vec<String>>,
+    required_tags: Vec<String>,
+    excluded_tags: Vec<String>,
+    rule_pack_urls: Vec<Url>,
+    rules_public_key: Option<String>,
+    rule_pack_cache: Option<RulePackCache>,
 }
 
 impl RuleLoader {
@@ -40,6 +56,11 @@ impl RuleLoader {
             load_builtins: true,
             additional_load_paths: Vec::new(),
             enabled_rule_ids: None, // None means "all rules enabled"
+            required_tags: Vec::new(),
+            excluded_tags: Vec::new(),
+            rule_pack_urls: Vec::new(),
+            rules_public_key: None,
+            rule_pack_cache: None, // None means the user cache directory
         }
     }
 
@@ -66,8 +87,41 @@ impl RuleLoader {
         self
     }
 
+    /// Only enable rules that have at least one of `tags`.
+    pub fn require_tags<S: AsRef<str>, I: IntoIterator<Item = S>>(mut self, tags: I) -> Self {
+        self.required_tags.extend(tags.into_iter().map(|s| s.as_ref().to_string()));
+        self
+    }
+
+    /// Disable rules that have any of `tags`.
+    pub fn exclude_tags<S: AsRef<str>, I: IntoIterator<Item = S>>(mut self, tags: I) -> Self {
+        self.excluded_tags.extend(tags.into_iter().map(|s| s.as_ref().to_string()));
+        self
+    }
+
+    /// Load rules from the remote rule packs at `urls` (see `rule_pack`).
+    pub fn rule_pack_urls<I: IntoIterator<Item = Url>>(mut self, urls: I) -> Self {
+        self.rule_pack_urls.extend(urls);
+        self
+    }
+
+    /// Ed25519 public key used to verify signed rule pack tarballs.
+    pub fn rules_public_key(mut self, public_key: Option<String>) -> Self {
+        self.rules_public_key = public_key;
+        self
+    }
+
+    pub fn rule_pack_cache(mut self, cache: RulePackCache) -> Self {
+        self.rule_pack_cache = Some(cache);
+        self
+    }
+
     pub fn load(&self, args: &cli::commands::scan::ScanArgs) -> Result<LoadedRules> {
-        let confidence = Confidence::from(args.confidence);
+        self.load_with_confidence(Confidence::from(args.confidence))
+    }
+
+    /// Load rules at or above the given confidence level.
+    pub fn load_with_confidence(&self, confidence: Confidence) -> Result<LoadedRules> {
         let mut id_to_rule: BTreeMap<String, Rule> = BTreeMap::new();
 
         if self.load_builtins {
@@ -79,29 +133,57 @@ impl RuleLoader {
             }
         }
 
-        if !self.additional_load_paths.is_empty() {
-            let custom_rules = Rules::from_paths(&self.additional_load_paths, confidence)
-                .context(RuleLoaderError::AdditionalPathLoadError)?;
+        let mut custom_paths = self.additional_load_paths.clone();
+        if !self.rule_pack_urls.is_empty() {
+            let cache = match &self.rule_pack_cache {
+                Some(cache) => cache.clone(),
+                None => RulePackCache::user_default()?,
+            };
+            for url in &self.rule_pack_urls {
+                let rules_dir = cache
+                    .ensure(url, self.rules_public_key.as_deref(), false)
+                    .context(RuleLoaderError::RulePackLoadError(url.to_string()))?;
+                custom_paths.push(rules_dir);
+            }
+        }
+
+        if !custom_paths.is_empty() {
+            let templates =
+                get_builtin_validation_templates().context(RuleLoaderError::BuiltinLoadError)?;
+            let custom_rules =
+                Rules::from_paths_with_templates(&custom_paths, confidence, templates)
+                    .context(RuleLoaderError::AdditionalPathLoadError)?;
             for rule_syntax in custom_rules {
                 let id = rule_syntax.id.clone();
                 id_to_rule.insert(id, Rule::new(rule_syntax));
             }
         }
 
-        Ok(LoadedRules { id_to_rule, enabled_rule_ids: self.enabled_rule_ids.clone() })
+        Ok(LoadedRules {
+            id_to_rule,
+            enabled_rule_ids: self.enabled_rule_ids.clone(),
+            required_tags: self.required_tags.clone(),
+            excluded_tags: self.excluded_tags.clone(),
+        })
     }
 
     pub fn from_rule_specifiers(specs: &RuleSpecifierArgs) -> Self {
         Self::new()
             .load_builtins(specs.load_builtins)
             .additional_rule_load_paths(specs.rules_path.as_slice())
+            .rule_pack_urls(specs.rules_url.iter().cloned())
+            .rules_public_key(specs.rules_public_key.clone())
             .enable_rule_ids(specs.rule.iter())
+            .require_tags(specs.rule_tag.iter())
+            .exclude_tags(specs.exclude_rule_tag.iter())
     }
 }
 
 pub struct LoadedRules {
     id_to_rule: BTreeMap<String, Rule>,
     enabled_rule_ids: Option<Vec<String>>,
+    required_tags: Vec<String>,
+    excluded_tags: Vec<String>,
 }
 
 impl LoadedRules {
@@ -116,7 +198,7 @@ impl LoadedRules {
     }
 
     pub fn resolve_enabled_rules(&self) -> Result<Vec<&Rule>> {
-        let resolved_rules = match &self.enabled_rule_ids {
+        let mut resolved_rules: Vec<&Rule> = match &self.enabled_rule_ids {
             // No selectors ⇒ every rule is enabled
             None => {
                 debug!("Using all available rules");
@@ -156,6 +238,40 @@ impl LoadedRules {
             }
         };
 
+        if !self.required_tags.is_empty() || !self.excluded_tags.is_empty() {
+            resolved_rules.retain(|rule| {
+                (self.required_tags.is_empty()
+                    || self.required_tags.iter().any(|tag| rule.has_tag(tag)))
+                    && !self.excluded_tags.iter().any(|tag| rule.has_tag(tag))
+            });
+            if resolved_rules.is_empty() {
+                bail!(RuleLoaderError::NoRulesForTags);
+            }
+        }
+
+        // Multi-part rules only fire alongside the rules they require, so pull those in too
+        let mut enabled: HashSet<&str> = resolved_rules.iter().map(|r| r.id()).collect();
+        let mut next = 0;
+        while next < resolved_rules.len() {
+            let rule = resolved_rules[next];
+            next += 1;
+            let Some(requires) = rule.requires() else { continue };
+            for required in &requires.rules {
+                if enabled.contains(required.as_str()) {
+                    continue;
+                }
+                let Some(part) = self.id_to_rule.get(required) else {
+                    bail!(RuleLoaderError::UnknownRequiredRule(
+                        rule.id().to_string(),
+                        required.clone()
+                    ));
+                };
+                debug!("Enabling rule `{}` required by `{}`", part.id(), rule.id());
+                enabled.insert(part.id());
+                resolved_rules.push(part);
+            }
+        }
+
         info!("Loaded {}", Counted::regular(resolved_rules.len(), "rule"));
         for rule in &resolved_rules {
             trace!("Using rule `{}`: {}", rule.id(), rule.name());
@@ -0,0 +1,486 @@
+//! Remote rule packs loaded with `--rules-url`.
+//!
+//! A rule pack is a tarball or a git repository of rule files. A pack must be
+//! pinned to one version and verified before it is used:
+//!
+//! * `https://host/pack-1.2.0.tar.gz#sha256=<HEX>` checks the tarball's SHA-256;
+//! * `https://host/pack-1.2.0.tar.gz` with `--rules-public-key` checks the
+//!   Ed25519 signature published next to it at `<URL>.sig`;
+//! * `git+https://host/rules.git#<COMMIT>` checks out exactly that commit.
+//!
+//! Verified packs are unpacked under the user cache directory and reused
+//! without network access until `kingfisher rules update` fetches them again.
+
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{
+    cli::commands::rules::RulesUpdateArgs,
+    git_binary::{CloneMode, Git},
+    git_url::GitUrl,
+    validation::http_client_builder,
+};
+
+const METADATA_FILE: &str = "pack.json";
+const RULES_DIR: &str = "rules";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Where a rule pack comes from and how it is pinned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulePackSource {
+    Tarball { url: Url, sha256: Option<String> },
+    Git { url: GitUrl, commit: String },
+}
+
+impl RulePackSource {
+    pub fn parse(spec: &Url) -> Result<Self> {
+        if let Some(git_url) = spec.as_str().strip_prefix("git+") {
+            let mut url = Url::parse(git_url)?;
+            let commit = url.fragment().map(str::to_ascii_lowercase).unwrap_or_default();
+            if !(commit.len() == 40 || commit.len() == 64)
+                || !commit.bytes().all(|b| b.is_ascii_hexdigit())
+            {
+                bail!("git rule pack {spec} must be pinned to a full commit id (`#<COMMIT>`)");
+            }
+            url.set_fragment(None);
+            let url = GitUrl::try_from(url).map_err(|e| anyhow!("{spec}: {e}"))?;
+            return Ok(Self::Git { url, commit });
+        }
+
+        if !matches!(spec.scheme(), "https" | "http" | "file") {
+            bail!("unsupported rule pack URL {spec}: expected https, http, file or git+https");
+        }
+        let sha256 = match spec.fragment() {
+            None => None,
+            Some(fragment) => {
+                let Some(hex) = fragment.strip_prefix("sha256=") else {
+                    bail!("rule pack {spec}: the URL fragment must be `#sha256=<HEX>`");
+                };
+                if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    bail!("rule pack {spec}: `sha256` must be 64 hex characters");
+                }
+                Some(hex.to_ascii_lowercase())
+            }
+        };
+        let mut url = spec.clone();
+        url.set_fragment(None);
+        Ok(Self::Tarball { url, sha256 })
+    }
+}
+
+/// How a cached pack was verified.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Verification {
+    Sha256,
+    Signature,
+    Commit,
+}
+
+/// Written next to each cached pack.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RulePackMetadata {
+    pub url: String,
+    pub verified_by: Vec<Verification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    pub fetched_at: String,
+}
+
+/// The on-disk cache of verified rule packs.
+#[derive(Debug, Clone)]
+pub struct RulePackCache {
+    root: PathBuf,
+}
+
+impl RulePackCache {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The cache under `$XDG_CACHE_HOME/kingfisher/rule-packs` (or the platform equivalent).
+    pub fn user_default() -> Result<Self> {
+        let env_dir = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+        let base = env_dir("XDG_CACHE_HOME");
+        #[cfg(windows)]
+        let base = base.or_else(|| env_dir("LOCALAPPDATA"));
+        let base = base
+            .or_else(|| env_dir("HOME").map(|home| home.join(".cache")))
+            .context("cannot locate a cache directory for rule packs; set XDG_CACHE_HOME")?;
+        Ok(Self::new(base.join("kingfisher").join("rule-packs")))
+    }
+
+    /// Directory holding the pack for `spec`. Each pinned URL gets its own
+    /// entry, and so does each public key it is verified with: a pack cached
+    /// without a key, or under another key, is fetched and verified again.
+    pub fn pack_dir(&self, spec: &Url, public_key: Option<&str>) -> Result<PathBuf> {
+        let mut hasher = Sha256::new();
+        hasher.update(spec.as_str().as_bytes());
+        if let Some(key) = public_key {
+            hasher.update(b"\0");
+            hasher.update(decode_bytes::<32>(key, "--rules-public-key")?);
+        }
+        Ok(self.root.join(hex::encode(&hasher.finalize()[..16])))
+    }
+
+    /// Return the rules directory of a verified pack, fetching it when it is
+    /// not cached yet or when `refresh` is set.
+    pub fn ensure(&self, spec: &Url, public_key: Option<&str>, refresh: bool) -> Result<PathBuf> {
+        let source = RulePackSource::parse(spec)?;
+        // Only tarballs carry a detached signature; a git pack is verified by
+        // its pinned commit id alone, so a key must not look like it was checked.
+        if public_key.is_some() && matches!(source, RulePackSource::Git { .. }) {
+            bail!(
+                "rule pack {spec}: --rules-public-key only verifies tarballs; git rule packs are \
+                 verified by their pinned commit id"
+            );
+        }
+        let pack_dir = self.pack_dir(spec, public_key)?;
+        let rules_dir = pack_dir.join(RULES_DIR);
+        if !refresh && pack_dir.join(METADATA_FILE).is_file() && rules_dir.is_dir() {
+            debug!("Using cached rule pack {spec} from {}", pack_dir.display());
+            return Ok(rules_dir);
+        }
+
+        fs::create_dir_all(&self.root)
+            .with_context(|| format!("Failed to create {}", self.root.display()))?;
+        let staging = tempfile::Builder::new().prefix(".fetch-").tempdir_in(&self.root)?;
+        let verified_by = fetch_pack(&source, public_key, &staging.path().join(RULES_DIR))?;
+        let metadata = RulePackMetadata {
+            url: spec.to_string(),
+            verified_by,
+            public_key: public_key.map(str::to_string),
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let mut file = fs::File::create(staging.path().join(METADATA_FILE))?;
+        serde_json::to_writer_pretty(&mut file, &metadata)?;
+        file.write_all(b"\n")?;
+
+        if pack_dir.exists() {
+            fs::remove_dir_all(&pack_dir)
+                .with_context(|| format!("Failed to remove {}", pack_dir.display()))?;
+        }
+        fs::rename(staging.keep(), &pack_dir)
+            .with_context(|| format!("Failed to move rule pack into {}", pack_dir.display()))?;
+        info!("Cached rule pack {spec} in {}", pack_dir.display());
+        Ok(rules_dir)
+    }
+
+    /// Metadata of every cached pack.
+    pub fn cached_packs(&self) -> Result<Vec<RulePackMetadata>> {
+        let mut packs = Vec::new();
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Ok(packs);
+        };
+        for entry in entries {
+            let path = entry?.path().join(METADATA_FILE);
+            if let Ok(contents) = fs::read(&path) {
+                let metadata = serde_json::from_slice(&contents)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?;
+                packs.push(metadata);
+            }
+        }
+        packs.sort_by(|a: &RulePackMetadata, b| a.url.cmp(&b.url));
+        Ok(packs)
+    }
+}
+
+/// Fetch and verify a pack, unpacking its files into `dest`.
+fn fetch_pack(
+    source: &RulePackSource,
+    public_key: Option<&str>,
+    dest: &Path,
+) -> Result<Vec<Verification>> {
+    match source {
+        RulePackSource::Tarball { url, sha256 } => {
+            let archive = fetch_bytes(url)?;
+            let mut verified_by = Vec::new();
+            if let Some(expected) = sha256 {
+                let actual = hex::encode(Sha256::digest(&archive));
+                if &actual != expected {
+                    bail!("rule pack {url} has SHA-256 {actual}, expected {expected}");
+                }
+                verified_by.push(Verification::Sha256);
+            }
+            if let Some(key) = public_key {
+                let mut sig_url = url.clone();
+                sig_url.set_path(&format!("{}.sig", url.path()));
+                let signature = fetch_bytes(&sig_url)
+                    .with_context(|| format!("Failed to fetch signature for rule pack {url}"))?;
+                verify_signature(&archive, &signature, key)
+                    .with_context(|| format!("rule pack {url} failed signature verification"))?;
+                verified_by.push(Verification::Signature);
+            }
+            if verified_by.is_empty() {
+                bail!(
+                    "rule pack {url} is not pinned: add `#sha256=<HEX>` to the URL or pass \
+                     --rules-public-key"
+                );
+            }
+            unpack_tarball(&archive, dest)
+                .with_context(|| format!("Failed to unpack rule pack {url}"))?;
+            Ok(verified_by)
+        }
+        RulePackSource::Git { url, commit } => {
+            let git = Git::new(false);
+            git.create_fresh_clone(url, dest, CloneMode::Checkout)?;
+            git.checkout_commit(dest, commit)
+                .with_context(|| format!("rule pack {url} has no commit {commit}"))?;
+            fs::remove_dir_all(dest.join(".git"))?;
+            Ok(vec![Verification::Commit])
+        }
+    }
+}
+
+fn fetch_bytes(url: &Url) -> Result<Vec<u8>> {
+    match url.scheme() {
+        "file" => {
+            let path = url.to_file_path().map_err(|_| anyhow!("invalid file URL {url}"))?;
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))
+        }
+        _ => {
+            // Rules may be loaded from a Tokio worker, where blocking on another
+            // runtime panics, so download on a separate thread.
+            let url = url.clone();
+            std::thread::spawn(move || -> Result<Vec<u8>> {
+                let client = http_client_builder()?.timeout(DOWNLOAD_TIMEOUT).build()?;
+                tokio::runtime::Builder::new_current_thread().enable_all().build()?.block_on(
+                    async {
+                        let response = client.get(url).send().await?.error_for_status()?;
+                        Ok(response.bytes().await?.to_vec())
+                    },
+                )
+            })
+            .join()
+            .map_err(|_| anyhow!("rule pack download thread panicked"))?
+        }
+    }
+}
+
+fn unpack_tarball(archive: &[u8], dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest)?;
+    let reader: Box<dyn Read + '_> = if archive.starts_with(&[0x1f, 0x8b]) {
+        Box::new(flate2::read::GzDecoder::new(archive))
+    } else {
+        Box::new(archive)
+    };
+    // `unpack` refuses entries that would land outside `dest`.
+    tar::Archive::new(reader).unpack(dest)?;
+    Ok(())
+}
+
+/// Decode `N` bytes given as hex or base64.
+fn decode_bytes<const N: usize>(text: &str, what: &str) -> Result<[u8; N]> {
+    let text = text.trim();
+    let bytes = if text.len() == 2 * N { hex::decode(text)? } else { BASE64.decode(text)? };
+    bytes.try_into().map_err(|_| anyhow!("{what} must be {N} bytes, as hex or base64"))
+}
+
+/// Check an Ed25519 `signature` (raw, hex or base64) of `data`.
+fn verify_signature(data: &[u8], signature: &[u8], public_key: &str) -> Result<()> {
+    let key = VerifyingKey::from_bytes(&decode_bytes::<32>(public_key, "--rules-public-key")?)?;
+    let signature = match <[u8; 64]>::try_from(signature) {
+        Ok(raw) => raw,
+        Err(_) => decode_bytes::<64>(&String::from_utf8_lossy(signature), "the signature")?,
+    };
+    key.verify_strict(data, &Signature::from_bytes(&signature))?;
+    Ok(())
+}
+
+/// Run the `rules update` command.
+pub fn run_rules_update(args: &RulesUpdateArgs) -> Result<()> {
+    let cache = RulePackCache::user_default()?;
+    let packs: Vec<(Url, Option<String>)> = if args.rules_url.is_empty() {
+        cache
+            .cached_packs()?
+            .into_iter()
+            .map(|pack| Ok((Url::parse(&pack.url)?, pack.public_key)))
+            .collect::<Result<_>>()?
+    } else {
+        args.rules_url.iter().map(|url| (url.clone(), None)).collect()
+    };
+    if packs.is_empty() {
+        println!("No rule packs to update; pass --rules-url");
+        return Ok(());
+    }
+
+    let mut failures = 0;
+    for (url, cached_key) in packs {
+        let public_key = args.rules_public_key.as_deref().or(cached_key.as_deref());
+        match cache.ensure(&url, public_key, true) {
+            Ok(rules_dir) => println!("Updated {url} ({})", rules_dir.display()),
+            Err(e) => {
+                failures += 1;
+                eprintln!("Failed to update {url}: {e:#}");
+            }
+        }
+    }
+    if failures > 0 {
+        bail!("{failures} rule pack(s) failed to update");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const RULES: &str =
+        "rules:\n  - name: Pack Key\n    id: pack.key.1\n    pattern: '(pk_[a-z]{8})'\n";
+
+    fn tarball() -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(RULES.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "pack/rules.yml", RULES.as_bytes()).unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn file_url(path: &Path, fragment: Option<&str>) -> Url {
+        let mut url = Url::from_file_path(path).unwrap();
+        url.set_fragment(fragment);
+        url
+    }
+
+    #[test]
+    fn parses_pinned_sources() -> Result<()> {
+        let sha = "ab".repeat(32);
+        let spec = Url::parse(&format!("https://example.com/pack-1.0.tar.gz#sha256={sha}"))?;
+        assert_eq!(
+            RulePackSource::parse(&spec)?,
+            RulePackSource::Tarball {
+                url: Url::parse("https://example.com/pack-1.0.tar.gz")?,
+                sha256: Some(sha),
+            }
+        );
+
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+        let spec = Url::parse(&format!("git+https://example.com/rules.git#{commit}"))?;
+        assert!(matches!(
+            RulePackSource::parse(&spec)?,
+            RulePackSource::Git { commit: c, .. } if c == commit
+        ));
+
+        for bad in [
+            "git+https://example.com/rules.git#main",
+            "https://example.com/pack.tar.gz#md5=abc",
+            "ftp://example.com/pack.tar.gz",
+        ] {
+            assert!(RulePackSource::parse(&Url::parse(bad)?).is_err(), "{bad}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn caches_tarball_pinned_by_sha256() -> Result<()> {
+        let work = TempDir::new()?;
+        let archive = tarball();
+        let pack = work.path().join("pack.tar.gz");
+        fs::write(&pack, &archive)?;
+        let cache = RulePackCache::new(work.path().join("cache"));
+
+        let bad = file_url(&pack, Some(&format!("sha256={}", "0".repeat(64))));
+        let err = cache.ensure(&bad, None, false).unwrap_err();
+        assert!(err.to_string().contains("expected 0000"), "{err:#}");
+        assert!(cache.ensure(&file_url(&pack, None), None, false).is_err());
+
+        let good =
+            file_url(&pack, Some(&format!("sha256={}", hex::encode(Sha256::digest(&archive)))));
+        let rules_dir = cache.ensure(&good, None, false)?;
+        assert_eq!(fs::read_to_string(rules_dir.join("pack/rules.yml"))?, RULES);
+
+        // A cached pack is reused even if the source disappears.
+        fs::remove_file(&pack)?;
+        assert_eq!(cache.ensure(&good, None, false)?, rules_dir);
+        assert!(cache.ensure(&good, None, true).is_err());
+        assert_eq!(cache.cached_packs()?[0].verified_by, [Verification::Sha256]);
+        Ok(())
+    }
+
+    #[test]
+    fn verifies_detached_signature() -> Result<()> {
+        let work = TempDir::new()?;
+        let archive = tarball();
+        let pack = work.path().join("pack.tar.gz");
+        fs::write(&pack, &archive)?;
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = hex::encode(signing_key.verifying_key().as_bytes());
+        let cache = RulePackCache::new(work.path().join("cache"));
+        let url = file_url(&pack, None);
+
+        let other_key = SigningKey::from_bytes(&[8u8; 32]);
+        fs::write(work.path().join("pack.tar.gz.sig"), other_key.sign(&archive).to_bytes())?;
+        assert!(cache.ensure(&url, Some(&public_key), false).is_err());
+
+        let signature = BASE64.encode(signing_key.sign(&archive).to_bytes());
+        fs::write(work.path().join("pack.tar.gz.sig"), signature)?;
+        let rules_dir = cache.ensure(&url, Some(&public_key), false)?;
+        assert!(rules_dir.join("pack/rules.yml").is_file());
+        let cached = cache.cached_packs()?;
+        assert_eq!(cached[0].verified_by, [Verification::Signature]);
+        assert_eq!(cached[0].public_key.as_deref(), Some(public_key.as_str()));
+        Ok(())
+    }
+
+    #[test]
+    fn public_key_is_refused_for_git_packs() -> Result<()> {
+        let work = TempDir::new()?;
+        let cache = RulePackCache::new(work.path().join("cache"));
+        let public_key = hex::encode(SigningKey::from_bytes(&[7u8; 32]).verifying_key().as_bytes());
+        let url = Url::parse(
+            "git+https://example.com/rules.git#0123456789abcdef0123456789abcdef01234567",
+        )?;
+        let err = cache.ensure(&url, Some(&public_key), false).unwrap_err();
+        assert!(err.to_string().contains("only verifies tarballs"), "{err:#}");
+        assert!(cache.cached_packs()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn public_key_is_part_of_the_cache_key() -> Result<()> {
+        let work = TempDir::new()?;
+        let archive = tarball();
+        let pack = work.path().join("pack.tar.gz");
+        fs::write(&pack, &archive)?;
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = hex::encode(signing_key.verifying_key().as_bytes());
+        let cache = RulePackCache::new(work.path().join("cache"));
+        let url =
+            file_url(&pack, Some(&format!("sha256={}", hex::encode(Sha256::digest(&archive)))));
+        let unsigned_dir = cache.ensure(&url, None, false)?;
+
+        // A pack cached without a key is not trusted once a key is required.
+        let other_key = SigningKey::from_bytes(&[8u8; 32]);
+        fs::write(work.path().join("pack.tar.gz.sig"), other_key.sign(&archive).to_bytes())?;
+        assert!(cache.ensure(&url, Some(&public_key), false).is_err());
+
+        fs::write(work.path().join("pack.tar.gz.sig"), signing_key.sign(&archive).to_bytes())?;
+        let signed_dir = cache.ensure(&url, Some(&public_key), false)?;
+        assert_ne!(signed_dir, unsigned_dir);
+        // The same key given as base64 maps to the same entry.
+        let base64_key = BASE64.encode(signing_key.verifying_key().as_bytes());
+        assert_eq!(cache.ensure(&url, Some(&base64_key), false)?, signed_dir);
+        Ok(())
+    }
+}
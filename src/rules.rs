@@ -4,11 +4,22 @@ use serde::Deserialize;
 use thiserror::Error;
 use tracing::{debug, debug_span, error};
 
+pub mod checksum;
 pub mod rule;
-use std::{collections::BTreeMap, fs::File, io::BufReader, path::Path};
+pub mod scope;
+pub mod template;
+pub mod transform;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
 
 use rule::{Confidence, RuleSyntax, Validation};
 use serde::de::DeserializeOwned;
+use template::ValidationTemplates;
+use transform::RuleTransform;
 
 #[derive(Debug, Error)]
 pub enum RulesError {
@@ -29,6 +40,21 @@ pub enum RulesError {
 
     #[error("HTTP validation for rule `{rule_id}` in file {path} missing response_matcher")]
     MissingResponseMatcher { path: String, rule_id: String },
+
+    #[error("Rule `{rule_id}` in file {path} uses unknown validation template `{template}`")]
+    UnknownValidationTemplate { path: String, rule_id: String, template: String },
+
+    #[error("Validation template `{template}` is defined in both {first} and {second}")]
+    DuplicateValidationTemplate { template: String, first: String, second: String },
+
+    #[error("Invalid validation template `{template}` in file {path}: {message}")]
+    InvalidValidationTemplate { path: String, template: String, message: String },
+
+    #[error("Invalid validation template reference in rule `{rule_id}` in file {path}: {message}")]
+    InvalidTemplateReference { path: String, rule_id: String, message: String },
+
+    #[error("Invalid transform in rule `{rule_id}` in file {path}: {message}")]
+    InvalidTransform { path: String, rule_id: String, message: String },
 }
 
 #[derive(Clone, Default)]
@@ -38,6 +64,7 @@ pub struct Rules {
 
 #[derive(Deserialize)]
 struct RawRules {
+    #[serde(default)]
     rules: Vec<RuleSyntax>,
 }
 
@@ -54,76 +81,105 @@ impl Rules {
         iterable: I,
         confidence: Confidence,
     ) -> Result<Self> {
+        Self::from_paths_and_contents_with_templates(
+            iterable,
+            confidence,
+            ValidationTemplates::default(),
+        )
+    }
+
+    /// Loads a batch of rule files that share validation templates. `templates`
+    /// holds templates defined outside the batch, such as the built-in ones.
+    pub fn from_paths_and_contents_with_templates<
+        'a,
+        I: IntoIterator<Item = (&'a Path, &'a [u8])>,
+    >(
+        iterable: I,
+        confidence: Confidence,
+        mut templates: ValidationTemplates,
+    ) -> Result<Self> {
+        let files: Vec<(&Path, &[u8])> = iterable.into_iter().collect();
+        let mut docs = Vec::with_capacity(files.len());
+        for (path, contents) in &files {
+            let doc: serde_yaml::Value =
+                serde_yaml::from_slice(contents).map_err(|e| yaml_parse_error(path, e))?;
+            templates.collect(path, &doc)?;
+            docs.push(doc);
+        }
+
         let mut rules = Self::new();
-        for (path, contents) in iterable {
-            match serde_yaml::from_slice::<RawRules>(contents) {
-                Ok(rs) => {
-                    for rule_syntax in rs.rules {
-                        if !rule_syntax.confidence.is_at_least(&confidence) {
-                            continue;
-                        }
-                        if let Some(Validation::Http(http_val)) = &rule_syntax.validation {
-                            if http_val
-                                .request
-                                .response_matcher
-                                .as_ref()
-                                .map_or(true, |m| m.is_empty())
-                            {
-                                bail!(RulesError::MissingResponseMatcher {
-                                    path: path.display().to_string(),
-                                    rule_id: rule_syntax.id.clone(),
-                                });
-                            }
-                        }
-                        rules.rules.insert(rule_syntax.id.clone(), rule_syntax);
-                    }
-                }
-                Err(e) => {
-                    if let Some(location) = e.location() {
-                        error!(
-                            "Failed to parse rules YAML from {}: {}, at line: {}, column: {}",
-                            path.display(),
-                            e,
-                            location.line(),
-                            location.column()
-                        );
-                        bail!(RulesError::InvalidResponseMatcherVariant(
-                            path.display().to_string(),
-                            location.line(),
-                            location.column(),
-                        ));
-                    } else {
-                        error!("Failed to parse rules YAML from {}: {}", path.display(), e);
-                        bail!(RulesError::YamlParseError(format!(
-                            "Failed to load rules YAML from {}: {}",
-                            path.display(),
-                            e
-                        )));
-                    }
-                }
+        for ((path, contents), mut doc) in files.into_iter().zip(docs) {
+            // Files without template references are parsed straight from their
+            // bytes so that errors keep their line and column.
+            let raw = if templates.expand(path, &mut doc)? {
+                serde_yaml::from_value::<RawRules>(doc)
+            } else {
+                serde_yaml::from_slice::<RawRules>(contents)
             }
+            .map_err(|e| yaml_parse_error(path, e))?;
+            rules.insert_syntaxes(path, raw.rules, confidence)?;
         }
         Ok(rules)
     }
 
+    fn insert_syntaxes(
+        &mut self,
+        path: &Path,
+        syntaxes: Vec<RuleSyntax>,
+        confidence: Confidence,
+    ) -> Result<()> {
+        for rule_syntax in syntaxes {
+            if !rule_syntax.confidence.is_at_least(&confidence) {
+                continue;
+            }
+            if let Some(Validation::Http(http_val)) = &rule_syntax.validation {
+                if http_val.request.response_matcher.as_ref().map_or(true, |m| m.is_empty()) {
+                    bail!(RulesError::MissingResponseMatcher {
+                        path: path.display().to_string(),
+                        rule_id: rule_syntax.id.clone(),
+                    });
+                }
+            }
+            if let Err(e) = RuleTransform::from_syntax(&rule_syntax) {
+                bail!(RulesError::InvalidTransform {
+                    path: path.display().to_string(),
+                    rule_id: rule_syntax.id.clone(),
+                    message: e.root_cause().to_string(),
+                });
+            }
+            self.rules.insert(rule_syntax.id.clone(), rule_syntax);
+        }
+        Ok(())
+    }
+
     pub fn from_paths<P: AsRef<Path>, I: IntoIterator<Item = P>>(
         paths: I,
         confidence: Confidence,
+    ) -> Result<Self> {
+        Self::from_paths_with_templates(paths, confidence, ValidationTemplates::default())
+    }
+
+    /// Loads rule files and directories as one batch sharing validation templates.
+    pub fn from_paths_with_templates<P: AsRef<Path>, I: IntoIterator<Item = P>>(
+        paths: I,
+        confidence: Confidence,
+        templates: ValidationTemplates,
     ) -> Result<Self> {
         let mut num_paths = 0;
-        let mut rules = Rules::new();
+        let mut yaml_files = Vec::new();
         for input in paths {
             num_paths += 1;
             let input = input.as_ref();
             if input.is_file() {
-                rules.update(Rules::from_yaml_file(input, confidence)?);
+                yaml_files.push(input.to_path_buf());
             } else if input.is_dir() {
-                rules.update(Rules::from_directory(input, confidence)?);
+                yaml_files.extend(yaml_files_in(input)?);
             } else {
                 error!("Invalid input type: {} is neither a file nor a directory", input.display());
                 bail!(RulesError::InvalidInputError(input.display().to_string()));
             }
         }
+        let rules = Self::from_yaml_files_with_templates(&yaml_files, confidence, templates)?;
         debug!("Loaded {} rules from {} paths", rules.num_rules(), num_paths);
         Ok(rules)
     }
@@ -131,81 +187,41 @@ impl Rules {
     pub fn from_yaml_file<P: AsRef<Path>>(path: P, confidence: Confidence) -> Result<Self> {
         let path = path.as_ref();
         let _span = debug_span!("Rules::from_yaml_file", "{}", path.display()).entered();
-        match load_yaml_file::<RawRules, _>(path) {
-            Ok(rs) => {
-                let mut rules = Rules::new();
-                for rule_syntax in rs.rules {
-                    if !rule_syntax.confidence.is_at_least(&confidence) {
-                        continue;
-                    }
-                    if let Some(Validation::Http(http_val)) = &rule_syntax.validation {
-                        if http_val.request.response_matcher.as_ref().map_or(true, |m| m.is_empty())
-                        {
-                            bail!(RulesError::MissingResponseMatcher {
-                                path: path.display().to_string(),
-                                rule_id: rule_syntax.id.clone(),
-                            });
-                        }
-                    }
-                    rules.rules.insert(rule_syntax.id.clone(), rule_syntax);
-                }
-                debug!("Loaded {} rules from {}", rules.num_rules(), path.display());
-                Ok(rules)
-            }
-            Err(e) => {
-                error!("Failed to load rules YAML from {}: {}", path.display(), e);
-                bail!(RulesError::YamlParseError(format!(
-                    "Failed to load rules YAML from {}: {}",
-                    path.display(),
-                    e
-                )))
-            }
-        }
+        Self::from_yaml_files([path], confidence)
     }
 
     pub fn from_yaml_files<P: AsRef<Path>, I: IntoIterator<Item = P>>(
         paths: I,
         confidence: Confidence,
     ) -> Result<Self> {
-        let mut num_paths = 0;
-        let mut rules = Rules::new();
+        Self::from_yaml_files_with_templates(paths, confidence, ValidationTemplates::default())
+    }
+
+    fn from_yaml_files_with_templates<P: AsRef<Path>, I: IntoIterator<Item = P>>(
+        paths: I,
+        confidence: Confidence,
+        templates: ValidationTemplates,
+    ) -> Result<Self> {
+        let mut files = Vec::new();
         for path in paths {
-            num_paths += 1;
-            rules.update(Rules::from_yaml_file(path.as_ref(), confidence)?);
+            let path = path.as_ref().to_path_buf();
+            let contents = std::fs::read(&path)
+                .with_context(|| format!("Failed to open YAML file: {}", path.display()))?;
+            files.push((path, contents));
         }
-        debug!("Loaded {} rules from {} YAML files", rules.num_rules(), num_paths);
+        let rules = Self::from_paths_and_contents_with_templates(
+            files.iter().map(|(p, c)| (p.as_path(), c.as_slice())),
+            confidence,
+            templates,
+        )?;
+        debug!("Loaded {} rules from {} YAML files", rules.num_rules(), files.len());
         Ok(rules)
     }
 
     pub fn from_directory<P: AsRef<Path>>(path: P, confidence: Confidence) -> Result<Self> {
         let path = path.as_ref();
         let _span = debug_span!("Rules::from_directory", "{}", path.display()).entered();
-        let yaml_types =
-            TypesBuilder::new().add_defaults().select("yaml").build().map_err(|e| {
-                error!("Failed to build YAML types: {}", e);
-                RulesError::YamlTypesBuildError(e.to_string())
-            })?;
-        let walker = WalkBuilder::new(path)
-            .types(yaml_types)
-            .follow_links(true)
-            .standard_filters(false)
-            .build();
-        let mut yaml_files = Vec::new();
-        for entry in walker {
-            match entry {
-                Ok(entry) => {
-                    if entry.file_type().map_or(false, |t| !t.is_dir()) {
-                        yaml_files.push(entry.into_path());
-                    }
-                }
-                Err(e) => {
-                    debug!("Failed to read directory entry: {}", e);
-                }
-            }
-        }
-        yaml_files.sort();
-        debug!("Found {} YAML files in {}", yaml_files.len(), path.display());
-        Self::from_yaml_files(&yaml_files, confidence)
+        Self::from_yaml_files(yaml_files_in(path)?, confidence)
     }
 
     #[inline]
@@ -233,6 +249,65 @@ impl IntoIterator for Rules {
     }
 }
 
+/// Lists the YAML files under `path`, sorted.
+fn yaml_files_in(path: &Path) -> Result<Vec<PathBuf>> {
+    let yaml_types = TypesBuilder::new().add_defaults().select("yaml").build().map_err(|e| {
+        error!("Failed to build YAML types: {}", e);
+        RulesError::YamlTypesBuildError(e.to_string())
+    })?;
+    let walker =
+        WalkBuilder::new(path).types(yaml_types).follow_links(true).standard_filters(false).build();
+    let mut yaml_files = Vec::new();
+    for entry in walker {
+        match entry {
+            Ok(entry) => {
+                if entry.file_type().map_or(false, |t| !t.is_dir()) {
+                    yaml_files.push(entry.into_path());
+                }
+            }
+            Err(e) => {
+                debug!("Failed to read directory entry: {}", e);
+            }
+        }
+    }
+    yaml_files.sort();
+    debug!("Found {} YAML files in {}", yaml_files.len(), path.display());
+    Ok(yaml_files)
+}
+
+fn yaml_parse_error(path: &Path, e: serde_yaml::Error) -> RulesError {
+    let Some(location) = e.location() else {
+        error!("Failed to parse rules YAML from {}: {}", path.display(), e);
+        return RulesError::YamlParseError(format!(
+            "Failed to load rules YAML from {}: {}",
+            path.display(),
+            e
+        ));
+    };
+    error!(
+        "Failed to parse rules YAML from {}: {}, at line: {}, column: {}",
+        path.display(),
+        e,
+        location.line(),
+        location.column()
+    );
+    // `ResponseMatcher` is untagged, so serde can only say that none of its
+    // variants matched.
+    if e.to_string().contains("untagged enum ResponseMatcher") {
+        RulesError::InvalidResponseMatcherVariant(
+            path.display().to_string(),
+            location.line(),
+            location.column(),
+        )
+    } else {
+        RulesError::YamlParseError(format!(
+            "Failed to load rules YAML from {}: {}",
+            path.display(),
+            e
+        ))
+    }
+}
+
 pub fn load_yaml_file<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T> {
     let path = path.as_ref();
     let file = File::open(path)
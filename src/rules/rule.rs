@@ -5,7 +5,8 @@
 //! and associated metadata such as confidence and fingerprints.
 
 use std::{
-    borrow::Cow, cmp::Ordering, collections::BTreeMap, fmt, hash::Hash, path::Path, str::FromStr,
+    borrow::Cow, cmp::Ordering, collections::BTreeMap, fmt, hash::Hash, ops::Range, path::Path,
+    str::FromStr,
 };
 
 use anyhow::{anyhow, Context, Result};
@@ -20,6 +21,8 @@ use serde::{Deserialize, Serialize};
 // use sha1::{Digest, Sha1};
 use xxhash_rust::xxh3::xxh3_64;
 
+use super::checksum::Checksum;
+
 /// Returns false as the default value.
 fn default_false() -> bool {
     false
@@ -52,6 +55,49 @@ pub struct DependsOnRule {
     pub variable: String,
 }
 
+/// Other rules that must match near a multi-part rule's match before it is
+/// reported, such as the username and host that go with a password.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct Requirement {
+    /// IDs of the rules that must all match.
+    pub rules: Vec<String>,
+    /// Maximum number of line breaks between this match and each part.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within_lines: Option<usize>,
+    /// Maximum number of bytes between this match and each part.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within_bytes: Option<usize>,
+}
+
+impl Requirement {
+    /// Line distance used when neither `within_lines` nor `within_bytes` is set.
+    pub const DEFAULT_WITHIN_LINES: usize = 5;
+
+    /// Whether a part spanning `part` is close enough to a match spanning
+    /// `primary`. Both are byte ranges into `input`; overlapping spans are
+    /// always close enough.
+    pub fn is_near(&self, input: &[u8], primary: Range<usize>, part: Range<usize>) -> bool {
+        let gap = if part.start >= primary.end {
+            primary.end..part.start
+        } else if primary.start >= part.end {
+            part.end..primary.start
+        } else {
+            return true;
+        };
+        let within_lines = match (self.within_lines, self.within_bytes) {
+            (None, None) => Some(Self::DEFAULT_WITHIN_LINES),
+            (lines, _) => lines,
+        };
+        self.within_bytes.is_none_or(|max| gap.len() <= max)
+            && within_lines
+                .is_none_or(|max| input.get(gap).is_some_and(|gap| bytecount_newlines(gap) <= max))
+    }
+}
+
+fn bytecount_newlines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b == b'\n').count()
+}
+
 /// Configuration for HTTP validation. This contains a request configuration
 /// and an optional multipart configuration.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
@@ -250,6 +296,31 @@ impl<'de> Deserialize<'de> for Confidence {
     }
 }
 
+/// How much damage a leak of the secret could do, independent of how
+/// confident the rule is that a match is real.
+#[derive(Serialize, Deserialize, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 /// The syntactic representation of a rule.
 #[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone)]
 pub struct RuleSyntax {
@@ -268,6 +339,12 @@ pub struct RuleSyntax {
     /// Whether the rule is visible to end-users.
     #[serde(default = "default_true")]
     pub visible: bool,
+    /// Impact of a leak, separate from the confidence of the match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Free-form labels such as `cloud`, `ai`, `payment` or `pii`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     /// Example inputs that should match.
     #[serde(default)]
     pub examples: Vec<String>,
@@ -283,6 +360,52 @@ pub struct RuleSyntax {
     /// Optional dependencies on other rules.
     #[serde(default)]
     pub depends_on_rule: Vec<Option<DependsOnRule>>,
+    /// Other rules that must match nearby for this rule to report a finding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires: Option<Requirement>,
+    /// Liquid filters applied in order to every capture before it is
+    /// fingerprinted, deduplicated or validated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transform: Vec<String>,
+    /// Globs a blob path must match for the rule to apply.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_paths: Vec<String>,
+    /// Globs that stop the rule from applying to a matching blob path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_paths: Vec<String>,
+    /// Languages (as guessed from the blob) the rule is limited to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// Offline checksum the matched token must carry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<Checksum>,
+}
+
+/// An empty, visible rule; mostly useful as the base of a struct literal.
+impl Default for RuleSyntax {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            id: String::new(),
+            pattern: String::new(),
+            min_entropy: 0.0,
+            confidence: Confidence::default(),
+            visible: true,
+            severity: None,
+            tags: Vec::new(),
+            examples: Vec::new(),
+            negative_examples: Vec::new(),
+            references: Vec::new(),
+            validation: None,
+            depends_on_rule: Vec::new(),
+            requires: None,
+            transform: Vec::new(),
+            include_paths: Vec::new(),
+            exclude_paths: Vec::new(),
+            languages: Vec::new(),
+            checksum: None,
+        }
+    }
 }
 
 lazy_static! {
@@ -333,6 +456,7 @@ impl RuleSyntax {
     ///     visible: true,
     ///     validation: None,
     ///     depends_on_rule: vec![],
+    ///     ..Default::default()
     /// };
     /// assert_eq!(r.as_anchored_regex().unwrap().as_str(), r"hello\s*world$");
     /// ```
@@ -436,4 +560,25 @@ impl Rule {
     pub fn confidence(&self) -> Confidence {
         self.syntax.confidence
     }
+
+    /// Returns the severity of the rule, if it sets one.
+    pub fn severity(&self) -> Option<Severity> {
+        self.syntax.severity
+    }
+
+    /// Returns the tags of the rule.
+    pub fn tags(&self) -> &[String] {
+        &self.syntax.tags
+    }
+
+    /// Whether the rule carries `tag`, compared case-insensitively.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.syntax.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Rules that must match near this one, if it is a multi-part rule.
+    #[inline]
+    pub fn requires(&self) -> Option<&Requirement> {
+        self.syntax.requires.as_ref()
+    }
 }
@@ -0,0 +1,154 @@
+//! Path and language scoping for rules.
+//!
+//! A rule can restrict where it applies with `include_paths`, `exclude_paths`
+//! and `languages`. Globs that do not start with `/` or `**` are anchored
+//! anywhere in the path, so `*.tfvars` matches `infra/prod.tfvars` and
+//! `test/**` matches `src/test/fixtures.rs`. A single `*` never crosses a
+//! directory separator.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use super::rule::RuleSyntax;
+
+/// Compiled form of a rule's `include_paths`, `exclude_paths` and `languages`.
+#[derive(Debug, Clone)]
+pub struct RuleScope {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    languages: Vec<String>,
+}
+
+impl RuleScope {
+    /// Compiles the scope of a rule, returning `None` for rules that apply everywhere.
+    pub fn from_syntax(syntax: &RuleSyntax) -> Result<Option<Self>> {
+        if syntax.include_paths.is_empty()
+            && syntax.exclude_paths.is_empty()
+            && syntax.languages.is_empty()
+        {
+            return Ok(None);
+        }
+        let include = build_globset(&syntax.include_paths)
+            .with_context(|| format!("Invalid include_paths for rule `{}`", syntax.id))?;
+        let exclude = build_globset(&syntax.exclude_paths)
+            .with_context(|| format!("Invalid exclude_paths for rule `{}`", syntax.id))?;
+        let languages = syntax.languages.iter().map(|l| l.to_ascii_lowercase()).collect();
+        Ok(Some(Self { include, exclude, languages }))
+    }
+
+    /// Whether a blob with the given path and guessed language is in scope.
+    ///
+    /// Blobs without a path never satisfy `include_paths`, and blobs whose
+    /// language could not be guessed never satisfy `languages`.
+    pub fn allows(&self, path: Option<&Path>, language: Option<&str>) -> bool {
+        if let Some(include) = &self.include {
+            if !path.is_some_and(|p| include.is_match(p)) {
+                return false;
+            }
+        }
+        if let (Some(exclude), Some(path)) = (&self.exclude, path) {
+            if exclude.is_match(path) {
+                return false;
+            }
+        }
+        if !self.languages.is_empty() {
+            return language
+                .is_some_and(|lang| self.languages.iter().any(|l| l.eq_ignore_ascii_case(lang)));
+        }
+        true
+    }
+}
+
+fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let anchored = if pattern.starts_with('/') || pattern.starts_with("**") {
+            pattern.clone()
+        } else {
+            format!("**/{pattern}")
+        };
+        let glob = GlobBuilder::new(&anchored)
+            .literal_separator(true)
+            .build()
+            .with_context(|| format!("Invalid glob `{pattern}`"))?;
+        builder.add(glob);
+    }
+    Ok(Some(builder.build()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(include: &[&str], exclude: &[&str], languages: &[&str]) -> RuleScope {
+        let to_vec = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let syntax = RuleSyntax {
+            name: "Scoped".into(),
+            id: "test.scoped.1".into(),
+            pattern: "x".into(),
+            min_entropy: 0.0,
+            confidence: Default::default(),
+            visible: true,
+            examples: vec![],
+            negative_examples: vec![],
+            references: vec![],
+            validation: None,
+            depends_on_rule: vec![],
+            include_paths: to_vec(include),
+            exclude_paths: to_vec(exclude),
+            languages: to_vec(languages),
+            ..Default::default()
+        };
+        RuleScope::from_syntax(&syntax).unwrap().unwrap()
+    }
+
+    #[test]
+    fn include_paths_match_anywhere_in_path() {
+        let s = scope(&["*.tfvars", ".env"], &[], &[]);
+        assert!(s.allows(Some(Path::new("infra/prod.tfvars")), None));
+        assert!(s.allows(Some(Path::new("/home/me/app/.env")), None));
+        assert!(s.allows(Some(Path::new(".env")), None));
+        assert!(!s.allows(Some(Path::new("infra/main.tf")), None));
+        assert!(!s.allows(None, None));
+    }
+
+    #[test]
+    fn exclude_paths_win_over_include_paths() {
+        let s = scope(&["*.yml"], &["test/**", "*.lock"], &[]);
+        assert!(s.allows(Some(Path::new("config/application.yml")), None));
+        assert!(!s.allows(Some(Path::new("src/test/application.yml")), None));
+        let s = scope(&[], &["*.lock"], &[]);
+        assert!(!s.allows(Some(Path::new("Cargo.lock")), None));
+        assert!(s.allows(Some(Path::new("Cargo.toml")), None));
+        assert!(s.allows(None, None));
+    }
+
+    #[test]
+    fn languages_compare_case_insensitively() {
+        let s = scope(&[], &[], &["yaml", "Python"]);
+        assert!(s.allows(None, Some("YAML")));
+        assert!(s.allows(None, Some("python")));
+        assert!(!s.allows(None, Some("Rust")));
+        assert!(!s.allows(None, None));
+    }
+
+    #[test]
+    fn unscoped_rules_have_no_scope() {
+        let syntax: RuleSyntax = serde_yaml::from_str("name: n\nid: test.1\npattern: x\n").unwrap();
+        assert!(RuleScope::from_syntax(&syntax).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_glob_is_an_error() {
+        let syntax: RuleSyntax =
+            serde_yaml::from_str("name: n\nid: test.1\npattern: x\ninclude_paths: ['a[']\n")
+                .unwrap();
+        let err = RuleScope::from_syntax(&syntax).unwrap_err();
+        assert!(format!("{err:#}").contains("test.1"));
+    }
+}
@@ -0,0 +1,378 @@
+//! Named validation templates.
+//!
+//! A rule file can declare templates under a top-level `validation_templates`
+//! key and rules can use one in place of a full `validation` block:
+//!
+//! ```yaml
+//! validation_templates:
+//!   bearer_get:
+//!     params:
+//!       url:                  # no default: required
+//!       status: [200]         # default used when the rule does not set it
+//!     validation:
+//!       type: Http
+//!       content:
+//!         request:
+//!           method: GET
+//!           url: ${url}
+//!           headers:
+//!             Authorization: Bearer {{ TOKEN }}
+//!           response_matcher:
+//!             - type: StatusMatch
+//!               status: ${status}
+//!
+//! rules:
+//!   - name: Acme API Key
+//!     id: kingfisher.acme.1
+//!     pattern: ...
+//!     validation:
+//!       template: bearer_get
+//!       url: https://api.acme.example/v1/me
+//! ```
+//!
+//! `${name}` placeholders are replaced when the file is loaded, before the
+//! Liquid `{{ ... }}` variables that are rendered at validation time. A string
+//! that is exactly one placeholder takes the parameter's value as-is, so lists
+//! and numbers keep their type. Templates are shared by every file loaded
+//! together, and the built-in templates are available to custom rules.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use anyhow::{bail, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_yaml::{Mapping, Value};
+
+use super::RulesError;
+
+/// Top-level key under which a rule file declares templates.
+pub const TEMPLATES_KEY: &str = "validation_templates";
+
+/// Key that turns a rule's `validation` block into a template reference.
+const TEMPLATE_REF_KEY: &str = "template";
+
+lazy_static! {
+    static ref PLACEHOLDER: Regex =
+        Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("placeholder regex should compile");
+}
+
+#[derive(Clone, Debug)]
+struct Template {
+    path: String,
+    builtin: bool,
+    /// Parameter defaults; `None` marks a required parameter.
+    params: BTreeMap<String, Option<Value>>,
+    body: Value,
+}
+
+/// Templates visible to a set of rule files.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationTemplates {
+    templates: BTreeMap<String, Template>,
+}
+
+impl ValidationTemplates {
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Marks every template as built-in, letting custom rule files override them.
+    pub fn into_builtin(mut self) -> Self {
+        for template in self.templates.values_mut() {
+            template.builtin = true;
+        }
+        self
+    }
+
+    /// Registers the templates declared in a parsed rule file.
+    pub fn collect(&mut self, path: &Path, doc: &Value) -> Result<()> {
+        let Some(declared) = doc.get(TEMPLATES_KEY) else {
+            return Ok(());
+        };
+        let path = path.display().to_string();
+        let Some(declared) = declared.as_mapping() else {
+            bail!(RulesError::InvalidValidationTemplate {
+                path,
+                template: TEMPLATES_KEY.to_string(),
+                message: "expected a mapping of template names".into(),
+            });
+        };
+        for (name, def) in declared {
+            let name = name.as_str().unwrap_or_default().to_string();
+            let template = parse_template(&path, &name, def)?;
+            if let Some(existing) = self.templates.get(&name) {
+                if !existing.builtin {
+                    bail!(RulesError::DuplicateValidationTemplate {
+                        template: name,
+                        first: existing.path.clone(),
+                        second: path,
+                    });
+                }
+            }
+            self.templates.insert(name, template);
+        }
+        Ok(())
+    }
+
+    /// Replaces template references in a parsed rule file with the expanded
+    /// validation blocks, returning whether anything was expanded.
+    pub fn expand(&self, path: &Path, doc: &mut Value) -> Result<bool> {
+        let Some(rules) = doc.get_mut("rules").and_then(Value::as_sequence_mut) else {
+            return Ok(false);
+        };
+        let mut expanded = false;
+        for rule in rules {
+            let rule_id = rule.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+            let Some(validation) = rule.get_mut("validation") else {
+                continue;
+            };
+            let Some(reference) = validation.as_mapping() else {
+                continue;
+            };
+            let Some(name) = reference.get(TEMPLATE_REF_KEY) else {
+                continue;
+            };
+            let name = name.as_str().unwrap_or_default();
+            let Some(template) = self.templates.get(name) else {
+                bail!(RulesError::UnknownValidationTemplate {
+                    path: path.display().to_string(),
+                    rule_id,
+                    template: name.to_string(),
+                });
+            };
+            let params = bind_params(template, name, reference)
+                .map_err(|message| reference_error(path, &rule_id, message))?;
+            *validation = substitute(&template.body, &params)
+                .map_err(|message| reference_error(path, &rule_id, message))?;
+            expanded = true;
+        }
+        Ok(expanded)
+    }
+}
+
+fn reference_error(path: &Path, rule_id: &str, message: String) -> RulesError {
+    RulesError::InvalidTemplateReference {
+        path: path.display().to_string(),
+        rule_id: rule_id.to_string(),
+        message,
+    }
+}
+
+fn parse_template(path: &str, name: &str, def: &Value) -> Result<Template> {
+    let invalid = |message: String| RulesError::InvalidValidationTemplate {
+        path: path.to_string(),
+        template: name.to_string(),
+        message,
+    };
+    let Some(body) = def.get("validation") else {
+        bail!(invalid("missing `validation`".into()));
+    };
+    let mut params = BTreeMap::new();
+    if let Some(declared) = def.get("params") {
+        let Some(declared) = declared.as_mapping() else {
+            bail!(invalid("`params` must be a mapping of names to defaults".into()));
+        };
+        for (param, default) in declared {
+            let Some(param) = param.as_str() else {
+                bail!(invalid("parameter names must be strings".into()));
+            };
+            let default = (!default.is_null()).then(|| default.clone());
+            params.insert(param.to_string(), default);
+        }
+    }
+    let mut used = BTreeSet::new();
+    collect_placeholders(body, &mut used);
+    if let Some(undeclared) = used.iter().find(|p| !params.contains_key(*p)) {
+        bail!(invalid(format!("placeholder `${{{undeclared}}}` is not a declared parameter")));
+    }
+    Ok(Template { path: path.to_string(), builtin: false, params, body: body.clone() })
+}
+
+fn collect_placeholders(value: &Value, out: &mut BTreeSet<String>) {
+    match value {
+        Value::String(s) => {
+            out.extend(PLACEHOLDER.captures_iter(s).map(|c| c[1].to_string()));
+        }
+        Value::Sequence(items) => items.iter().for_each(|v| collect_placeholders(v, out)),
+        Value::Mapping(map) => map.values().for_each(|v| collect_placeholders(v, out)),
+        Value::Tagged(tagged) => collect_placeholders(&tagged.value, out),
+        _ => {}
+    }
+}
+
+fn bind_params(
+    template: &Template,
+    name: &str,
+    reference: &Mapping,
+) -> Result<BTreeMap<String, Value>, String> {
+    let mut bound = BTreeMap::new();
+    for (key, value) in reference {
+        let key = key.as_str().unwrap_or_default();
+        if key == TEMPLATE_REF_KEY {
+            continue;
+        }
+        if !template.params.contains_key(key) {
+            return Err(format!("template `{name}` has no parameter `{key}`"));
+        }
+        bound.insert(key.to_string(), value.clone());
+    }
+    for (param, default) in &template.params {
+        if bound.contains_key(param) {
+            continue;
+        }
+        match default {
+            Some(default) => {
+                bound.insert(param.clone(), default.clone());
+            }
+            None => return Err(format!("template `{name}` requires parameter `{param}`")),
+        }
+    }
+    Ok(bound)
+}
+
+fn substitute(value: &Value, params: &BTreeMap<String, Value>) -> Result<Value, String> {
+    Ok(match value {
+        Value::String(s) => {
+            if let Some(whole) = PLACEHOLDER.captures(s).filter(|c| c[0].len() == s.len()) {
+                return Ok(params[&whole[1]].clone());
+            }
+            let mut error = None;
+            let replaced = PLACEHOLDER.replace_all(s, |c: &regex::Captures<'_>| {
+                match &params[&c[1]] {
+                    Value::String(v) => v.clone(),
+                    Value::Number(n) => n.to_string(),
+                    Value::Bool(b) => b.to_string(),
+                    _ => {
+                        error = Some(format!(
+                            "parameter `{}` must be a string, number or boolean to be used inside `{s}`",
+                            &c[1]
+                        ));
+                        String::new()
+                    }
+                }
+            });
+            if let Some(error) = error {
+                return Err(error);
+            }
+            Value::String(replaced.into_owned())
+        }
+        Value::Sequence(items) => {
+            Value::Sequence(items.iter().map(|v| substitute(v, params)).collect::<Result<_, _>>()?)
+        }
+        Value::Mapping(map) => {
+            let mut out = Mapping::with_capacity(map.len());
+            for (k, v) in map {
+                out.insert(k.clone(), substitute(v, params)?);
+            }
+            Value::Mapping(out)
+        }
+        other => other.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(yaml: &str) -> Value {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn templates(yaml: &str) -> Result<ValidationTemplates> {
+        let mut templates = ValidationTemplates::default();
+        templates.collect(Path::new("templates.yml"), &doc(yaml))?;
+        Ok(templates)
+    }
+
+    const GOOD_TEMPLATES: &str = r#"
+validation_templates:
+  bearer_get:
+    params:
+      url:
+      status: [200]
+      version: 1
+    validation:
+      type: Http
+      content:
+        request:
+          method: GET
+          url: ${url}
+          headers:
+            Authorization: Bearer {{ TOKEN }}
+            X-Api-Version: v${version}
+          response_matcher:
+            - type: StatusMatch
+              status: ${status}
+"#;
+
+    #[test]
+    fn expands_references_with_defaults_and_typed_values() -> Result<()> {
+        let templates = templates(GOOD_TEMPLATES)?;
+        let mut rules = doc(r#"
+rules:
+  - id: acme.1
+    validation:
+      template: bearer_get
+      url: https://api.acme.example/v1/me?q={{ TOKEN }}
+  - id: acme.2
+    validation:
+      template: bearer_get
+      url: https://api.acme.example/v2/me
+      status: [200, 403]
+      version: 2
+"#);
+        assert!(templates.expand(Path::new("acme.yml"), &mut rules)?);
+        let request = |i: usize| rules["rules"][i]["validation"]["content"]["request"].clone();
+        assert_eq!(request(0)["url"], "https://api.acme.example/v1/me?q={{ TOKEN }}");
+        assert_eq!(request(0)["headers"]["Authorization"], "Bearer {{ TOKEN }}");
+        assert_eq!(request(0)["headers"]["X-Api-Version"], "v1");
+        assert_eq!(request(0)["response_matcher"][0]["status"], doc("[200]"));
+        assert_eq!(request(1)["response_matcher"][0]["status"], doc("[200, 403]"));
+        assert_eq!(request(1)["headers"]["X-Api-Version"], "v2");
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_bad_references() -> Result<()> {
+        let templates = templates(GOOD_TEMPLATES)?;
+        let expand = |yaml: &str| {
+            let err = templates.expand(Path::new("acme.yml"), &mut doc(yaml)).unwrap_err();
+            err.to_string()
+        };
+        let unknown = expand("rules:\n  - id: a.1\n    validation: {template: nope}\n");
+        assert!(unknown.contains("unknown validation template `nope`"), "{unknown}");
+        let missing = expand("rules:\n  - id: a.1\n    validation: {template: bearer_get}\n");
+        assert!(missing.contains("requires parameter `url`"), "{missing}");
+        let extra =
+            expand("rules:\n  - id: a.1\n    validation: {template: bearer_get, url: x, uri: y}\n");
+        assert!(extra.contains("no parameter `uri`"), "{extra}");
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_undeclared_placeholders_and_duplicates() {
+        let typo = GOOD_TEMPLATES.replace("v${version}", "v${verison}");
+        let err = templates(&typo).unwrap_err().to_string();
+        assert!(err.contains("`${verison}` is not a declared parameter"), "{err}");
+
+        let mut templates = templates(GOOD_TEMPLATES).unwrap();
+        let err = templates.collect(Path::new("other.yml"), &doc(GOOD_TEMPLATES)).unwrap_err();
+        assert!(err.to_string().contains("defined in both"), "{err}");
+
+        let mut templates = templates.into_builtin();
+        assert!(templates.collect(Path::new("custom.yml"), &doc(GOOD_TEMPLATES)).is_ok());
+    }
+
+    #[test]
+    fn files_without_references_are_untouched() -> Result<()> {
+        let templates = templates(GOOD_TEMPLATES)?;
+        let yaml = "rules:\n  - id: a.1\n    validation: {type: Raw, content: x}\n";
+        let mut rules = doc(yaml);
+        assert!(!templates.expand(Path::new("a.yml"), &mut rules)?);
+        assert_eq!(rules, doc(yaml));
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! Post-match transforms for rule captures.
+//!
+//! A rule's `transform` list holds Liquid filters, written the way they would
+//! appear after a `|` in a validation template:
+//!
+//! ```yaml
+//! transform:
+//!   - strip_quotes
+//!   - unescape
+//!   - "replace: ' ', ''"
+//! ```
+//!
+//! The filters run in order on every capture of a match, so the normalized
+//! value is what gets fingerprinted, deduplicated, baselined and validated.
+//! Filters whose output changes from one run to the next (timestamps, UUIDs,
+//! random strings) are rejected because they would break deduplication.
+
+use anyhow::{bail, Context, Result};
+use liquid::{object, Parser, ParserBuilder, Template};
+use once_cell::sync::Lazy;
+use tracing::debug;
+
+use super::rule::RuleSyntax;
+use crate::liquid_filters::register_all;
+
+const NONDETERMINISTIC_FILTERS: &[&str] =
+    &["unix_timestamp", "iso_timestamp", "iso_timestamp_no_frac", "uuid", "random_string"];
+
+static PARSER: Lazy<Parser> = Lazy::new(|| {
+    register_all(ParserBuilder::with_stdlib()).build().expect("liquid parser should build")
+});
+
+/// Compiled form of a rule's `transform` pipeline.
+pub struct RuleTransform {
+    template: Template,
+}
+
+impl RuleTransform {
+    /// Compiles the transform of a rule, returning `None` for rules without one.
+    pub fn from_syntax(syntax: &RuleSyntax) -> Result<Option<Self>> {
+        if syntax.transform.is_empty() {
+            return Ok(None);
+        }
+        Self::compile(&syntax.transform)
+            .map(Some)
+            .with_context(|| format!("Invalid transform for rule `{}`", syntax.id))
+    }
+
+    fn compile(filters: &[String]) -> Result<Self> {
+        for filter in filters {
+            let name = filter.split(':').next().unwrap_or_default().trim();
+            if name.is_empty() || filter.contains("}}") || filter.contains("{%") {
+                bail!("`{filter}` is not a filter expression");
+            }
+            if NONDETERMINISTIC_FILTERS.contains(&name) {
+                bail!("filter `{name}` does not give the same output for the same input");
+            }
+        }
+        let source = format!("{{{{ value | {} }}}}", filters.join(" | "));
+        let template = PARSER.parse(&source).map_err(|e| anyhow::anyhow!("{e}"))?;
+        Ok(Self { template })
+    }
+
+    /// Runs the pipeline on one captured value. Values the filters cannot
+    /// handle (such as invalid Base64 passed to `b64dec`) are kept unchanged.
+    pub fn apply(&self, value: &str) -> String {
+        match self.template.render(&object!({ "value": value })) {
+            Ok(transformed) => transformed,
+            Err(e) => {
+                debug!("Transform failed, keeping the captured value: {e}");
+                value.to_string()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(filters: &[&str]) -> Result<RuleTransform> {
+        RuleTransform::compile(&filters.iter().map(|f| f.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn filters_run_in_order() {
+        let t = transform(&["strip_quotes", "unescape", "replace: 'KEY', 'key'"]).unwrap();
+        assert_eq!(t.apply(r#""-----BEGIN KEY-----\nabc""#), "-----BEGIN key-----\nabc");
+    }
+
+    #[test]
+    fn failing_filters_keep_the_value() {
+        let t = transform(&["b64dec"]).unwrap();
+        assert_eq!(t.apply("not base64!"), "not base64!");
+    }
+
+    #[test]
+    fn rejects_nondeterministic_and_unknown_filters() {
+        assert!(transform(&["uuid"]).is_err());
+        assert!(transform(&["random_string: 8"]).is_err());
+        assert!(transform(&["no_such_filter"]).is_err());
+        assert!(transform(&["strip_quotes }}{{ 'x'"]).is_err());
+    }
+}
@@ -1,17 +1,30 @@
 use std::{sync::Arc, time::Instant};
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use once_cell::sync::OnceCell;
 use regex::bytes::Regex;
+use rustc_hash::FxHashMap;
 use tracing::{debug, debug_span, error};
-use vectorscan_rs::{BlockDatabase, Flag, Pattern};
+use vectorscan_rs::{BlockDatabase, Flag, Pattern, StreamingDatabase};
+use xxhash_rust::xxh3::Xxh3;
 
-use crate::rules::rule::{Rule, RULE_COMMENTS_PATTERN};
+use crate::rules::{
+    rule::{Rule, RULE_COMMENTS_PATTERN},
+    scope::RuleScope,
+    transform::RuleTransform,
+};
 
 pub struct RulesDatabase {
     // pub(crate) rules: Vec<Rule,>,
     pub(crate) rules: Vec<Arc<Rule>>,
     pub(crate) anchored_regexes: Vec<Regex>,
     pub(crate) vsdb: BlockDatabase,
+    /// Index and compiled scope of every rule limited to certain paths or languages.
+    pub(crate) rule_scopes: Vec<(usize, RuleScope)>,
+    /// Compiled `transform` pipeline of every rule that has one, by rule index.
+    pub(crate) rule_transforms: FxHashMap<usize, RuleTransform>,
+    /// Stream-mode database for files too large to load, compiled on first use.
+    streaming_db: OnceCell<StreamingDatabase>,
 }
 
 pub fn format_regex_pattern(pattern: &str) -> String {
@@ -69,7 +82,16 @@ impl RulesDatabase {
                 let d1 = t1.elapsed().as_secs_f64();
                 let (anchored_regexes, d2) = Self::compile_regexes(&rules)?;
                 debug!("Compiled {} rules: vectorscan {}s; regex {}s", rules.len(), d1, d2);
-                Ok(RulesDatabase { rules, vsdb, anchored_regexes })
+                let rule_scopes = Self::compile_scopes(&rules)?;
+                let rule_transforms = Self::compile_transforms(&rules)?;
+                Ok(RulesDatabase {
+                    rules,
+                    vsdb,
+                    anchored_regexes,
+                    rule_scopes,
+                    rule_transforms,
+                    streaming_db: OnceCell::new(),
+                })
             }
             Err(e) => {
                 error!(
@@ -82,7 +104,7 @@ impl RulesDatabase {
         }
     }
 
-    fn compile_rules_individually(rules: Vec<Arc<Rule>>) -> Result<Self> {
+    pub(crate) fn compile_rules_individually(rules: Vec<Arc<Rule>>) -> Result<Self> {
         // NOTE: This function only used when attempting to determine which rule failed
         // to compile
         let mut compiled_rules = Vec::new();
@@ -137,7 +159,64 @@ impl RulesDatabase {
             bail!("Failed to compile the following rules:\n{}", error_messages.join("\n"));
         }
         let vsdb = BlockDatabase::new(compiled_patterns)?;
-        Ok(RulesDatabase { rules: compiled_rules, vsdb, anchored_regexes: compiled_regexes })
+        let rule_scopes = Self::compile_scopes(&compiled_rules)?;
+        let rule_transforms = Self::compile_transforms(&compiled_rules)?;
+        Ok(RulesDatabase {
+            rules: compiled_rules,
+            vsdb,
+            anchored_regexes: compiled_regexes,
+            rule_scopes,
+            rule_transforms,
+            streaming_db: OnceCell::new(),
+        })
+    }
+
+    /// Returns the rules compiled for Vectorscan's streaming mode, compiling
+    /// them on the first call. Pattern ids are rule indices, as in `vsdb`.
+    pub(crate) fn streaming_db(&self) -> Result<&StreamingDatabase> {
+        self.streaming_db.get_or_try_init(|| {
+            let t1 = Instant::now();
+            let patterns = self
+                .rules
+                .iter()
+                .enumerate()
+                .map(|(id, rule)| {
+                    Pattern::new(
+                        rule.syntax().pattern.clone().into_bytes(),
+                        Flag::default(),
+                        Some(id.try_into().unwrap()),
+                    )
+                })
+                .collect();
+            let db = StreamingDatabase::new(patterns)
+                .context("Failed to compile rules for streaming")?;
+            debug!(
+                "Compiled {} rules for streaming in {}s",
+                self.rules.len(),
+                t1.elapsed().as_secs_f64()
+            );
+            Ok(db)
+        })
+    }
+
+    fn compile_scopes(rules: &[Arc<Rule>]) -> Result<Vec<(usize, RuleScope)>> {
+        let mut scopes = Vec::new();
+        for (id, rule) in rules.iter().enumerate() {
+            if let Some(scope) = RuleScope::from_syntax(rule.syntax())? {
+                scopes.push((id, scope));
+            }
+        }
+        Ok(scopes)
+    }
+
+    fn compile_transforms(rules: &[Arc<Rule>]) -> Result<FxHashMap<usize, RuleTransform>> {
+        let mut transforms = FxHashMap::default();
+        for (id, rule) in rules.iter().enumerate() {
+            if let Some(transform) = RuleTransform::from_syntax(rule.syntax())? {
+                transforms.insert(id, transform);
+            }
+        }
+        Ok(transforms)
     }
 
     fn compile_regexes(rules: &[Arc<Rule>]) -> Result<(Vec<Regex>, f64)> {
@@ -180,6 +259,33 @@ impl RulesDatabase {
     pub fn rules(&self) -> &[Arc<Rule>] {
         &self.rules
     }
+
+    /// Hash identifying this exact ruleset, independent of rule order.
+    ///
+    /// Combines each rule's pattern fingerprint with its effective entropy
+    /// threshold and the rest of its definition, so any change that could
+    /// alter the matches produced for a blob yields a different value.
+    pub fn fingerprint(&self) -> String {
+        let mut entries: Vec<_> = self
+            .rules
+            .iter()
+            .map(|r| {
+                format!(
+                    "{}|{}|{}",
+                    r.finding_sha1_fingerprint(),
+                    r.min_entropy(),
+                    r.syntax().to_json()
+                )
+            })
+            .collect();
+        entries.sort_unstable();
+        let mut hasher = Xxh3::new();
+        for entry in &entries {
+            hasher.update(entry.as_bytes());
+            hasher.update(b"\0");
+        }
+        format!("{:x}", hasher.digest())
+    }
 }
 #[cfg(test)]
 mod test_vectorscan {
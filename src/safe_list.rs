@@ -9,15 +9,36 @@
 //   if let Some(reason) = is_safe_match_reason(bytes) {
 //       // reason contains the rule description
 //   }
+//
+// Users can add their own safe rules and disable built-in ones by id with a
+// safe-list file (`--safe-list`), see `load_user_safe_list`.
+
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
 
+use anyhow::{bail, Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use once_cell::sync::Lazy;
 use regex::bytes::Regex;
-use std::sync::Mutex;
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
+use crate::{
+    allowlist::{Allowlist, AllowlistContext},
+    util::intern,
+};
+
 /// A rule that describes *why* a match is considered safe/benign.
 #[derive(Debug)]
 struct SafeRule {
+    /// Stable id, used to disable the rule and shown with suppressed matches.
+    id: &'static str,
     /// Human-friendly reason that will be logged when this rule fires.
     description: &'static str,
     /// Compiled regex to detect the benign pattern.
@@ -40,102 +61,313 @@ fn compile(pattern: &'static str) -> Regex {
 static SAFE_LIST_FILTER_RULES: Lazy<Vec<SafeRule>> = Lazy::new(|| {
     vec![
         SafeRule {
+            id: "examplekey-assignment",
             description: "Assignment ending with EXAMPLEKEY (placeholder)",
             regex: compile(r"(?i)[:=][^:=]{0,64}EXAMPLEKEY"),
         },
         SafeRule {
+            id: "aws-example-key",
             description: "AWS AKIA key explicitly marked as example/fake/test/sample",
             regex: compile(r"(?i)\b(AKIA(?:.*?EXAMPLE|.*?FAKE|TEST|.*?SAMPLE))\b"),
         },
         SafeRule {
+            id: "redaction-marker",
             description: "Secret-like key followed by redaction marker (&&, ||, or ***** run)",
             regex: compile(
                 r"(?i)(password|pass|pwd|passwd|secret|cred|key|auth|authorization)[^=:?]{0,8}[=:?][^=:?]{0,8}\s(&&|\|\||\*{5,50})",
             ),
         },
         SafeRule {
+            id: "chained-short-assignment",
             description: "Secret-like key + short value followed by another short assignment on same line (example-y)",
             regex: compile(
                 r"(?i)(password|pass|pwd|passwd|secret|cred|key|auth|authorization)[^=:?]{0,8}[=:?][^=:?]{0,8}\b\w{4,12}\s{0,6}=\s{0,6}\D{0,3}\w{1,12}",
             ),
         },
         SafeRule {
+            id: "shell-variable-reference",
             description: "Secret-like key assigned from a shell variable reference (e.g., $FOO), not a literal",
             regex: compile(
                 r"(?i)(password|pass|pwd|passwd|secret|cred|key|auth|authorization)[^=:?]{0,8}[=:?][^=:?]{0,8}\$\w{4,30}",
             ),
         },
         SafeRule {
+            id: "openssl-rand",
             description: "Secret-like key set via randomness generator command (openssl rand ...), not a literal",
             regex: compile(
                 r"(?i)(password|pass|pwd|passwd|secret|cred|key|auth|authorization)[^=:?]{0,16}[=:?][^=:?]{0,8}\bopenssl\s{0,4}rand\b",
             ),
         },
         SafeRule {
+            id: "encrypted-marker",
             description: "Secret-like key assigned a value containing 'encrypted' (metadata/marker)",
             regex: compile(
                 r"(?i)(password|pass|pwd|passwd|secret|cred|key|auth|authorization)[^=:?]{0,8}[=:?][^=:?]{0,8}encrypted",
             ),
         },
         SafeRule {
+            id: "boolean-literal",
             description: "Secret-like key assigned boolean literal (true/false)",
             regex: compile(
                 r"(?i)(password|pass|pwd|passwd|secret|cred|key|auth|authorization)[^=:?]{0,8}[=:?][^=:?]{0,8}\b(?:false|true)\b",
             ),
         },
         SafeRule {
+            id: "null-placeholder",
             description: "Secret-like key assigned to null-ish or self-referential placeholders",
             regex: compile(
                 r"(?i)(password|pass|pwd|passwd|secret|cred|key|auth|authorization)[^=:?]{0,8}[=:?][^=:?]{0,8}\b(null|nil|none|password|pass|pwd|passwd|secret|cred|key|auth|authorization).{1,6}$",
             ),
         },
         SafeRule {
+            id: "hunter2",
             description: "Classic xkcd fake password 'hunter2'",
             regex: compile(
                 r"(?i)(password|pass|pwd|passwd|secret|cred|key|auth|authorization)[^=:?]{0,8}[=:?][^=:?]{0,8}hunter2",
             ),
         },
         SafeRule {
+            id: "placeholder-sequence",
             description: "Obvious placeholder sequences (123456789 or abcdefghij)",
             regex: compile(r"(?i)123456789|abcdefghij"),
         },
         SafeRule {
+            id: "secretmanager-tag",
             description: "Literal placeholder tag '<secretmanager>'",
             regex: compile(r"(?i)<secretmanager>"),
         },
         SafeRule {
+            id: "openapi-schema-reference",
             description: "OpenAPI schema references near assignment/query (not a secret)",
             regex: compile(r"(?i)[=:?][^=:?]{0,8}#/components/schemas/"),
         },
         SafeRule {
+            id: "example-mongodb-uri",
             description: "Example MongoDB URI with placeholder user/pass like user:pass or foo:bar",
             regex: compile(
                 r"(?i)\b(mongodb(?:\+srv)?://(?:user|foo)[^:@]+:(?:pass|bar)[^@]+@[-\w.%+/:]{3,64}(?:/\w+)?)",
             ),
         },
         SafeRule {
+            id: "classpath-uri",
             description: "Classpath URI (configuration reference, not a secret)",
             regex: compile(r"(?i)\b(classpath://)"),
         },
         SafeRule {
+            id: "property-placeholder",
             description: "Assignment using property placeholder like ${ENV_VAR}",
             regex: compile(r"(?i)(\b[^\s\t]{0,16}[=:][^$]*\$\{[a-z_-]{5,30}\})"),
         },
         SafeRule {
+            id: "example-basic-auth-url",
             description: "URL with basic auth to host ending in example/test (placeholder)",
             regex: compile(r"(?i)\b((?:https?:)?//[^:@]{3,50}:[^:@]{3,50}@[\w.]{0,16}(?:example|test))"),
         },
         SafeRule {
+            id: "secretmanager-assignment",
             description: "Assignment ending with SECRETMANAGER (explicit placeholder)",
             regex: compile(r"(?i)[:=][^:=]{0,32}\bSECRETMANAGER"),
         },
     ]
 });
 
+/// A safe rule loaded from a safe-list file, optionally limited to some
+/// detection rules.
+#[derive(Debug)]
+struct UserSafeRule {
+    rule: SafeRule,
+    /// Globs over the ids of the detection rules this safe rule applies to.
+    scope: Option<GlobSet>,
+}
+
+#[derive(Debug, Default)]
+struct UserSafeList {
+    rules: Vec<UserSafeRule>,
+    disabled: FxHashSet<&'static str>,
+}
+
+static USER_SAFE_LIST: Lazy<RwLock<UserSafeList>> =
+    Lazy::new(|| RwLock::new(UserSafeList::default()));
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SafeListFile {
+    #[serde(default)]
+    safe_rules: Vec<SafeRuleSyntax>,
+    #[serde(default)]
+    disable: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SafeRuleSyntax {
+    id: String,
+    description: String,
+    regex: String,
+    #[serde(default)]
+    rules: Vec<String>,
+}
+
+/// Why a match was treated as benign.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SafeReason {
+    pub id: &'static str,
+    pub description: &'static str,
+}
+
+impl From<&SafeRule> for SafeReason {
+    fn from(rule: &SafeRule) -> Self {
+        Self { id: rule.id, description: rule.description }
+    }
+}
+
+/// Ids of the built-in safe rules, in the order they are tried.
+pub fn builtin_safe_rule_ids() -> impl Iterator<Item = &'static str> {
+    SAFE_LIST_FILTER_RULES.iter().map(|rule| rule.id)
+}
+
+/// Load additional safe rules, and built-in ones to disable, from a YAML file:
+///
+/// ```yaml
+/// safe_rules:
+///   - id: internal-placeholder
+///     description: Values from our config templates
+///     regex: '(?i)\bCHANGE_?ME\b'
+///     rules: ["kingfisher.generic.*"]   # optional
+/// disable:
+///   - placeholder-sequence
+/// ```
+pub fn load_user_safe_list(path: &Path) -> Result<()> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read safe list {}", path.display()))?;
+    let list = UserSafeList::from_yaml(&contents)
+        .with_context(|| format!("Invalid safe list {}", path.display()))?;
+    let mut user = USER_SAFE_LIST.write().unwrap();
+    user.rules.extend(list.rules);
+    user.disabled.extend(list.disabled);
+    Ok(())
+}
+
+impl UserSafeList {
+    fn from_yaml(contents: &str) -> Result<Self> {
+        let file: SafeListFile = serde_yaml::from_str(contents)?;
+        let mut list = Self::default();
+        for syntax in file.safe_rules {
+            list.rules.push(UserSafeRule::compile(syntax)?);
+        }
+        for id in &file.disable {
+            list.disabled.insert(builtin_safe_rule_id(id)?);
+        }
+        Ok(list)
+    }
+
+    /// The first enabled built-in safe rule, then the first user safe rule in
+    /// scope for `rule_id`, that matches the input.
+    fn reason(&self, rule_id: Option<&str>, input: &[u8]) -> Option<SafeReason> {
+        if let Some(rule) = SAFE_LIST_FILTER_RULES
+            .iter()
+            .filter(|rule| !self.disabled.contains(rule.id))
+            .find(|rule| rule.regex.is_match(input))
+        {
+            return Some(rule.into());
+        }
+        self.rules
+            .iter()
+            .filter(|user_rule| match (&user_rule.scope, rule_id) {
+                (None, _) => true,
+                (Some(scope), Some(rule_id)) => scope.is_match(rule_id),
+                (Some(_), None) => false,
+            })
+            .find(|user_rule| user_rule.rule.regex.is_match(input))
+            .map(|user_rule| (&user_rule.rule).into())
+    }
+}
+
+impl UserSafeRule {
+    fn compile(syntax: SafeRuleSyntax) -> Result<Self> {
+        let regex = Regex::new(&syntax.regex)
+            .with_context(|| format!("safe rule `{}` has an invalid regex", syntax.id))?;
+        let scope = if syntax.rules.is_empty() {
+            None
+        } else {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &syntax.rules {
+                builder.add(Glob::new(pattern).with_context(|| {
+                    format!("safe rule `{}` has an invalid rule glob `{pattern}`", syntax.id)
+                })?);
+            }
+            Some(builder.build()?)
+        };
+        Ok(Self {
+            rule: SafeRule {
+                id: intern(&syntax.id),
+                description: intern(&syntax.description),
+                regex,
+            },
+            scope,
+        })
+    }
+}
+
+fn builtin_safe_rule_id(id: &str) -> Result<&'static str> {
+    match builtin_safe_rule_ids().find(|known| *known == id) {
+        Some(id) => Ok(id),
+        None => bail!(
+            "Unknown built-in safe rule `{id}`; known ids: {}",
+            builtin_safe_rule_ids().collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+/// Stop using the built-in safe rule with the given id.
+pub fn disable_builtin_safe_rule(id: &str) -> Result<()> {
+    let id = builtin_safe_rule_id(id)?;
+    USER_SAFE_LIST.write().unwrap().disabled.insert(id);
+    Ok(())
+}
+
+/// A match dropped by a safe rule, kept for `--report-suppressed`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SuppressedMatch {
+    pub rule_id: String,
+    pub path: String,
+    pub line: usize,
+    pub snippet: String,
+    pub safe_rule: String,
+    pub reason: String,
+}
+
+static RECORD_SUPPRESSED: AtomicBool = AtomicBool::new(false);
+static SUPPRESSED_MATCHES: Lazy<Mutex<Vec<SuppressedMatch>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Keep matches dropped by safe rules so they can be reported.
+pub fn set_record_suppressed(enabled: bool) {
+    RECORD_SUPPRESSED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_recording_suppressed() -> bool {
+    RECORD_SUPPRESSED.load(Ordering::Relaxed)
+}
+
+pub fn record_suppressed(suppressed: SuppressedMatch) {
+    SUPPRESSED_MATCHES.lock().unwrap().push(suppressed);
+}
+
+/// Matches dropped by safe rules so far, in a stable order.
+pub fn suppressed_matches() -> Vec<SuppressedMatch> {
+    let mut matches = SUPPRESSED_MATCHES.lock().unwrap().clone();
+    matches.sort_by(|a, b| {
+        (&a.path, a.line, &a.rule_id, &a.snippet).cmp(&(&b.path, b.line, &b.rule_id, &b.snippet))
+    });
+    matches.dedup();
+    matches
+}
+
 // User-supplied allow-list patterns (regexes) and skipwords. These are empty by
 // default and populated via CLI flags at runtime.
 static USER_SAFE_REGEXES: Lazy<Mutex<Vec<Regex>>> = Lazy::new(|| Mutex::new(Vec::new()));
 static USER_SAFE_SKIPWORDS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static USER_ALLOWLIST: Lazy<Mutex<Option<Arc<Allowlist>>>> = Lazy::new(|| Mutex::new(None));
 
 /// Register an additional allow-list regex provided by the user.
 /// If the pattern fails to compile, the error is returned so the caller can
@@ -152,13 +384,29 @@ pub fn add_user_skipword(word: &str) {
     USER_SAFE_SKIPWORDS.lock().unwrap().push(word.to_lowercase());
 }
 
+/// Use the entries of an allowlist file (`--allowlist`) in addition to the
+/// skip regexes and skipwords.
+pub fn set_user_allowlist(allowlist: Allowlist) {
+    *USER_ALLOWLIST.lock().unwrap() = Some(Arc::new(allowlist));
+}
+
+/// The allowlist file in use, if any, for reporting per-entry counts.
+pub fn user_allowlist() -> Option<Arc<Allowlist>> {
+    USER_ALLOWLIST.lock().unwrap().clone()
+}
+
 /// Returns `true` if the given input matches any user-supplied allow-list
-/// patterns (regexes or skipwords).
+/// patterns (regexes, skipwords or allowlist file entries).
 ///
 /// `secret` is the primary capture group (typically just the secret value)
 /// while `full_match` includes the entire match, allowing regexes to target
-/// surrounding context such as variable names.
-pub fn is_user_match(secret: &[u8], full_match: &[u8]) -> bool {
+/// surrounding context such as variable names. `context` locates the match
+/// for the scopes of allowlist entries.
+pub fn is_user_match(secret: &[u8], full_match: &[u8], context: &AllowlistContext<'_>) -> bool {
+    let allowlist = USER_ALLOWLIST.lock().unwrap().clone();
+    if allowlist.is_some_and(|allowlist| allowlist.matches(secret, context)) {
+        return true;
+    }
     {
         let regexes = USER_SAFE_REGEXES.lock().unwrap();
         if regexes.iter().any(|re| re.is_match(secret) || re.is_match(full_match)) {
@@ -189,13 +437,18 @@ pub fn is_user_match(secret: &[u8], full_match: &[u8]) -> bool {
     false
 }
 
+/// Returns the safe rule that marks the input as a *benign* placeholder/test
+/// string for detection rule `rule_id`, if any. Safe rules loaded from a file
+/// are tried after the built-in ones; without a `rule_id`, only those that are
+/// not limited to certain rules apply.
+pub fn safe_match_reason(rule_id: Option<&str>, input: &[u8]) -> Option<SafeReason> {
+    USER_SAFE_LIST.read().unwrap().reason(rule_id, input)
+}
+
 /// Returns `Some(&'static str)` with the rule description if the input likely
 /// contains *benign* placeholder/test strings; otherwise `None`.
 pub fn is_safe_match_reason(input: &[u8]) -> Option<&'static str> {
-    SAFE_LIST_FILTER_RULES
-        .iter()
-        .find(|rule| rule.regex.is_match(input))
-        .map(|rule| rule.description)
+    safe_match_reason(None, input).map(|reason| reason.description)
 }
 
 /// Returns true if the input likely contains *benign* placeholder/test strings,
@@ -208,3 +461,50 @@ pub fn is_safe_match(input: &[u8]) -> bool {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_safe_rule_ids_are_unique() {
+        let ids: Vec<_> = builtin_safe_rule_ids().collect();
+        let unique: FxHashSet<_> = ids.iter().collect();
+        assert_eq!(ids.len(), unique.len());
+    }
+
+    // These build their own `UserSafeList` so they never touch the process-wide one
+    #[test]
+    fn user_safe_rules_apply_within_their_scope() -> Result<()> {
+        let list = UserSafeList::from_yaml(
+            r#"
+safe_rules:
+  - id: acme-template
+    description: Placeholder from the acme config templates
+    regex: 'ACME_FILL_ME_[0-9]+'
+    rules: ["acme.*"]
+"#,
+        )?;
+        let input = b"token = ACME_FILL_ME_42";
+        assert_eq!(list.reason(Some("acme.token"), input).map(|r| r.id), Some("acme-template"));
+        assert_eq!(list.reason(Some("other.token"), input), None);
+        assert_eq!(list.reason(None, input), None);
+        Ok(())
+    }
+
+    #[test]
+    fn disabled_builtin_rules_stop_matching() -> Result<()> {
+        let input = b"password = hunter2";
+        let defaults = UserSafeList::default();
+        assert_eq!(defaults.reason(None, input).map(|r| r.id), Some("hunter2"));
+        let list = UserSafeList::from_yaml("disable: [hunter2]\n")?;
+        assert_eq!(list.reason(None, input), None);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_builtin_ids_cannot_be_disabled() {
+        let err = UserSafeList::from_yaml("disable: [no-such-rule]\n").unwrap_err();
+        assert!(format!("{err:#}").contains("no-such-rule"));
+    }
+}
@@ -0,0 +1,315 @@
+//! Cross-file resolution of `depends_on_rule` values (`--cross-file-dependencies`).
+//!
+//! When a rule's dependency did not match in the same blob, matches of the
+//! dependency rule from other blobs under the same origin root (the same Git
+//! repository, or the scanned filesystem) are tried instead. Candidates are
+//! ranked nearest first: same directory, then same commit, then the longest
+//! shared path prefix.
+
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use gix::ObjectId;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    blob::BlobId,
+    findings_store::FindingsStoreMessage,
+    location::{Location, OffsetSpan},
+    matcher::{OwnedBlobMatch, PairedDependency},
+    origin::{Origin, OriginSet},
+    rules::rule::DependsOnRule,
+};
+
+/// Candidates tried for each missing dependency.
+const MAX_CANDIDATES_PER_DEPENDENCY: usize = 3;
+/// Validation attempts made for one match, across all combinations of candidates.
+const MAX_ATTEMPTS: usize = 6;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Root {
+    Filesystem,
+    GitRepo(Arc<PathBuf>),
+}
+
+/// What a candidate shares with the blob that needs it, closest first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Proximity {
+    Directory,
+    Commit,
+    Root,
+}
+
+/// Where a blob was found, as far as pairing is concerned.
+#[derive(Clone, Debug)]
+pub(super) struct BlobPlace {
+    root: Option<Root>,
+    path: Option<PathBuf>,
+    commit: Option<ObjectId>,
+}
+
+impl BlobPlace {
+    pub(super) fn of(origin: &OriginSet) -> Self {
+        match origin.first() {
+            Origin::File(e) => Self {
+                root: Some(Root::Filesystem),
+                path: Some(e.path.to_path_buf()),
+                commit: None,
+            },
+            Origin::GitRepo(e) => Self {
+                root: Some(Root::GitRepo(Arc::clone(&e.repo_path))),
+                path: e.first_commit.as_ref().map(|c| PathBuf::from(&c.blob_path)),
+                commit: e.first_commit.as_ref().map(|c| c.commit_metadata.commit_id),
+            },
+            // Extended origins (buckets, chat, tickets) have no common root to pair within
+            Origin::Extended(_) => Self { root: None, path: None, commit: None },
+        }
+    }
+
+    /// How far `other` is from this blob, smallest first, or `None` when it is
+    /// under a different root.
+    fn distance(&self, other: &BlobPlace) -> Option<(Proximity, Reverse<usize>)> {
+        let root = self.root.as_ref()?;
+        if other.root.as_ref() != Some(root) {
+            return None;
+        }
+        let (shared, same_dir) = match (&self.path, &other.path) {
+            (Some(a), Some(b)) => (shared_components(a, b), a.parent() == b.parent()),
+            _ => (0, false),
+        };
+        let proximity = if same_dir {
+            Proximity::Directory
+        } else if self.commit.is_some() && self.commit == other.commit {
+            Proximity::Commit
+        } else {
+            Proximity::Root
+        };
+        Some((proximity, Reverse(shared)))
+    }
+}
+
+fn shared_components(a: &Path, b: &Path) -> usize {
+    a.components().zip(b.components()).take_while(|(x, y)| x == y).count()
+}
+
+struct Candidate {
+    rule_id: String,
+    value: String,
+    location: Location,
+    blob_id: BlobId,
+    place: BlobPlace,
+    path: String,
+}
+
+/// One set of dependency values to validate a match with.
+pub(super) struct PairingAttempt {
+    pub dependent_variables: FxHashMap<String, Vec<(String, OffsetSpan)>>,
+    pub missing_dependencies: FxHashMap<String, Vec<String>>,
+    pub pairings: Vec<PairedDependency>,
+}
+
+/// Matches of every rule that some rule depends on, across all scanned blobs.
+pub(super) struct CandidateIndex {
+    by_rule: FxHashMap<String, Vec<Candidate>>,
+}
+
+impl CandidateIndex {
+    pub(super) fn new(messages: &[Arc<FindingsStoreMessage>]) -> Self {
+        let needed: FxHashSet<&str> = messages
+            .iter()
+            .flat_map(|msg| msg.2.rule.syntax().depends_on_rule.iter().flatten())
+            .map(|dep| dep.rule_id.as_str())
+            .collect();
+
+        let mut by_rule: FxHashMap<String, Vec<Candidate>> = FxHashMap::default();
+        for (origin, blob_md, m) in messages.iter().map(|msg| &**msg) {
+            if !needed.contains(m.rule.id()) {
+                continue;
+            }
+            let Some(capture) = m.groups.captures.get(1).or_else(|| m.groups.captures.first())
+            else {
+                continue;
+            };
+            let path = origin
+                .iter()
+                .find_map(|o| o.blob_path())
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| format!("blob:{}", blob_md.id.hex()));
+            by_rule.entry(m.rule.id().to_string()).or_default().push(Candidate {
+                rule_id: m.rule.id().to_string(),
+                value: capture.value.to_string(),
+                location: m.location.clone(),
+                blob_id: blob_md.id,
+                place: BlobPlace::of(origin),
+                path,
+            });
+        }
+        Self { by_rule }
+    }
+
+    /// The nearest distinct values of `rule_id` outside `blob_id`.
+    fn ranked(&self, rule_id: &str, place: &BlobPlace, blob_id: BlobId) -> Vec<&Candidate> {
+        let mut scored: Vec<_> = self
+            .by_rule
+            .get(rule_id)
+            .into_iter()
+            .flatten()
+            .filter(|c| c.blob_id != blob_id)
+            .filter_map(|c| Some((place.distance(&c.place)?, c)))
+            .collect();
+        scored.sort_by(|(a, ca), (b, cb)| a.cmp(b).then_with(|| ca.path.cmp(&cb.path)));
+
+        let mut seen = HashSet::new();
+        scored
+            .into_iter()
+            .map(|(_, c)| c)
+            .filter(|c| seen.insert(c.value.as_str()))
+            .take(MAX_CANDIDATES_PER_DEPENDENCY)
+            .collect()
+    }
+
+    /// Attempts to validate `om` with, nearest combination first. Empty when
+    /// nothing is missing or some missing dependency has no candidate.
+    pub(super) fn attempts(
+        &self,
+        om: &OwnedBlobMatch,
+        place: &BlobPlace,
+        dependent_variables: &FxHashMap<String, Vec<(String, OffsetSpan)>>,
+        missing_dependencies: &FxHashMap<String, Vec<String>>,
+    ) -> Vec<PairingAttempt> {
+        let Some(missing) = missing_dependencies.get(om.rule.id()) else {
+            return Vec::new();
+        };
+        let deps: Vec<&DependsOnRule> = om
+            .rule
+            .syntax()
+            .depends_on_rule
+            .iter()
+            .flatten()
+            .filter(|dep| missing.contains(&dep.rule_id))
+            .collect();
+        let ranked: Vec<Vec<&Candidate>> =
+            deps.iter().map(|dep| self.ranked(&dep.rule_id, place, om.blob_id)).collect();
+        if deps.is_empty() || ranked.iter().any(Vec::is_empty) {
+            return Vec::new();
+        }
+
+        let mut combos: Vec<Vec<usize>> = vec![Vec::new()];
+        for candidates in &ranked {
+            combos = combos
+                .into_iter()
+                .flat_map(|combo| {
+                    (0..candidates.len()).map(move |i| {
+                        let mut next = combo.clone();
+                        next.push(i);
+                        next
+                    })
+                })
+                .collect();
+        }
+        combos.sort_by_key(|combo| combo.iter().sum::<usize>());
+        combos.truncate(MAX_ATTEMPTS);
+
+        let mut remaining_missing = missing_dependencies.clone();
+        remaining_missing.remove(om.rule.id());
+        combos
+            .into_iter()
+            .map(|combo| {
+                let mut vars = dependent_variables.clone();
+                let pairings = deps
+                    .iter()
+                    .zip(&ranked)
+                    .zip(combo)
+                    .map(|((dep, candidates), i)| {
+                        let c = candidates[i];
+                        let variable = dep.variable.to_uppercase();
+                        vars.insert(
+                            variable.clone(),
+                            vec![(c.value.clone(), c.location.offset_span)],
+                        );
+                        PairedDependency {
+                            rule_id: c.rule_id.clone(),
+                            variable,
+                            value: c.value.clone(),
+                            path: c.path.clone(),
+                            location: c.location.clone(),
+                        }
+                    })
+                    .collect();
+                PairingAttempt {
+                    dependent_variables: vars,
+                    missing_dependencies: remaining_missing.clone(),
+                    pairings,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::location::{SourcePoint, SourceSpan};
+
+    fn file(path: &str) -> BlobPlace {
+        BlobPlace::of(&OriginSet::from(Origin::from_file(PathBuf::from(path))))
+    }
+
+    fn candidate(value: &str, path: &str) -> Candidate {
+        let point = SourcePoint { line: 1, column: 0 };
+        Candidate {
+            rule_id: "demo.id".into(),
+            value: value.into(),
+            location: Location {
+                offset_span: OffsetSpan { start: 0, end: value.len() },
+                source_span: SourceSpan { start: point, end: point },
+            },
+            blob_id: BlobId::new(path.as_bytes()),
+            place: file(path),
+            path: path.into(),
+        }
+    }
+
+    #[test]
+    fn candidates_are_ranked_nearest_first_and_deduplicated() {
+        let index = CandidateIndex {
+            by_rule: FxHashMap::from_iter([(
+                "demo.id".to_string(),
+                vec![
+                    candidate("far", "/repo/other/deep/config.yml"),
+                    candidate("sibling", "/repo/app/config.yml"),
+                    candidate("cousin", "/repo/app/sub/config.yml"),
+                    candidate("sibling", "/repo/app/other.yml"),
+                    candidate("own", "/repo/app/secrets.env"),
+                ],
+            )]),
+        };
+        let own_blob = BlobId::new(b"/repo/app/secrets.env");
+        let values: Vec<_> = index
+            .ranked("demo.id", &file("/repo/app/secrets.env"), own_blob)
+            .iter()
+            .map(|c| c.value.as_str())
+            .collect();
+        assert_eq!(values, ["sibling", "cousin", "far"]);
+    }
+
+    #[test]
+    fn different_roots_are_never_paired() {
+        let repo = BlobPlace {
+            root: Some(Root::GitRepo(Arc::new(PathBuf::from("/clones/a")))),
+            path: Some(PathBuf::from("config.yml")),
+            commit: None,
+        };
+        let other_repo = BlobPlace {
+            root: Some(Root::GitRepo(Arc::new(PathBuf::from("/clones/b")))),
+            ..repo.clone()
+        };
+        assert!(repo.distance(&other_repo).is_none());
+        assert!(repo.distance(&file("/clones/a/config.yml")).is_none());
+        assert_eq!(repo.distance(&repo).map(|d| d.0), Some(Proximity::Directory));
+    }
+}
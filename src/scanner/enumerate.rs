@@ -18,13 +18,16 @@ use rayon::{
     prelude::{ParallelIterator, *},
 };
 use serde::{Deserialize, Deserializer};
-use tracing::{debug, error};
+use tracing::{debug, error, info, warn};
 
 use smallvec::smallvec;
 
 use crate::{
-    binary::is_binary,
+    allowlist,
+    binary::is_binary_blob,
     blob::{Blob, BlobAppearance, BlobId, BlobIdMap},
+    blob_cache::{BlobCache, CachedScan, LocationScopes},
+    checkpoint::Checkpoint,
     cli::commands::{github::GitHistoryMode, scan},
     decompress::{decompress_file_to_temp, CompressedContent},
     findings_store,
@@ -37,13 +40,14 @@ use crate::{
     rules_database::RulesDatabase,
     scanner::{
         processing::BlobProcessor,
+        repos::DatastoreMessage,
         runner::{create_datastore_channel, spawn_datastore_writer_thread},
         util::is_compressed_file,
     },
     scanner_pool::ScannerPool,
     DirectoryResult, EnumeratorConfig, EnumeratorFileResult, FileResult, FilesystemEnumerator,
     FoundInput, GitDiffConfig, GitRepoEnumerator, GitRepoResult, GitRepoWithMetadataEnumerator,
-    PathBuf,
+    LargeFileResult, PathBuf,
 };
 
 type OwnedBlob = Blob<'static>;
@@ -137,7 +141,26 @@ pub fn enumerate_filesystem_inputs(
         repo_scan_timeout,
         exclude_globset: exclude_globset.clone(),
         git_diff: diff_config.clone(),
+        checkpoint: datastore.lock().unwrap().checkpoint(),
     };
+    let checkpoint = enum_cfg.checkpoint.as_deref();
+    // Rule scopes and allowlist entries can make findings depend on where a blob is
+    let location_scopes = LocationScopes::of(rules_db);
+    // Replayed blobs are not matched again, so their suppressed matches would be missing
+    if args.blob_cache.is_some() && args.report_suppressed {
+        warn!("--report-suppressed scans every blob; ignoring --blob-cache");
+    }
+    let blob_cache = args
+        .blob_cache
+        .as_deref()
+        .filter(|_| !args.report_suppressed)
+        .map(|path| BlobCache::open(path, rules_db, args, location_scopes))
+        .transpose()
+        .context("Failed to open blob cache")?;
+    if let Some(cache) = &blob_cache {
+        debug!("Blob cache holds {} blobs scanned under the current ruleset", cache.len());
+    }
+    let blob_cache = blob_cache.as_ref();
     let (send_ds, recv_ds) = create_datastore_channel(args.num_jobs);
     let datastore_writer_thread =
         spawn_datastore_writer_thread(datastore, recv_ds, !args.no_dedup)?;
@@ -147,7 +170,7 @@ pub fn enumerate_filesystem_inputs(
     let seen_blobs = BlobIdMap::new();
     let scanner_pool = Arc::new(ScannerPool::new(Arc::new(rules_db.vsdb.clone())));
 
-    let matcher = Matcher::new(
+    let mut matcher = Matcher::new(
         &rules_db,
         scanner_pool.clone(),
         &seen_blobs,
@@ -157,6 +180,7 @@ pub fn enumerate_filesystem_inputs(
         &args.extra_ignore_comments,
         args.no_inline_ignore,
     )?;
+    matcher.set_decoders(args.enabled_encodings());
     let blob_processor_init_time = Mutex::new(t1.elapsed());
     let make_blob_processor = || -> BlobProcessor {
         let t1 = Instant::now();
@@ -167,15 +191,24 @@ pub fn enumerate_filesystem_inputs(
         }
         BlobProcessor { matcher }
     };
+    // Files over `--max-file-size` are streamed once everything else is scanned
+    let large_files: Mutex<Vec<LargeFileResult>> = Mutex::new(Vec::new());
+    let send_large = send_ds.clone();
     let scan_res: Result<()> = input_recv
         .into_iter()
         .par_bridge()
-        .filter_map(|input| match (&enum_cfg, input).into_blob_iter() {
-            Err(e) => {
-                debug!("Error enumerating input: {e:#}");
+        .filter_map(|input| match input {
+            FoundInput::LargeFile(file) => {
+                large_files.lock().unwrap().push(file);
                 None
             }
-            Ok(blob_iter) => blob_iter,
+            input => match (&enum_cfg, input).into_blob_iter() {
+                Err(e) => {
+                    debug!("Error enumerating input: {e:#}");
+                    None
+                }
+                Ok(blob_iter) => blob_iter,
+            },
         })
         .flatten()
         .try_for_each_init(
@@ -194,7 +227,7 @@ pub fn enumerate_filesystem_inputs(
                 } else {
                     false
                 };
-                let is_binary = is_binary(&blob.bytes());
+                let is_binary = is_binary_blob(&blob);
                 let should_skip = if is_archive {
                     // For archives: skip only if --no_extract_archives is true
                     args.content_filtering_args.no_extract_archives
@@ -217,28 +250,80 @@ pub fn enumerate_filesystem_inputs(
                     });
                     return Ok(());
                 }
+                // Progress kept across runs must be keyed by the exact content
+                let content_id =
+                    (checkpoint.is_some() || blob_cache.is_some()).then(|| blob.content_id());
+                let checkpoint_key = checkpoint.zip(content_id.as_ref()).map(|(cp, id)| {
+                    cp.blob_key(id, &origin, args.no_dedup || location_scopes.any())
+                });
+                if let (Some(cp), Some(key)) = (checkpoint, checkpoint_key.as_deref()) {
+                    if cp.is_blob_complete(key) {
+                        return Ok(());
+                    }
+                }
                 progress.inc(blob.len().try_into().unwrap());
-                match processor.run(origin, blob, args.no_dedup, args.redact, args.no_base64) {
-                    Ok(None) => {
-                        // nothing to record
+                let cache_key =
+                    blob_cache.zip(content_id.as_ref()).map(|(cache, id)| cache.key(id, &origin));
+                let cached = blob_cache
+                    .zip(cache_key.as_ref())
+                    .and_then(|(cache, key)| cache.get(key, &blob).map(|hit| (cache, hit)));
+                let result = match cached {
+                    Some((_, CachedScan::Clean)) => Ok(None),
+                    Some((_, CachedScan::Findings(md, matches))) => {
+                        Ok(Some((origin, md, matches.into_iter().map(|m| (None, m)).collect())))
+                    }
+                    None => {
+                        let (res, allowlist_hits) = allowlist::record_hits(|| {
+                            processor.run(origin, blob, args.no_dedup, args.redact)
+                        });
+                        if let (Some(cache), Some(key)) = (blob_cache, cache_key) {
+                            match &res {
+                                Ok(None) => cache.record_clean(key, &allowlist_hits),
+                                // An empty list means the blob was already recorded this run
+                                Ok(Some((_, md, matches))) if !matches.is_empty() => cache
+                                    .record_findings(
+                                        key,
+                                        md,
+                                        matches.iter().map(|(_, m)| m),
+                                        &allowlist_hits,
+                                    ),
+                                _ => {}
+                            }
+                        }
+                        res
                     }
+                };
+                record_scan_result(result, checkpoint.zip(checkpoint_key), &send_ds)
+            },
+        );
+
+    let stream_res: Result<()> =
+        large_files.into_inner().unwrap().into_par_iter().try_for_each_init(
+            || make_blob_processor.clone()(),
+            |processor, file| {
+                info!("Streaming {} ({} bytes)", file.path.display(), file.num_bytes);
+                progress.inc(file.num_bytes);
+                match processor.run_stream(&file.path, args.redact) {
+                    Ok(None) => {}
                     Ok(Some((origin_set, blob_metadata, vec_of_matches))) => {
+                        let origin_set = Arc::new(origin_set);
+                        let blob_metadata = Arc::new(blob_metadata);
                         for (_, single_match) in vec_of_matches {
-                            // Send each match
-                            send_ds.send((
-                                Arc::new(origin_set.clone()),
-                                Arc::new(blob_metadata.clone()),
+                            send_large.send((
+                                origin_set.clone(),
+                                blob_metadata.clone(),
                                 single_match,
                             ))?;
                         }
                     }
                     Err(e) => {
-                        debug!("Error scanning input: {e:#}");
+                        debug!("Error streaming {}: {e:#}", file.path.display());
                     }
                 }
                 Ok(())
             },
         );
+    drop(send_large);
 
     enum_thread.join().unwrap().context("Failed to enumerate inputs")?;
     let (..) = datastore_writer_thread
@@ -246,6 +331,11 @@ pub fn enumerate_filesystem_inputs(
         .unwrap()
         .context("Failed to save results to the datastore")?;
     scan_res.context("Failed to scan inputs")?;
+    stream_res.context("Failed to scan large files")?;
+    if let Some(cache) = blob_cache {
+        cache.flush().context("Failed to write blob cache")?;
+        info!("Skipped {} blobs already scanned under the current ruleset", cache.hits());
+    }
     progress.finish();
     Ok(())
 }
@@ -253,6 +343,40 @@ pub fn enumerate_filesystem_inputs(
 /// Initialize a `FilesystemEnumerator` based on the command-line arguments and
 /// datastore. Also initialize a `Gitignore` that is the same as that used by
 /// the filesystem enumerator.
+/// Send a blob's matches to the datastore and mark the blob complete in the
+/// checkpoint. A blob whose scan failed is not marked, so `--resume` scans it again.
+fn record_scan_result(
+    result: Result<Option<DatastoreMessage>>,
+    checkpoint: Option<(&Checkpoint, String)>,
+    send_ds: &crossbeam_channel::Sender<findings_store::FindingsStoreMessage>,
+) -> Result<()> {
+    let mut checkpointed = Vec::new();
+    match result {
+        Ok(None) => {
+            // nothing to record
+        }
+        Ok(Some((origin_set, blob_metadata, vec_of_matches))) => {
+            for (_, single_match) in vec_of_matches {
+                let message =
+                    (Arc::new(origin_set.clone()), Arc::new(blob_metadata.clone()), single_match);
+                if checkpoint.is_some() {
+                    checkpointed.push(message.clone());
+                }
+                // Send each match
+                send_ds.send(message)?;
+            }
+        }
+        Err(e) => {
+            debug!("Error scanning input: {e:#}");
+            return Ok(());
+        }
+    }
+    if let Some((cp, key)) = checkpoint {
+        cp.record_blob(key, checkpointed);
+    }
+    Ok(())
+}
+
 fn make_fs_enumerator(
     args: &scan::ScanArgs,
     input_roots: Vec<PathBuf>,
@@ -435,6 +559,7 @@ impl ParallelBlobIterator for FileResult {
 struct GitRepoResultIter<'a> {
     inner: GitRepoResult,
     deadline: std::time::Instant,
+    checkpoint: Option<Arc<Checkpoint>>,
     _marker: std::marker::PhantomData<&'a ()>,
 }
 
@@ -448,6 +573,7 @@ impl ParallelBlobIterator for GitRepoResult {
         Ok(Some(GitRepoResultIter {
             inner: self,
             deadline: Instant::now() + PLACEHOLDER,
+            checkpoint: None,
             _marker: std::marker::PhantomData,
         }))
     }
@@ -465,8 +591,10 @@ impl<'a> rayon::iter::ParallelIterator for GitRepoResultIter<'a> {
         let repo_path = Arc::new(self.inner.path.clone());
         let deadline = self.deadline;
         let flag = Arc::new(AtomicBool::new(false)); // first-timeout gate
+        let checkpoint = self.checkpoint;
 
-        self.inner
+        let result = self
+            .inner
             .blobs
             .into_par_iter()
             .with_min_len(1024)
@@ -506,7 +634,15 @@ impl<'a> rayon::iter::ParallelIterator for GitRepoResultIter<'a> {
                     Err(e) if e.to_string() == "__timeout_silenced__"
                 )
             })
-            .drive_unindexed(consumer)
+            .drive_unindexed(consumer);
+
+        // A repo that hit its deadline is left unfinished so `--resume` retries it.
+        if let Some(cp) = checkpoint {
+            if !flag.load(Ordering::Relaxed) {
+                cp.record_repo(&repo_path);
+            }
+        }
+        result
     }
 }
 
@@ -614,6 +750,14 @@ impl<'cfg> ParallelBlobIterator for (&'cfg EnumeratorConfig, FoundInput) {
                     return Ok(None);
                 }
 
+                if cfg.checkpoint.as_ref().is_some_and(|cp| cp.is_repo_complete(path)) {
+                    debug!(
+                        "Skipping Git repository completed by a previous run: {}",
+                        path.display()
+                    );
+                    return Ok(None);
+                }
+
                 // Try to open a Git repository at that path
                 let repository = match open_git_repo(path)? {
                     Some(r) => r,
@@ -697,6 +841,7 @@ impl<'cfg> ParallelBlobIterator for (&'cfg EnumeratorConfig, FoundInput) {
                             .map(|iter| {
                                 iter.map(|mut gri| {
                                     gri.deadline = Instant::now() + timeout;
+                                    gri.checkpoint = cfg.checkpoint.clone();
                                     FoundInputIter::GitRepo(gri)
                                 })
                             })
@@ -708,6 +853,9 @@ impl<'cfg> ParallelBlobIterator for (&'cfg EnumeratorConfig, FoundInput) {
             FoundInput::EnumeratorFile(i) => {
                 Ok(i.into_blob_iter()?.map(FoundInputIter::EnumeratorFile))
             }
+
+            // ───────────── oversized file, streamed instead of loaded ─────────────
+            FoundInput::LargeFile(_) => Ok(None),
         }
     }
 }
@@ -931,7 +1079,27 @@ mod tests {
     use gix::{open::Options, open_opts};
     use tempfile::tempdir;
 
-    use super::reference_candidates;
+    use super::{record_scan_result, reference_candidates};
+    use crate::checkpoint::Checkpoint;
+
+    #[test]
+    fn failed_scans_are_not_checkpointed() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("resume.db");
+        let (send_ds, _recv_ds) = crossbeam_channel::unbounded();
+
+        let cp = Checkpoint::open(&path, dir.path().to_path_buf())?;
+        record_scan_result(Err(anyhow::anyhow!("scan failed")), Some((&cp, "a".into())), &send_ds)?;
+        record_scan_result(Ok(None), Some((&cp, "b".into())), &send_ds)?;
+        cp.flush()?;
+        drop(cp);
+
+        // Only the blob that was actually scanned is skipped on resume
+        let cp = Checkpoint::open(&path, dir.path().to_path_buf())?;
+        assert!(!cp.is_blob_complete("a"));
+        assert!(cp.is_blob_complete("b"));
+        Ok(())
+    }
 
     #[test]
     fn reference_candidates_for_plain_branch() {
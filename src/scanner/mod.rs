@@ -8,6 +8,7 @@ pub(crate) use repos::{
 pub use runner::{load_and_record_rules, run_async_scan, run_scan};
 pub(crate) use validation::run_secret_validation;
 
+mod dependencies;
 mod docker;
 mod enumerate;
 mod processing;
@@ -1,14 +1,17 @@
+use std::fs::File;
+
 use anyhow::Result;
 use tokio::time::Instant;
 use tracing::{debug_span, trace};
 
 use crate::{
-    blob::{Blob, BlobMetadata},
+    blob::{Blob, BlobId, BlobMetadata},
     content_type::ContentInspector,
     location::LocationMapping,
     matcher::{Match, Matcher, OwnedBlobMatch, ScanResult},
     origin::{Origin, OriginSet},
     scanner::repos::DatastoreMessage,
+    transcode::{OffsetMap, Transcoded},
     Path,
 };
 
@@ -27,7 +30,6 @@ impl<'a> BlobProcessor<'a> {
         blob: Blob,
         no_dedup: bool,
         redact: bool,
-        no_base64: bool,
     ) -> Result<Option<DatastoreMessage>> {
         let _span = debug_span!("matcher", temp_id = blob.temp_id()).entered();
         let t1 = Instant::now();
@@ -35,8 +37,20 @@ impl<'a> BlobProcessor<'a> {
             .iter()
             .find_map(|p| p.blob_path())
             .and_then(|path| ContentInspector::default().guess_language(path, blob.bytes()));
+        // UTF-16 and UTF-32 text is scanned as UTF-8; offsets are mapped back below
+        let transcoded = blob
+            .text_encoding()
+            .and_then(|(encoding, bom_len)| {
+                Transcoded::with_encoding(blob.bytes(), encoding, bom_len)
+            })
+            .map(|t| {
+                trace!("blob detected as {}; scanning it as UTF-8", t.encoding);
+                (blob.with_bytes(t.text), t.offsets)
+            });
+        let scanned = transcoded.as_ref().map_or(&blob, |(text, _)| text);
+        // `--no-base64` is already folded into the matcher's decoders
         let res =
-            self.matcher.scan_blob(&blob, &origin, language_hint, redact, no_dedup, no_base64)?;
+            self.matcher.scan_blob(scanned, &origin, language_hint, redact, no_dedup, false)?;
         let scan_us = t1.elapsed().as_micros();
         match res {
             // blob already seen, but with no matches; nothing to do!
@@ -88,7 +102,7 @@ impl<'a> BlobProcessor<'a> {
                     }
                 }
 
-                let bytes = blob.bytes();
+                let bytes = scanned.bytes();
                 let loc_mapping = if bytes.len() <= LOCATION_LIMIT_BYTES {
                     Some(LocationMapping::new(bytes))
                 } else {
@@ -97,11 +111,14 @@ impl<'a> BlobProcessor<'a> {
                 let converted_matches: Vec<(Option<f64>, Match)> = matches
                     .into_iter()
                     .map(|m| {
-                        let converted_match = Match::convert_owned_blobmatch_to_match(
+                        let mut converted_match = Match::convert_owned_blobmatch_to_match(
                             loc_mapping.as_ref(),
                             &OwnedBlobMatch::from_blob_match(m),
                             origin_type,
                         );
+                        if let Some((_, offsets)) = &transcoded {
+                            map_to_original(&mut converted_match, offsets);
+                        }
                         (None, converted_match)
                     })
                     .collect();
@@ -109,6 +126,39 @@ impl<'a> BlobProcessor<'a> {
             }
         }
     }
+
+    /// Scans a file too large to load into memory as a stream; see
+    /// [`Matcher::scan_stream`].
+    pub fn run_stream(&mut self, path: &Path, redact: bool) -> Result<Option<DatastoreMessage>> {
+        let _span = debug_span!("stream_matcher", path = %path.display()).entered();
+        let t1 = Instant::now();
+        let origin = OriginSet::new(Origin::from_file(path.to_owned()), vec![]);
+        let id = BlobId::from_file(path)?;
+        let file = File::open(path)?;
+        let num_bytes = file.metadata()?.len();
+        let matches = self.matcher.scan_stream(file, &id, &origin, redact)?;
+        trace!("({}us) stream scanned; {} matches", t1.elapsed().as_micros(), matches.len());
+        if matches.is_empty() {
+            return Ok(None);
+        }
+        let metadata = BlobMetadata {
+            id,
+            num_bytes: num_bytes.try_into()?,
+            mime_essence: None,
+            language: None,
+        };
+        Ok(Some((origin, metadata, matches.into_iter().map(|m| (None, m)).collect())))
+    }
+}
+
+/// Points the byte offsets of a match found in transcoded text at the original
+/// bytes. Line and column numbers stay those of the UTF-8 text, which is what
+/// an editor displays for the file.
+fn map_to_original(m: &mut Match, offsets: &OffsetMap) {
+    m.location.offset_span = offsets.original_span(&m.location.offset_span);
+    for part in &mut m.parts {
+        part.location.offset_span = offsets.original_span(&part.location.offset_span);
+    }
 }
 
 #[derive(Default)]
@@ -70,11 +70,17 @@ pub fn clone_or_update_git_repos(
         ProgressBar::hidden()
     };
 
+    let checkpoint = datastore.lock().unwrap().checkpoint();
     for repo_url in repo_urls {
         let output_dir = {
             let datastore = datastore.lock().unwrap();
             datastore.clone_destination(repo_url)
         };
+        if checkpoint.as_ref().is_some_and(|cp| cp.is_repo_complete(&output_dir)) {
+            progress.suspend(|| debug!("Skipping {repo_url}; already scanned by a previous run"));
+            progress.inc(1);
+            continue;
+        }
         if output_dir.is_dir() {
             progress.suspend(|| info!("Updating clone of {repo_url}..."));
             match git.update_clone(repo_url, &output_dir) {
@@ -678,7 +684,7 @@ pub async fn fetch_s3_objects(
 
     let scanner_pool = Arc::new(ScannerPool::new(Arc::new(rules_db.vsdb.clone())));
     let seen_blobs = BlobIdMap::new();
-    let matcher = Matcher::new(
+    let mut matcher = Matcher::new(
         rules_db,
         scanner_pool,
         &seen_blobs,
@@ -688,6 +694,7 @@ pub async fn fetch_s3_objects(
         &args.extra_ignore_comments,
         args.no_inline_ignore,
     )?;
+    matcher.set_decoders(args.enabled_encodings());
     let mut processor = BlobProcessor { matcher };
 
     let progress = if progress_enabled {
@@ -715,7 +722,7 @@ pub async fn fetch_s3_objects(
         let blob = crate::blob::Blob::from_bytes(bytes);
 
         if let Some((origin, blob_md, scored_matches)) =
-            processor.run(origin, blob, args.no_dedup, args.redact, args.no_base64)?
+            processor.run(origin, blob, args.no_dedup, args.redact)?
         {
             // Wrap origin & metadata once:
             let origin_arc = Arc::new(origin);
@@ -759,7 +766,7 @@ pub async fn fetch_gcs_objects(
 
     let scanner_pool = Arc::new(ScannerPool::new(Arc::new(rules_db.vsdb.clone())));
     let seen_blobs = BlobIdMap::new();
-    let matcher = Matcher::new(
+    let mut matcher = Matcher::new(
         rules_db,
         scanner_pool,
         &seen_blobs,
@@ -769,6 +776,7 @@ pub async fn fetch_gcs_objects(
         &args.extra_ignore_comments,
         args.no_inline_ignore,
     )?;
+    matcher.set_decoders(args.enabled_encodings());
     let mut processor = BlobProcessor { matcher };
 
     let progress = if progress_enabled {
@@ -796,7 +804,7 @@ pub async fn fetch_gcs_objects(
         let blob = crate::blob::Blob::from_bytes(bytes);
 
         if let Some((origin, blob_md, scored_matches)) =
-            processor.run(origin, blob, args.no_dedup, args.redact, args.no_base64)?
+            processor.run(origin, blob, args.no_dedup, args.redact)?
         {
             let origin_arc = Arc::new(origin);
             let blob_arc = Arc::new(blob_md);
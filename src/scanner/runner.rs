@@ -1,5 +1,6 @@
 use std::{
     fs,
+    path::Path,
     sync::{Arc, Mutex},
 };
 
@@ -10,8 +11,11 @@ use tokio::time::{Duration, Instant};
 use tracing::{debug, error, error_span, info, trace};
 
 use crate::{
+    allowlist::Allowlist,
     azure, bitbucket,
+    checkpoint::Checkpoint,
     cli::{commands::scan, global},
+    datastore::{Datastore, META_CLONE_DIR, META_JIRA_URL},
     findings_store,
     findings_store::{FindingsStore, FindingsStoreMessage},
     gitea, github, gitlab,
@@ -68,6 +72,20 @@ pub async fn run_async_scan(
     for word in &args.skip_word {
         safe_list::add_user_skipword(word);
     }
+    if let Some(path) = args.allowlist.as_ref() {
+        safe_list::set_user_allowlist(Allowlist::load(path)?);
+    }
+    for path in &args.safe_list {
+        safe_list::load_user_safe_list(path)?;
+    }
+    for id in &args.disable_safe_rule {
+        safe_list::disable_builtin_safe_rule(id)?;
+    }
+    safe_list::set_record_suppressed(args.report_suppressed);
+
+    if let Some(path) = args.resume.as_ref() {
+        resume_from_checkpoint(path, &datastore)?;
+    }
 
     let start_time = Instant::now();
 
@@ -217,6 +235,7 @@ pub async fn run_async_scan(
             &matcher_stats,
         )?;
     }
+    flush_checkpoint(&datastore)?;
 
     if !args.no_dedup {
         // Final deduplication step before validation (or before reporting)
@@ -280,8 +299,19 @@ pub async fn run_async_scan(
         let parser = register_all(liquid::ParserBuilder::with_stdlib()).build()?;
         let cache = Arc::new(SkipMap::new());
         // Run validation
-        run_secret_validation(Arc::clone(&datastore), &parser, &client, &cache, args.num_jobs)
-            .await?;
+        run_secret_validation(
+            Arc::clone(&datastore),
+            &parser,
+            &client,
+            &cache,
+            args.num_jobs,
+            args.cross_file_dependencies,
+        )
+        .await?;
+        flush_checkpoint(&datastore)?;
+    }
+    if let Some(path) = args.datastore.as_ref() {
+        persist_findings(path, &datastore, args)?;
     }
     // // Call cmd_report here
     crate::reporter::run(global_args, Arc::clone(&datastore), args)
@@ -298,6 +328,51 @@ pub async fn run_async_scan(
     Ok(())
 }
 
+/// Open (or create) the `--resume` checkpoint, restore the findings it holds
+/// and attach it to `datastore` so the scan phases can record their progress.
+fn resume_from_checkpoint(path: &Path, datastore: &Arc<Mutex<FindingsStore>>) -> Result<()> {
+    let mut ds = datastore.lock().unwrap();
+    let checkpoint = Checkpoint::open(path, ds.clone_root())
+        .with_context(|| format!("Failed to open checkpoint {}", path.display()))?;
+    checkpoint.load_into(&mut ds)?;
+    if checkpoint.num_completed_blobs() > 0 || checkpoint.num_completed_repos() > 0 {
+        info!(
+            "Resuming from checkpoint {}: {} repos and {} blobs already scanned, {} findings restored",
+            path.display(),
+            checkpoint.num_completed_repos(),
+            checkpoint.num_completed_blobs(),
+            ds.get_num_matches(),
+        );
+    }
+    ds.set_checkpoint(Arc::new(checkpoint));
+    Ok(())
+}
+
+fn flush_checkpoint(datastore: &Arc<Mutex<FindingsStore>>) -> Result<()> {
+    let checkpoint = datastore.lock().unwrap().checkpoint();
+    match checkpoint {
+        Some(cp) => cp.flush().context("Failed to write checkpoint"),
+        None => Ok(()),
+    }
+}
+
+/// Write the final findings to the on-disk datastore so they can be re-reported later.
+fn persist_findings(
+    path: &Path,
+    datastore: &Arc<Mutex<FindingsStore>>,
+    args: &scan::ScanArgs,
+) -> Result<()> {
+    let mut db = Datastore::create(path)?;
+    let ds = datastore.lock().unwrap();
+    db.save_findings(&ds)?;
+    db.set_metadata(META_CLONE_DIR, &ds.clone_root().to_string_lossy())?;
+    if let Some(jira_url) = args.input_specifier_args.jira_url.as_ref() {
+        db.set_metadata(META_JIRA_URL, jira_url.as_str())?;
+    }
+    info!("Saved findings to datastore {}", path.display());
+    Ok(())
+}
+
 fn initialize_environment() -> Result<()> {
     let init_progress = ProgressBar::new_spinner();
     init_progress.set_message("Initializing thread pool...");
@@ -19,6 +19,7 @@ use crate::{
     matcher::MatcherStats,
     rule_profiling::ConcurrentRuleProfiler,
     rules_database::RulesDatabase,
+    safe_list,
 };
 
 macro_rules! safe_println {
@@ -125,11 +126,13 @@ pub fn print_scan_summary(
             }
         });
     let matcher_stats = matcher_stats.lock().unwrap();
+    let allowlist_summary = safe_list::user_allowlist().map(|a| a.summary()).unwrap_or_default();
+    let suppressed = args.report_suppressed.then(safe_list::suppressed_matches);
 
     if args.output_args.format == ReportOutputFormat::Json
         || args.output_args.format == ReportOutputFormat::Jsonl
     {
-        let summary = json!({
+        let mut summary = json!({
             "findings": total_findings,
             "successful_validations": successful_validations,
             "failed_validations": failed_validations,
@@ -137,8 +140,12 @@ pub fn print_scan_summary(
             "blobs_scanned": matcher_stats.blobs_scanned,
             "bytes_scanned": matcher_stats.bytes_scanned,
             "scan_duration": duration.as_secs_f64(),
-            "findings_by_rule": sorted_findings
+            "findings_by_rule": sorted_findings,
+            "allowlist": allowlist_summary,
         });
+        if let Some(suppressed) = &suppressed {
+            summary["suppressed"] = json!(suppressed);
+        }
         safe_println!("{}", summary.to_string());
     } else if args.output_args.format == ReportOutputFormat::Pretty
         || args.output_args.output.is_some()
@@ -165,6 +172,34 @@ pub fn print_scan_summary(
             HumanBytes(matcher_stats.bytes_scanned)
         );
         safe_println!(" |Scan Duration...............: {}", humantime::format_duration(duration));
+        if let Some(suppressed) = &suppressed {
+            safe_println!(
+                " |Suppressed by Safe Rules....: {}",
+                suppressed.len().separate_with_commas()
+            );
+        }
+        if !allowlist_summary.is_empty() {
+            safe_println!(" |Allowlist Entries...........:");
+            for entry in &allowlist_summary {
+                if entry.expired {
+                    safe_println!(
+                        " |__{}: allowlist entry expired on {} ({}), {} not suppressed",
+                        entry.id,
+                        entry.expires,
+                        entry.owner,
+                        entry.matches.separate_with_commas()
+                    );
+                } else {
+                    safe_println!(
+                        " |__{}: {} suppressed ({}, until {})",
+                        entry.id,
+                        entry.matches.separate_with_commas(),
+                        entry.owner,
+                        entry.expires
+                    );
+                }
+            }
+        }
     }
 
     if args.rule_stats {
@@ -18,10 +18,14 @@ use tokio::{sync::Notify, time::timeout};
 
 use crate::{
     blob::BlobId,
+    checkpoint::StoredValidation,
     findings_store::{FindingsStore, FindingsStoreMessage},
     location::OffsetSpan,
     matcher::{Match, OwnedBlobMatch},
-    validation::{collect_variables_and_dependencies, validate_single_match, CachedResponse},
+    scanner::dependencies::{BlobPlace, CandidateIndex, PairingAttempt},
+    validation::{
+        collect_variables_and_dependencies, dependency_key, validate_single_match, CachedResponse,
+    },
 };
 
 #[allow(clippy::too_many_arguments)]
@@ -31,6 +35,7 @@ pub async fn run_secret_validation(
     client: &Client,
     cache: &Arc<SkipMap<String, CachedResponse>>,
     num_jobs: usize,
+    cross_file_dependencies: bool,
 ) -> Result<()> {
     // ── 1. Concurrency & counters ───────────────────────────────────────────
     let concurrency = if num_jobs > 0 { num_jobs } else { num_cpus::get() };
@@ -39,16 +44,22 @@ pub async fn run_secret_validation(
     let fail_count = Arc::new(AtomicUsize::new(0));
 
     // ── 2. Fetch rules + matches ────────────────────────────────────────────
-    let (_all_rules, all_matches_by_blob) = {
+    let (_all_rules, all_matches_by_blob, checkpoint) = {
         let ds = datastore.lock().unwrap();
         let rules = ds.get_rules()?;
         let mut map: FxHashMap<BlobId, Vec<Arc<FindingsStoreMessage>>> = FxHashMap::default();
         for arc_msg in ds.get_matches().iter().map(Arc::clone) {
             map.entry(arc_msg.1.id).or_default().push(arc_msg);
         }
-        (rules, map)
+        (rules, map, ds.checkpoint())
     };
 
+    // Matches other blobs can borrow dependency values from
+    let candidates = cross_file_dependencies.then(|| {
+        let messages: Vec<_> = all_matches_by_blob.values().flatten().cloned().collect();
+        Arc::new(CandidateIndex::new(&messages))
+    });
+
     // ── 3. Partition blobs ──────────────────────────────────────────────────
     let mut simple_matches = Vec::new();
     let mut dependent_blobs = FxHashMap::default(); // blob_id -- Vec<Arc<…>>
@@ -103,6 +114,7 @@ pub async fn run_secret_validation(
             let fail = fail_count.clone();
             // *** FIX: Clone the progress bar for each concurrent task ***
             let pb = pb.clone();
+            let checkpoint = checkpoint.clone();
 
             async move {
                 let secret = rep_arc
@@ -127,6 +139,26 @@ pub async fn run_secret_validation(
                     }
                 }
 
+                // Reuse the result of a previous, interrupted run
+                if let Some(stored) =
+                    checkpoint.as_ref().and_then(|cp| cp.validation::<StoredValidation>(&key))
+                {
+                    let status =
+                        StatusCode::from_u16(stored.status).unwrap_or(StatusCode::CONTINUE);
+                    count_outcome(stored.success, status, &success, &fail);
+                    val_res.insert(
+                        key,
+                        CachedResponse {
+                            body: stored.body,
+                            status,
+                            is_valid: stored.success,
+                            timestamp: Instant::now(),
+                        },
+                    );
+                    pb.inc(1);
+                    return;
+                }
+
                 let mut om = OwnedBlobMatch::convert_match_to_owned_blobmatch(
                     &rep_arc.2,
                     rep_arc.2.rule.clone(),
@@ -152,6 +184,9 @@ pub async fn run_secret_validation(
                     is_valid: om.validation_success,
                     timestamp: Instant::now(),
                 };
+                if let Some(cp) = checkpoint.as_ref().filter(|_| is_final(&om)) {
+                    cp.record_validation(key.clone(), &stored_validation(&om));
+                }
                 val_res.insert(key, cr);
 
                 // Now we use the cloned `pb`
@@ -215,9 +250,13 @@ pub async fn run_secret_validation(
                     let success = success_count.clone();
                     let fail = fail_count.clone();
                     let cache_glob = cache.clone();
+                    let checkpoint = checkpoint.clone();
+                    let checkpoint_key = format!("blob:{}", blob_id.hex());
+                    let candidates = candidates.clone();
+                    let place = BlobPlace::of(&matches_for_blob[0].0);
 
                     async move {
-                        let owned = matches_for_blob
+                        let mut owned = matches_for_blob
                             .iter()
                             .map(|arc_msg| {
                                 OwnedBlobMatch::convert_match_to_owned_blobmatch(
@@ -227,6 +266,29 @@ pub async fn run_secret_validation(
                             })
                             .collect::<Vec<_>>();
 
+                        // Reuse the results of a previous, interrupted run
+                        if let Some(stored) = checkpoint.as_ref().and_then(|cp| {
+                            cp.validation::<Vec<(u64, StoredValidation)>>(&checkpoint_key)
+                        }) {
+                            let stored: FxHashMap<_, _> = stored.into_iter().collect();
+                            if owned.iter().all(|om| stored.contains_key(&om.finding_fingerprint)) {
+                                for om in &mut owned {
+                                    let sv = &stored[&om.finding_fingerprint];
+                                    om.validation_success = sv.success;
+                                    om.validation_response_body = sv.body.clone();
+                                    om.validation_response_status = StatusCode::from_u16(sv.status)
+                                        .unwrap_or(StatusCode::CONTINUE);
+                                    count_outcome(
+                                        om.validation_success,
+                                        om.validation_response_status,
+                                        &success,
+                                        &fail,
+                                    );
+                                }
+                                return owned;
+                            }
+                        }
+
                         let (dep_vars, missing_deps) = collect_variables_and_dependencies(&owned);
 
                         let mut by_key: FxHashMap<String, Vec<OwnedBlobMatch>> =
@@ -248,27 +310,49 @@ pub async fn run_secret_validation(
                                 let success = success.clone();
                                 let fail = fail.clone();
                                 let cache_glob = cache_glob.clone();
+                                let attempts = candidates
+                                    .as_ref()
+                                    .map(|index| {
+                                        index.attempts(&rep, &place, &dep_vars, &miss_deps)
+                                    })
+                                    .unwrap_or_default();
 
                                 async move {
-                                    validate_single(
-                                        &mut rep,
-                                        &parser,
-                                        &client,
-                                        &dep_vars,
-                                        &miss_deps,
-                                        &val_cache,
-                                        &in_flight,
-                                        &success,
-                                        &fail,
-                                        &cache_glob,
-                                    )
-                                    .await;
+                                    if attempts.is_empty() {
+                                        validate_single(
+                                            &mut rep,
+                                            &parser,
+                                            &client,
+                                            &dep_vars,
+                                            &miss_deps,
+                                            &val_cache,
+                                            &in_flight,
+                                            &success,
+                                            &fail,
+                                            &cache_glob,
+                                        )
+                                        .await;
+                                    } else {
+                                        validate_with_pairings(
+                                            &mut rep,
+                                            attempts,
+                                            &parser,
+                                            &client,
+                                            &val_cache,
+                                            &in_flight,
+                                            &success,
+                                            &fail,
+                                            &cache_glob,
+                                        )
+                                        .await;
+                                    }
                                     for d in &mut dups {
                                         d.validation_success = rep.validation_success;
                                         d.validation_response_body =
                                             rep.validation_response_body.clone();
                                         d.validation_response_status =
                                             rep.validation_response_status;
+                                        d.paired_dependencies = rep.paired_dependencies.clone();
                                     }
                                     let mut out = vec![rep];
                                     out.extend(dups);
@@ -279,7 +363,17 @@ pub async fn run_secret_validation(
                             .collect()
                             .await;
 
-                        validated.into_iter().flatten().collect::<Vec<_>>()
+                        let validated = validated.into_iter().flatten().collect::<Vec<_>>();
+                        if let Some(cp) = checkpoint.as_ref() {
+                            if validated.iter().all(is_final) {
+                                let results: Vec<_> = validated
+                                    .iter()
+                                    .map(|om| (om.finding_fingerprint, stored_validation(om)))
+                                    .collect();
+                                cp.record_validation(checkpoint_key, &results);
+                            }
+                        }
+                        validated
                     }
                 })
                 .collect();
@@ -309,6 +403,7 @@ pub async fn run_secret_validation(
                             validation_success: om.validation_success,
                             validation_response_body: om.validation_response_body.clone(),
                             validation_response_status: om.validation_response_status.as_u16(),
+                            paired_dependencies: om.paired_dependencies,
                             ..orig.2.clone()
                         },
                     )));
@@ -328,6 +423,32 @@ pub async fn run_secret_validation(
     Ok(())
 }
 
+fn count_outcome(
+    is_valid: bool,
+    status: StatusCode,
+    success_count: &AtomicUsize,
+    fail_count: &AtomicUsize,
+) {
+    if is_valid {
+        success_count.fetch_add(1, Ordering::Relaxed);
+    } else if status != StatusCode::CONTINUE {
+        fail_count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Timeouts are not checkpointed so that a resumed scan tries them again.
+fn is_final(om: &OwnedBlobMatch) -> bool {
+    om.validation_response_status != StatusCode::REQUEST_TIMEOUT
+}
+
+fn stored_validation(om: &OwnedBlobMatch) -> StoredValidation {
+    StoredValidation {
+        success: om.validation_success,
+        status: om.validation_response_status.as_u16(),
+        body: om.validation_response_body.clone(),
+    }
+}
+
 // ---------------------------------------------------
 // The core validation logic, used in an async pipeline
 // ---------------------------------------------------
@@ -343,17 +464,7 @@ async fn validate_single(
     fail_count: &AtomicUsize,
     cache2: &Arc<SkipMap<String, CachedResponse>>,
 ) {
-    // Build key
-    let dep_vars_str = dep_vars
-        .get(om.rule.id())
-        .map(|hm| {
-            let mut sorted: Vec<_> = hm.iter().collect();
-            sorted.sort_by(|(k, _), (k2, _)| k.cmp(k2));
-            sorted.into_iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("|")
-        })
-        .unwrap_or_default();
-    let capture0 = om.captures.captures.get(0).map_or(String::new(), |c| c.value.to_string());
-    let cache_key = format!("{}|{}|{}", om.rule.name(), capture0, dep_vars_str);
+    let cache_key = build_cache_key(om, dep_vars);
     // Check cache first
     if let Some(cached) = cache.get(&cache_key) {
         om.validation_success = cached.is_valid;
@@ -427,22 +538,64 @@ async fn validate_single(
     }
 }
 
+/// Validates `om` with each pairing of cross-file dependency values in turn,
+/// stopping at the first that validates. When none does, the nearest pairing's
+/// result is kept.
+#[allow(clippy::too_many_arguments)]
+async fn validate_with_pairings(
+    om: &mut OwnedBlobMatch,
+    attempts: Vec<PairingAttempt>,
+    parser: &Parser,
+    client: &Client,
+    cache: &DashMap<String, CachedResponse>,
+    in_progress: &DashMap<String, ()>,
+    success_count: &AtomicUsize,
+    fail_count: &AtomicUsize,
+    cache2: &Arc<SkipMap<String, CachedResponse>>,
+) {
+    // Individual attempts are not counted; only the outcome that is kept
+    let (attempt_success, attempt_fail) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    let mut nearest = None;
+    for attempt in attempts {
+        validate_single(
+            om,
+            parser,
+            client,
+            &attempt.dependent_variables,
+            &attempt.missing_dependencies,
+            cache,
+            in_progress,
+            &attempt_success,
+            &attempt_fail,
+            cache2,
+        )
+        .await;
+        om.paired_dependencies = attempt.pairings;
+        if om.validation_success {
+            nearest = None;
+            break;
+        }
+        nearest.get_or_insert_with(|| {
+            (
+                om.validation_response_body.clone(),
+                om.validation_response_status,
+                om.paired_dependencies.clone(),
+            )
+        });
+    }
+    if let Some((body, status, pairings)) = nearest {
+        om.validation_response_body = body;
+        om.validation_response_status = status;
+        om.paired_dependencies = pairings;
+    }
+    count_outcome(om.validation_success, om.validation_response_status, success_count, fail_count);
+}
+
 // Helper to compute the cache key for an OwnedBlobMatch
 fn build_cache_key(
     om: &OwnedBlobMatch,
     dep_vars: &FxHashMap<String, Vec<(String, OffsetSpan)>>,
 ) -> String {
-    // Build key
-    let dep_vars_str = dep_vars
-        .get(om.rule.id())
-        .map(|hm| {
-            let mut sorted: Vec<_> = hm.iter().collect();
-            sorted.sort_by(|(k, _), (k2, _)| k.cmp(k2));
-            sorted.into_iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("|")
-        })
-        .unwrap_or_default();
-    // For demonstration, we’ll do a simplistic approach
-    // You can adapt from your existing logic
     let capture0 = om.captures.captures.get(0).map_or(String::new(), |c| c.value.to_string());
-    format!("{}|{}|{}", om.rule.name(), capture0, dep_vars_str)
+    format!("{}|{}|{}", om.rule.name(), capture0, dependency_key(om, dep_vars))
 }
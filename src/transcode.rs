@@ -0,0 +1,202 @@
+//! Transcoding of UTF-16 and UTF-32 text to UTF-8 before matching.
+//!
+//! Rules are compiled as byte regexes over UTF-8, so a token stored as UTF-16LE
+//! (`g\0h\0p\0_\0...`) never matches. Blobs detected as UTF-16 or UTF-32, by
+//! byte order mark or by the zero-byte pattern of mostly-ASCII text, are
+//! converted to UTF-8 and scanned in that form. An [`OffsetMap`] kept
+//! alongside maps offsets in the UTF-8 text back to the original bytes.
+//!
+//! ASCII-compatible legacy encodings (Latin-1, Windows-125x, Shift_JIS, GBK)
+//! are not transcoded: the ASCII characters that secrets are made of already
+//! have the same bytes in them as in UTF-8.
+
+use strum::Display;
+
+use crate::location::OffsetSpan;
+
+/// Blobs larger than this are scanned as-is.
+pub const TRANSCODE_LIMIT: usize = 64 * 1024 * 1024;
+/// Bytes inspected when looking for BOM-less UTF-16 or UTF-32.
+const SAMPLE_LEN: usize = 4096;
+/// Fewer code units than this are too little to tell an encoding apart.
+const MIN_SAMPLE_UNITS: usize = 8;
+
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq)]
+pub enum TextEncoding {
+    #[strum(serialize = "UTF-16LE")]
+    Utf16Le,
+    #[strum(serialize = "UTF-16BE")]
+    Utf16Be,
+    #[strum(serialize = "UTF-32LE")]
+    Utf32Le,
+    #[strum(serialize = "UTF-32BE")]
+    Utf32Be,
+}
+
+impl TextEncoding {
+    fn unit_len(self) -> usize {
+        match self {
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => 2,
+            TextEncoding::Utf32Le | TextEncoding::Utf32Be => 4,
+        }
+    }
+
+    fn unit(self, bytes: &[u8]) -> u32 {
+        match self {
+            TextEncoding::Utf16Le => u16::from_le_bytes([bytes[0], bytes[1]]).into(),
+            TextEncoding::Utf16Be => u16::from_be_bytes([bytes[0], bytes[1]]).into(),
+            TextEncoding::Utf32Le => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            TextEncoding::Utf32Be => u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+
+    /// Detects UTF-16 or UTF-32 text, returning the encoding and the length of
+    /// its byte order mark.
+    pub fn detect(bytes: &[u8]) -> Option<(Self, usize)> {
+        // UTF-32LE is checked first because its BOM starts with the UTF-16LE one
+        const BOMS: [(&[u8], TextEncoding); 4] = [
+            (b"\xFF\xFE\x00\x00", TextEncoding::Utf32Le),
+            (b"\x00\x00\xFE\xFF", TextEncoding::Utf32Be),
+            (b"\xFF\xFE", TextEncoding::Utf16Le),
+            (b"\xFE\xFF", TextEncoding::Utf16Be),
+        ];
+        for (bom, encoding) in BOMS {
+            if bytes.starts_with(bom) {
+                return (bytes.len() - bom.len())
+                    .is_multiple_of(encoding.unit_len())
+                    .then_some((encoding, bom.len()));
+            }
+        }
+        let sample = &bytes[..bytes.len().min(SAMPLE_LEN)];
+        [TextEncoding::Utf32Le, TextEncoding::Utf32Be, TextEncoding::Utf16Le, TextEncoding::Utf16Be]
+            .into_iter()
+            .find(|&encoding| {
+                bytes.len().is_multiple_of(encoding.unit_len()) && encoding.looks_like_text(sample)
+            })
+            .map(|encoding| (encoding, 0))
+    }
+
+    /// Without a BOM, only text that is mostly ASCII can be recognised: in the
+    /// right encoding nearly every code unit is a printable ASCII character.
+    fn looks_like_text(self, sample: &[u8]) -> bool {
+        let (mut units, mut ascii) = (0, 0);
+        for unit in sample.chunks_exact(self.unit_len()) {
+            let u = self.unit(unit);
+            units += 1;
+            ascii += usize::from((0x20..0x7F).contains(&u) || matches!(u, 0x09 | 0x0A | 0x0D));
+        }
+        units >= MIN_SAMPLE_UNITS && ascii * 10 >= units * 9
+    }
+}
+
+/// Maps byte offsets in transcoded text back to the original blob.
+pub struct OffsetMap {
+    /// Original offset of each transcoded byte, plus one entry for the end
+    offsets: Vec<u32>,
+}
+
+impl OffsetMap {
+    pub fn original_offset(&self, offset: usize) -> usize {
+        self.offsets[offset.min(self.offsets.len() - 1)] as usize
+    }
+
+    pub fn original_span(&self, span: &OffsetSpan) -> OffsetSpan {
+        OffsetSpan { start: self.original_offset(span.start), end: self.original_offset(span.end) }
+    }
+}
+
+/// A blob's text converted to UTF-8.
+pub struct Transcoded {
+    pub encoding: TextEncoding,
+    pub text: Vec<u8>,
+    pub offsets: OffsetMap,
+}
+
+impl Transcoded {
+    /// Converts UTF-16 or UTF-32 text to UTF-8. Returns `None` for anything
+    /// else, and for blobs over [`TRANSCODE_LIMIT`]. Invalid code units become
+    /// U+FFFD.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (encoding, bom_len) = TextEncoding::detect(bytes)?;
+        Self::with_encoding(bytes, encoding, bom_len)
+    }
+
+    /// Converts text already detected as `encoding`, with a byte order mark of
+    /// `bom_len` bytes, to UTF-8. Returns `None` for blobs over [`TRANSCODE_LIMIT`].
+    pub fn with_encoding(bytes: &[u8], encoding: TextEncoding, bom_len: usize) -> Option<Self> {
+        if bytes.len() > TRANSCODE_LIMIT {
+            return None;
+        }
+        let unit_len = encoding.unit_len();
+        let units = bytes[bom_len..].chunks_exact(unit_len).map(|unit| encoding.unit(unit));
+
+        let mut text = Vec::with_capacity(bytes.len() / unit_len);
+        let mut offsets = Vec::with_capacity(bytes.len() / unit_len + 1);
+        let mut push = |c: char, pos: usize| {
+            let mut buf = [0; 4];
+            let encoded = c.encode_utf8(&mut buf);
+            text.extend_from_slice(encoded.as_bytes());
+            offsets.extend(std::iter::repeat_n(pos as u32, encoded.len()));
+        };
+
+        let mut pos = bom_len;
+        match encoding {
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+                for c in char::decode_utf16(units.map(|u| u as u16)) {
+                    // Unpaired surrogates are replaced one unit at a time
+                    let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+                    push(c, pos);
+                    pos += unit_len * c.len_utf16();
+                }
+            }
+            TextEncoding::Utf32Le | TextEncoding::Utf32Be => {
+                for u in units {
+                    push(char::from_u32(u).unwrap_or(char::REPLACEMENT_CHARACTER), pos);
+                    pos += unit_len;
+                }
+            }
+        }
+        offsets.push(bytes.len() as u32);
+        Some(Self { encoding, text, offsets: OffsetMap { offsets } })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn detects_boms_and_bomless_ascii_text() {
+        let mut with_bom = b"\xFF\xFE".to_vec();
+        with_bom.extend(utf16le("é"));
+        assert_eq!(TextEncoding::detect(&with_bom), Some((TextEncoding::Utf16Le, 2)));
+
+        let be: Vec<u8> = "token = abc123".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(TextEncoding::detect(&be), Some((TextEncoding::Utf16Be, 0)));
+
+        let utf32: Vec<u8> =
+            "token = abc123".chars().flat_map(|c| (c as u32).to_le_bytes()).collect();
+        assert_eq!(TextEncoding::detect(&utf32), Some((TextEncoding::Utf32Le, 0)));
+
+        assert_eq!(TextEncoding::detect(b"token = abc123, plain UTF-8"), None);
+        assert_eq!(TextEncoding::detect(&[0xFF, 0xFE, 0xFD]), None);
+    }
+
+    #[test]
+    fn offsets_map_back_to_the_original_bytes() {
+        let mut bytes = b"\xFF\xFE".to_vec();
+        bytes.extend(utf16le("é\u{1F600}\nkey=ghp_abc"));
+        let t = Transcoded::from_bytes(&bytes).unwrap();
+        assert_eq!(t.text, "é\u{1F600}\nkey=ghp_abc".as_bytes());
+
+        // "ghp_abc" starts after the BOM, é (1 unit), 😀 (2 units), \n and "key=" (5 units)
+        let start = t.text.windows(3).position(|w| w == b"ghp").unwrap();
+        let span = t.offsets.original_span(&OffsetSpan { start, end: t.text.len() });
+        assert_eq!(span, OffsetSpan { start: 2 + 2 * 8, end: bytes.len() });
+        assert_eq!(&bytes[span.start..span.end], utf16le("ghp_abc"));
+    }
+}
@@ -2,7 +2,10 @@ use std::{
     collections::BTreeMap,
     fs,
     hash::{Hash, Hasher},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -13,7 +16,7 @@ use http::StatusCode;
 use liquid::Object;
 use liquid_core::{Value, ValueView};
 use once_cell::sync::{Lazy, OnceCell};
-use reqwest::{header, header::HeaderValue, multipart, Client, Url};
+use reqwest::{header, header::HeaderValue, multipart, Client, ClientBuilder, Proxy, Url};
 use rustc_hash::FxHashMap;
 use tokio::{sync::Notify, time};
 use tracing::debug;
@@ -38,6 +41,7 @@ const VALIDATION_CACHE_SECONDS: u64 = 1200; // 20 minutes
 const MAX_VALIDATION_BODY_LEN: usize = 2048;
 
 static USER_AGENT_SUFFIX: OnceCell<String> = OnceCell::new();
+static IGNORE_CERTS: AtomicBool = AtomicBool::new(false);
 
 const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
          AppleWebKit/537.36 (KHTML, like Gecko) \
@@ -70,11 +74,34 @@ pub fn set_user_agent_suffix<S: Into<String>>(suffix: Option<S>) {
     }
 }
 
+/// Make clients from [`http_client_builder`] accept invalid TLS certificates (`--ignore-certs`).
+///
+/// Like [`set_user_agent_suffix`], this should be called once near program start.
+pub fn set_ignore_certs(ignore_certs: bool) {
+    IGNORE_CERTS.store(ignore_certs, Ordering::Relaxed);
+}
+
+/// Client builder for Kingfisher's own requests: [`GLOBAL_USER_AGENT`], the
+/// `HTTPS_PROXY` from the environment and `--ignore-certs`.
+pub fn http_client_builder() -> Result<ClientBuilder> {
+    let mut builder = Client::builder()
+        .user_agent(GLOBAL_USER_AGENT.as_str())
+        .danger_accept_invalid_certs(IGNORE_CERTS.load(Ordering::Relaxed));
+    if let Ok(proxy) = std::env::var("HTTPS_PROXY").or_else(|_| std::env::var("https_proxy")) {
+        builder = builder.proxy(Proxy::all(&proxy)?);
+    }
+    Ok(builder)
+}
+
 // Use SkipMap-based cache instead of a mutex-wrapped FxHashMap.
 type Cache = Arc<SkipMap<String, CachedResponse>>;
 
-/// Returns an opaque 64-bit fingerprint for “same secret under the same rule”.
-fn secret_fingerprint(m: &OwnedBlobMatch) -> u64 {
+/// Returns an opaque 64-bit fingerprint for “same secret under the same rule”,
+/// paired with the same dependency values.
+fn secret_fingerprint(
+    m: &OwnedBlobMatch,
+    dependent_variables: &FxHashMap<String, Vec<(String, OffsetSpan)>>,
+) -> u64 {
     let mut hasher = xxhash_rust::xxh3::Xxh3::new();
     m.rule.syntax().id.hash(&mut hasher);
 
@@ -82,9 +109,33 @@ fn secret_fingerprint(m: &OwnedBlobMatch) -> u64 {
     if let Some(c0) = m.captures.captures.get(0) {
         c0.value.hash(&mut hasher);
     }
+    dependency_key(m, dependent_variables).hash(&mut hasher);
     hasher.finish()
 }
 
+/// The `depends_on_rule` values validation would use for `m`, as `NAME=value`
+/// pairs joined with `|`. Validation renders the last value bound to each name.
+pub fn dependency_key(
+    m: &OwnedBlobMatch,
+    dependent_variables: &FxHashMap<String, Vec<(String, OffsetSpan)>>,
+) -> String {
+    let mut pairs: Vec<String> = m
+        .rule
+        .syntax()
+        .depends_on_rule
+        .iter()
+        .flatten()
+        .filter_map(|dep| {
+            let name = dep.variable.to_uppercase();
+            let (value, _) = dependent_variables.get(&name)?.last()?;
+            Some(format!("{name}={value}"))
+        })
+        .collect();
+    pairs.sort();
+    pairs.dedup();
+    pairs.join("|")
+}
+
 static VALIDATION_CACHE: OnceCell<DashMap<u64, CachedResponse>> = OnceCell::new();
 static IN_FLIGHT: OnceCell<DashMap<u64, Arc<Notify>>> = OnceCell::new();
 
@@ -255,7 +306,7 @@ async fn timed_validate_single_match<'a>(
     // ──────────────────────────────────────────────────────────
     // 1. process-wide fingerprint de-dup
     // ──────────────────────────────────────────────────────────
-    let fp = secret_fingerprint(m);
+    let fp = secret_fingerprint(m, dependent_variables);
 
     if let Some(entry) = VALIDATION_CACHE.get_or_init(DashMap::new).get(&fp) {
         if entry.timestamp.elapsed() < Duration::from_secs(VALIDATION_CACHE_SECONDS) {
@@ -1089,7 +1140,10 @@ rules:
             validation_response_status: StatusCode::OK,
             validation_success: false,
             calculated_entropy: 0.0, // or compute your own
-            is_base64: false,
+            encoding: None,
+            parts: Vec::new(),
+            visible: true,
+            paired_dependencies: Vec::new(),
         };
         let parser = register_all(liquid::ParserBuilder::with_stdlib()).build()?;
         let client = reqwest::Client::new();
@@ -0,0 +1,25 @@
+-- Direct assignments
+local db_password = "s3cr3tp@ssw0rd"
+api_key = "somekey_29f3d2hbiuhlf203hewidd3"
+local greeting = "Hello, World!"
+
+-- Field and index assignments
+config = {}
+config.private_key = "ja4wALsaho20af21dS"
+config["aws_secret"] = "eD4++rSUVbOmDrRI7EDLmskuwpAAddEA0WNwu+fI"
+
+-- Table constructors
+local settings = {
+    username = "admin",
+    password = "Mechanic#123",
+    ["SECURITY_CREDENTIALS"] = "412389uSwYkRm1Tg!",
+    port = 5432,
+}
+
+local passphrase = "trustno1" -- NOKINGFISHER
+
+local function connect(opts)
+    return opts.host .. ":" .. opts.port
+end
+
+connect({ host = "db.internal", port = 5432, token = "blink182" })
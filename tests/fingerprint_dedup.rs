@@ -33,6 +33,7 @@ fn make_match(fp: u64) -> Match {
         references: vec![],
         validation: None,
         depends_on_rule: vec![],
+        ..Default::default()
     };
     let rule = Arc::new(Rule::new(syntax));
     Match {
@@ -61,6 +62,9 @@ fn make_match(fp: u64) -> Match {
         calculated_entropy: 0.0,
         visible: true,
         is_base64: false,
+        encoding: None,
+        parts: Vec::new(),
+        paired_dependencies: Vec::new(),
     }
 }
 
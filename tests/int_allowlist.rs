@@ -55,6 +55,10 @@ fn run_skiplist(skip_regex: Vec<String>, skip_skipword: Vec<String>) -> Result<u
             rules_path: vec![rules_dir.clone()],
             rule: vec!["all".into()],
             load_builtins: false,
+            rule_tag: vec![],
+            exclude_rule_tag: vec![],
+            rules_url: vec![],
+            rules_public_key: None,
         },
         input_specifier_args: InputSpecifierArgs {
             path_inputs: vec![inputs_dir.join("a.txt")],
@@ -128,20 +132,31 @@ fn run_skiplist(skip_regex: Vec<String>, skip_skipword: Vec<String>) -> Result<u
             no_extract_archives: false,
             extraction_depth: 1,
             no_binary: true,
+            stream_large_files: false,
         },
         confidence: ConfidenceLevel::Low,
         no_validate: true,
+        cross_file_dependencies: false,
         rule_stats: false,
         only_valid: false,
         min_entropy: Some(0.0),
         redact: false,
         git_repo_timeout: 1800,
         output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+        datastore: None,
+        resume: None,
+        blob_cache: None,
         no_dedup: false,
         baseline_file: None,
         manage_baseline: false,
         skip_regex: skip_regex,
         skip_word: skip_skipword,
+        allowlist: None,
+        safe_list: Vec::new(),
+        disable_safe_rule: Vec::new(),
+        report_suppressed: false,
+        decode: Vec::new(),
+        no_decode: Vec::new(),
         skip_aws_account: Vec::new(),
         skip_aws_account_file: None,
         no_base64: false,
@@ -157,6 +172,8 @@ fn run_skiplist(skip_regex: Vec<String>, skip_skipword: Vec<String>) -> Result<u
         self_update: false,
         ignore_certs: false,
         user_agent_suffix: None,
+        config: None,
+        no_config: false,
     };
 
     let loaded = RuleLoader::from_rule_specifiers(&scan_args.rules).load(&scan_args)?;
@@ -53,6 +53,10 @@ fn test_gitlab_remote_scan() -> Result<()> {
             rules_path: Vec::new(),
             rule: vec!["all".into()],
             load_builtins: true,
+            rule_tag: vec![],
+            exclude_rule_tag: vec![],
+            rules_url: vec![],
+            rules_public_key: None,
         },
         input_specifier_args: InputSpecifierArgs {
             path_inputs: Vec::new(),
@@ -133,20 +137,31 @@ fn test_gitlab_remote_scan() -> Result<()> {
             extraction_depth: 2,
             no_binary: true,
             exclude: Vec::new(), // Exclude patterns
+            stream_large_files: false,
         },
         confidence: ConfidenceLevel::Medium,
         no_validate: false,
+        cross_file_dependencies: false,
         rule_stats: false,
         only_valid: false,
         min_entropy: None,
         redact: false,
         git_repo_timeout: 1800, // 30 minutes
         output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+        datastore: None,
+        resume: None,
+        blob_cache: None,
         no_dedup: true,
         baseline_file: None,
         manage_baseline: false,
         skip_regex: Vec::new(),
         skip_word: Vec::new(),
+        allowlist: None,
+        safe_list: Vec::new(),
+        disable_safe_rule: Vec::new(),
+        report_suppressed: false,
+        decode: Vec::new(),
+        no_decode: Vec::new(),
         skip_aws_account: Vec::new(),
         skip_aws_account_file: None,
         no_base64: false,
@@ -162,6 +177,8 @@ fn test_gitlab_remote_scan() -> Result<()> {
         self_update: false,
         ignore_certs: false,
         user_agent_suffix: None,
+        config: None,
+        no_config: false,
     };
 
     let datastore = Arc::new(Mutex::new(FindingsStore::new(clone_dir)));
@@ -204,6 +221,10 @@ fn test_gitlab_remote_scan_no_history() -> Result<()> {
             rules_path: Vec::new(),
             rule: vec!["all".into()],
             load_builtins: true,
+            rule_tag: vec![],
+            exclude_rule_tag: vec![],
+            rules_url: vec![],
+            rules_public_key: None,
         },
         input_specifier_args: InputSpecifierArgs {
             path_inputs: Vec::new(),
@@ -281,20 +302,31 @@ fn test_gitlab_remote_scan_no_history() -> Result<()> {
             extraction_depth: 2,
             no_binary: true,
             exclude: Vec::new(),
+            stream_large_files: false,
         },
         confidence: ConfidenceLevel::Medium,
         no_validate: false,
+        cross_file_dependencies: false,
         rule_stats: false,
         only_valid: false,
         min_entropy: None,
         redact: false,
         git_repo_timeout: 1800,
         output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+        datastore: None,
+        resume: None,
+        blob_cache: None,
         no_dedup: true,
         baseline_file: None,
         manage_baseline: false,
         skip_regex: Vec::new(),
         skip_word: Vec::new(),
+        allowlist: None,
+        safe_list: Vec::new(),
+        disable_safe_rule: Vec::new(),
+        report_suppressed: false,
+        decode: Vec::new(),
+        no_decode: Vec::new(),
         skip_aws_account: Vec::new(),
         skip_aws_account_file: None,
         no_base64: false,
@@ -311,6 +343,8 @@ fn test_gitlab_remote_scan_no_history() -> Result<()> {
         self_update: false,
         ignore_certs: false,
         user_agent_suffix: None,
+        config: None,
+        no_config: false,
     };
 
     let datastore = Arc::new(Mutex::new(FindingsStore::new(clone_dir)));
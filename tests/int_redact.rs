@@ -38,6 +38,10 @@ async fn test_redact_hashes_finding_values() -> Result<()> {
             rules_path: Vec::new(),
             rule: vec!["all".into()],
             load_builtins: true,
+            rule_tag: vec![],
+            exclude_rule_tag: vec![],
+            rules_url: vec![],
+            rules_public_key: None,
         },
         input_specifier_args: InputSpecifierArgs {
             path_inputs: vec![PathBuf::from("testdata/generic_secrets.py")],
@@ -110,20 +114,31 @@ async fn test_redact_hashes_finding_values() -> Result<()> {
             no_binary: true,
             no_extract_archives: false,
             exclude: Vec::new(),
+            stream_large_files: false,
         },
         confidence: ConfidenceLevel::Low,
         no_validate: true,
+        cross_file_dependencies: false,
         rule_stats: false,
         only_valid: false,
         min_entropy: Some(0.0),
         redact: true,
         git_repo_timeout: 1800,
         output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+        datastore: None,
+        resume: None,
+        blob_cache: None,
         no_dedup: true,
         baseline_file: None,
         manage_baseline: false,
         skip_regex: Vec::new(),
         skip_word: Vec::new(),
+        allowlist: None,
+        safe_list: Vec::new(),
+        disable_safe_rule: Vec::new(),
+        report_suppressed: false,
+        decode: Vec::new(),
+        no_decode: Vec::new(),
         skip_aws_account: Vec::new(),
         skip_aws_account_file: None,
         no_base64: false,
@@ -140,6 +155,8 @@ async fn test_redact_hashes_finding_values() -> Result<()> {
         progress: Mode::Never,
         ignore_certs: false,
         user_agent_suffix: None,
+        config: None,
+        no_config: false,
     };
 
     let loaded = RuleLoader::from_rule_specifiers(&scan_args.rules).load(&scan_args)?;
@@ -0,0 +1,99 @@
+use std::{fs, sync::Arc};
+
+use anyhow::Result;
+use kingfisher::{
+    cli::commands::{rules::RuleSpecifierArgs, validate::ValidateReportFormat},
+    revalidate::{parse_report, revalidate_records, write_report},
+    rule_loader::RuleLoader,
+    rules::rule::Confidence,
+};
+use tempfile::TempDir;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, Request, ResponseTemplate,
+};
+
+fn record(secret: &str, line: u32, status: &str) -> String {
+    format!(
+        r#"{{"rule":{{"name":"Rotating Token","id":"demo.rotating.1"}},"finding":{{"snippet":"{secret}","fingerprint":"{line}","confidence":"medium","entropy":"3.00","validation":{{"status":"{status}","response":""}},"language":"Unknown","line":{line},"column_start":1,"column_end":17,"path":"config/app.env"}}}}"#
+    )
+}
+
+#[tokio::test]
+async fn revalidated_report_marks_changed_findings() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/check"))
+        .respond_with(|req: &Request| {
+            let active = req.url.query().is_some_and(|q| q.contains("rvtoken_active01"));
+            ResponseTemplate::new(if active { 200 } else { 401 })
+        })
+        .mount(&server)
+        .await;
+
+    let work = TempDir::new()?;
+    fs::write(
+        work.path().join("demo.yml"),
+        format!(
+            r#"rules:
+  - name: Rotating Token
+    id: demo.rotating.1
+    pattern: 'TOKEN=(rvtoken_[a-z0-9]{{8}})'
+    confidence: medium
+    min_entropy: 0.0
+    validation:
+      type: Http
+      content:
+        request:
+          method: GET
+          url: '{base}/check?token={{{{ TOKEN }}}}'
+          response_matcher:
+            - type: StatusMatch
+              status:
+                - 200
+"#,
+            base = server.uri()
+        ),
+    )?;
+
+    let specs = RuleSpecifierArgs {
+        rules_path: vec![work.path().to_path_buf()],
+        rule: vec!["all".into()],
+        load_builtins: false,
+        rule_tag: vec![],
+        exclude_rule_tag: vec![],
+        rules_url: vec![],
+        rules_public_key: None,
+    };
+    let loaded = RuleLoader::from_rule_specifiers(&specs).load_with_confidence(Confidence::Low)?;
+    let rules: Vec<_> =
+        loaded.resolve_enabled_rules()?.into_iter().map(|r| Arc::new(r.clone())).collect();
+
+    let report = format!(
+        "{}\n{}\n",
+        record("rvtoken_active01", 1, "Inactive Credential"),
+        record("rvtoken_revoked1", 2, "Inactive Credential")
+    );
+    let (mut records, format) = parse_report(&report)?;
+    assert_eq!(format, ValidateReportFormat::Jsonl);
+
+    let client = reqwest::Client::new();
+    let summary = revalidate_records(&mut records, &rules, &client, 2).await?;
+    assert_eq!((summary.validated, summary.changed), (2, 1));
+
+    let active = &records[0].finding.validation;
+    assert_eq!(active.status, "Active Credential");
+    assert_eq!(active.previous_status.as_deref(), Some("Inactive Credential"));
+    assert_eq!(active.changed, Some(true));
+
+    let revoked = &records[1].finding.validation;
+    assert_eq!(revoked.status, "Inactive Credential");
+    assert_eq!(revoked.changed, Some(false));
+
+    let mut out = Vec::new();
+    write_report(&mut out, &records, format)?;
+    let first: serde_json::Value =
+        serde_json::from_str(String::from_utf8(out)?.lines().next().unwrap())?;
+    assert_eq!(first["finding"]["validation"]["changed"], true);
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use std::fs;
+
+use anyhow::Result;
+use kingfisher::{
+    cli::{
+        commands::{
+            output::OutputArgs,
+            rules::{RuleSpecifierArgs, RulesTestArgs, RulesTestOutputFormat},
+        },
+        global::Mode,
+        GlobalArgs,
+    },
+    rule_corpus::{evaluate, load_expectations, scan_corpus, FailureKind},
+};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn corpus_scan_reports_precision_and_recall() -> Result<()> {
+    let work = TempDir::new()?;
+    let rules_dir = work.path().join("rules");
+    let corpus = work.path().join("corpus");
+    fs::create_dir_all(&rules_dir)?;
+    fs::create_dir_all(corpus.join("config"))?;
+
+    fs::write(
+        rules_dir.join("demo.yml"),
+        r#"rules:
+  - id: demo.corpus.1
+    name: Demo corpus token
+    pattern: 'corpustoken_([a-z0-9]{12})'
+    confidence: low
+    min_entropy: 2.0
+"#,
+    )?;
+    fs::write(corpus.join("config/prod.env"), "A=1\nTOKEN=corpustoken_a1b2c3d4e5f6\n")?;
+    fs::write(corpus.join("config/sample.env"), "TOKEN=corpustoken_q9w8e7r6t5y4\n")?;
+    fs::write(corpus.join("missing.env"), "TOKEN=corpustoken_aaaaaaaaaaaa\n")?;
+    fs::write(
+        corpus.join("expectations.yml"),
+        r#"expectations:
+  - {path: config/prod.env, rule: demo.corpus.1, line: 2, expected: yes}
+  - {path: config/sample.env, rule: demo.corpus.1, expected: no}
+  - {path: missing.env, rule: demo.corpus.1, line: 1, expected: yes}
+  - {path: config/prod.env, rule: demo.corpus.1, line: 1, expected: no}
+"#,
+    )?;
+
+    let args = RulesTestArgs {
+        corpus: corpus.clone(),
+        expectations: None,
+        rules: RuleSpecifierArgs {
+            rules_path: vec![rules_dir],
+            rule: vec!["all".into()],
+            load_builtins: false,
+            rule_tag: vec![],
+            exclude_rule_tag: vec![],
+            rules_url: vec![],
+            rules_public_key: None,
+        },
+        output_args: OutputArgs { output: None, format: RulesTestOutputFormat::Json },
+    };
+    let global_args = GlobalArgs {
+        verbose: 0,
+        quiet: true,
+        color: Mode::Never,
+        progress: Mode::Never,
+        no_update_check: true,
+        self_update: false,
+        ignore_certs: false,
+        user_agent_suffix: None,
+        config: None,
+        no_config: false,
+    };
+
+    let expectations_path = corpus.join("expectations.yml");
+    let expectations = load_expectations(&expectations_path)?;
+    let findings = scan_corpus(&global_args, &args, &expectations_path).await?;
+    assert_eq!(findings.len(), 2, "{findings:?}");
+
+    let report = evaluate(&expectations, &findings);
+    let stats = &report.rules["demo.corpus.1"];
+    assert_eq!(stats.true_positives, 1);
+    assert_eq!(stats.false_positives, 1);
+    assert_eq!(stats.false_negatives, 1);
+    assert_eq!(stats.true_negatives, 1);
+    assert_eq!(stats.precision, Some(0.5));
+    assert_eq!(stats.recall, Some(0.5));
+
+    let kinds: Vec<_> = report.failures.iter().map(|f| f.kind).collect();
+    assert_eq!(kinds, vec![FailureKind::FalsePositive, FailureKind::FalseNegative]);
+    Ok(())
+}
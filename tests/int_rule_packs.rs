@@ -0,0 +1,82 @@
+use std::fs;
+
+use anyhow::Result;
+use kingfisher::{rule_loader::RuleLoader, rule_pack::RulePackCache, rules::rule::Confidence};
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use url::Url;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const PACK_RULES: &str = r#"rules:
+  - name: Internal Pack Key
+    id: internal.pack.1
+    pattern: '(ipk_[a-z0-9]{16})'
+    confidence: medium
+"#;
+
+fn pack_tarball() -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(PACK_RULES.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, "internal-rules/internal.yml", PACK_RULES.as_bytes())?;
+    Ok(builder.into_inner()?.finish()?)
+}
+
+fn loader(cache: &TempDir, url: Url) -> RuleLoader {
+    RuleLoader::new()
+        .load_builtins(false)
+        .rule_pack_urls([url])
+        .rule_pack_cache(RulePackCache::new(cache.path()))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn loads_pinned_rule_pack_over_http_and_reuses_cache() -> Result<()> {
+    let archive = pack_tarball()?;
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/packs/internal-1.0.0.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(archive.clone()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let cache = TempDir::new()?;
+    let sha256 = hex::encode(Sha256::digest(&archive));
+    let url = Url::parse(&format!("{}/packs/internal-1.0.0.tar.gz#sha256={sha256}", server.uri()))?;
+
+    for _ in 0..2 {
+        let loaded = loader(&cache, url.clone()).load_with_confidence(Confidence::Low)?;
+        let ids: Vec<_> =
+            loaded.resolve_enabled_rules()?.iter().map(|r| r.id().to_string()).collect();
+        assert_eq!(ids, ["internal.pack.1"]);
+    }
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_tampered_rule_pack() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/packs/internal-1.0.0.tar.gz"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(pack_tarball()?))
+        .mount(&server)
+        .await;
+
+    let cache = TempDir::new()?;
+    let pinned = hex::encode(Sha256::digest(b"a different release"));
+    let url = Url::parse(&format!("{}/packs/internal-1.0.0.tar.gz#sha256={pinned}", server.uri()))?;
+    let Err(err) = loader(&cache, url).load_with_confidence(Confidence::Low) else {
+        panic!("a rule pack with the wrong checksum should not load");
+    };
+    assert!(format!("{err:#}").contains("expected"), "{err:#}");
+    assert!(fs::read_dir(cache.path())?.all(|e| !e.unwrap().path().join("pack.json").exists()));
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::fs;
+
+use anyhow::Result;
+use kingfisher::{
+    cli::commands::rules::RuleSpecifierArgs,
+    rule_loader::RuleLoader,
+    rules::rule::{Confidence, Severity},
+};
+use tempfile::TempDir;
+
+const RULES: &str = r#"rules:
+  - name: Demo Cloud Key
+    id: demo.cloud.1
+    tags: [cloud]
+    severity: critical
+    pattern: '(cloudkey_[a-z0-9]{12})'
+    confidence: medium
+
+  - name: Demo AI Key
+    id: demo.ai.1
+    tags: [AI, cloud]
+    pattern: '(aikey_[a-z0-9]{12})'
+    confidence: medium
+
+  - name: Demo Payment Key
+    id: demo.payment.1
+    tags: [payment]
+    pattern: '(paykey_[a-z0-9]{12})'
+    confidence: medium
+"#;
+
+fn enabled_ids(
+    work: &TempDir,
+    rule_tag: &[&str],
+    exclude_rule_tag: &[&str],
+) -> Result<Vec<String>> {
+    let specs = RuleSpecifierArgs {
+        rules_path: vec![work.path().to_path_buf()],
+        rule: vec!["all".into()],
+        load_builtins: false,
+        rule_tag: rule_tag.iter().map(|t| t.to_string()).collect(),
+        exclude_rule_tag: exclude_rule_tag.iter().map(|t| t.to_string()).collect(),
+        rules_url: vec![],
+        rules_public_key: None,
+    };
+    let loaded = RuleLoader::from_rule_specifiers(&specs).load_with_confidence(Confidence::Low)?;
+    Ok(loaded.resolve_enabled_rules()?.iter().map(|r| r.id().to_string()).collect())
+}
+
+#[test]
+fn rule_tags_filter_enabled_rules() -> Result<()> {
+    let work = TempDir::new()?;
+    fs::write(work.path().join("demo.yml"), RULES)?;
+
+    assert_eq!(enabled_ids(&work, &[], &[])?.len(), 3);
+    assert_eq!(enabled_ids(&work, &["cloud"], &[])?, vec!["demo.ai.1", "demo.cloud.1"]);
+    assert_eq!(enabled_ids(&work, &["ai", "payment"], &[])?, vec!["demo.ai.1", "demo.payment.1"]);
+    assert_eq!(enabled_ids(&work, &["cloud"], &["ai"])?, vec!["demo.cloud.1"]);
+    assert_eq!(enabled_ids(&work, &[], &["Cloud"])?, vec!["demo.payment.1"]);
+    assert!(enabled_ids(&work, &["pii"], &[]).is_err());
+    Ok(())
+}
+
+#[test]
+fn severity_and_tags_are_loaded() -> Result<()> {
+    let work = TempDir::new()?;
+    fs::write(work.path().join("demo.yml"), RULES)?;
+
+    let specs = RuleSpecifierArgs {
+        rules_path: vec![work.path().to_path_buf()],
+        rule: vec!["demo.cloud.1".into()],
+        load_builtins: false,
+        rule_tag: vec![],
+        exclude_rule_tag: vec![],
+        rules_url: vec![],
+        rules_public_key: None,
+    };
+    let loaded = RuleLoader::from_rule_specifiers(&specs).load_with_confidence(Confidence::Low)?;
+    let rules = loaded.resolve_enabled_rules()?;
+    assert_eq!(rules[0].severity(), Some(Severity::Critical));
+    assert_eq!(rules[0].tags(), ["cloud".to_string()]);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::{fs, sync::Arc};
+
+use anyhow::Result;
+use kingfisher::{
+    cli::commands::rules::RuleSpecifierArgs, revalidate::validate_secret, rule_loader::RuleLoader,
+    rules::rule::Confidence,
+};
+use tempfile::TempDir;
+use wiremock::{
+    matchers::{method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn validates_secret_with_dependent_variable() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/whoami"))
+        .and(query_param("id", "acct-1234"))
+        .and(query_param("secret", "demosecret_abcdefgh"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+        .mount(&server)
+        .await;
+
+    let work = TempDir::new()?;
+    fs::write(
+        work.path().join("demo.yml"),
+        format!(
+            r#"rules:
+  - name: Demo Account ID
+    id: demo.account.1
+    pattern: '(acct-[0-9]{{4}})'
+    confidence: medium
+    min_entropy: 0.0
+
+  - name: Demo Secret
+    id: demo.secret.1
+    pattern: 'secret\s*=\s*(demosecret_[a-z]{{8}})'
+    confidence: medium
+    min_entropy: 0.0
+    depends_on_rule:
+      - rule_id: demo.account.1
+        variable: ACCOUNT
+    validation:
+      type: Http
+      content:
+        request:
+          method: GET
+          url: '{base}/whoami?id={{{{ ACCOUNT }}}}&secret={{{{ TOKEN }}}}'
+          response_matcher:
+            - report_response: true
+            - type: StatusMatch
+              status:
+                - 200
+"#,
+            base = server.uri()
+        ),
+    )?;
+
+    let specs = RuleSpecifierArgs {
+        rules_path: vec![work.path().to_path_buf()],
+        rule: vec!["all".into()],
+        load_builtins: false,
+        rule_tag: vec![],
+        exclude_rule_tag: vec![],
+        rules_url: vec![],
+        rules_public_key: None,
+    };
+    let loaded = RuleLoader::from_rule_specifiers(&specs).load_with_confidence(Confidence::Low)?;
+    let rule_by_id =
+        |id: &str| Arc::new(loaded.iter_rules().find(|r| r.id() == id).unwrap().clone());
+    let client = reqwest::Client::new();
+
+    let secret_rule = rule_by_id("demo.secret.1");
+    let vars = vec![("ACCOUNT".to_string(), "acct-1234".to_string())];
+    let om = validate_secret(&secret_rule, "demosecret_abcdefgh", &vars, &client).await?;
+    assert!(om.validation_success, "{}", om.validation_response_body);
+    assert_eq!(om.validation_response_status.as_u16(), 200);
+
+    let err = match validate_secret(&secret_rule, "demosecret_abcdefgh", &[], &client).await {
+        Ok(_) => panic!("validation should require the ACCOUNT variable"),
+        Err(e) => e,
+    };
+    assert!(err.to_string().contains("--var ACCOUNT=<value>"));
+
+    let account_rule = rule_by_id("demo.account.1");
+    assert!(validate_secret(&account_rule, "acct-1234", &[], &client).await.is_err());
+    Ok(())
+}
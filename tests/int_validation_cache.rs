@@ -108,6 +108,10 @@ async fn test_validation_cache_and_depvars() -> Result<()> {
             rules_path: vec![work_dir.path().to_path_buf()],
             rule: vec!["all".into()],
             load_builtins: false,
+            rule_tag: vec![],
+            exclude_rule_tag: vec![],
+            rules_url: vec![],
+            rules_public_key: None,
         },
         input_specifier_args: InputSpecifierArgs {
             path_inputs: vec![secret_file.clone()],
@@ -190,20 +194,31 @@ async fn test_validation_cache_and_depvars() -> Result<()> {
             no_binary: true,
             no_extract_archives: false,
             exclude: Vec::new(), // Exclude patterns
+            stream_large_files: false,
         },
         confidence: ConfidenceLevel::Low,
         no_validate: false,
+        cross_file_dependencies: false,
         rule_stats: false,
         only_valid: false,
         min_entropy: Some(0.0),
         redact: false,
         git_repo_timeout: 1800, // 30 minutes
         output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+        datastore: None,
+        resume: None,
+        blob_cache: None,
         no_dedup: true, // keep duplicates so the cache is stressed
         baseline_file: None,
         manage_baseline: false,
         skip_regex: Vec::new(),
         skip_word: Vec::new(),
+        allowlist: None,
+        safe_list: Vec::new(),
+        disable_safe_rule: Vec::new(),
+        report_suppressed: false,
+        decode: Vec::new(),
+        no_decode: Vec::new(),
         skip_aws_account: Vec::new(),
         skip_aws_account_file: None,
         no_base64: false,
@@ -238,6 +253,8 @@ async fn test_validation_cache_and_depvars() -> Result<()> {
         self_update: false,
         ignore_certs: false,
         user_agent_suffix: None,
+        config: None,
+        no_config: false,
     };
 
     run_async_scan(&global_args, &scan_args, Arc::clone(&datastore), &rules_db).await?;
@@ -0,0 +1,131 @@
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use kingfisher::{
+    cli::commands::rules::RuleSpecifierArgs,
+    rule_loader::RuleLoader,
+    rules::{
+        rule::{Confidence, Rule, Validation},
+        Rules, RulesError,
+    },
+};
+use tempfile::TempDir;
+
+const TEMPLATES: &str = r#"validation_templates:
+  acme_get:
+    params:
+      path:
+      status: [200]
+    validation:
+      type: Http
+      content:
+        request:
+          method: GET
+          url: https://api.acme.example${path}
+          headers:
+            X-Acme-Key: '{{ TOKEN }}'
+          response_matcher:
+            - type: StatusMatch
+              status: ${status}
+"#;
+
+const RULES: &str = r#"rules:
+  - name: Acme Key
+    id: demo.acme.1
+    pattern: '(acme_[a-z0-9]{12})'
+    confidence: medium
+    validation:
+      template: acme_get
+      path: /v1/me
+      status: [200, 204]
+
+  - name: Demo Bearer Token
+    id: demo.bearer.1
+    pattern: '(dbt_[a-z0-9]{12})'
+    confidence: medium
+    validation:
+      template: bearer_get
+      url: https://api.demo.example/v1/user
+"#;
+
+fn load(work: &TempDir) -> Result<Vec<Rule>> {
+    let specs = RuleSpecifierArgs {
+        rules_path: vec![work.path().to_path_buf()],
+        rule: vec!["all".into()],
+        load_builtins: false,
+        rule_tag: vec![],
+        exclude_rule_tag: vec![],
+        rules_url: vec![],
+        rules_public_key: None,
+    };
+    let loaded = RuleLoader::from_rule_specifiers(&specs).load_with_confidence(Confidence::Low)?;
+    Ok(loaded.resolve_enabled_rules()?.into_iter().cloned().collect())
+}
+
+fn request_url(rule: &Rule) -> &str {
+    match &rule.syntax().validation {
+        Some(Validation::Http(http)) => &http.request.url,
+        other => panic!("expected HTTP validation, got {other:?}"),
+    }
+}
+
+#[test]
+fn templates_expand_across_files_and_from_builtins() -> Result<()> {
+    let work = TempDir::new()?;
+    fs::write(work.path().join("templates.yml"), TEMPLATES)?;
+    fs::write(work.path().join("rules.yml"), RULES)?;
+
+    let rules = load(&work)?;
+    let acme = rules.iter().find(|r| r.id() == "demo.acme.1").unwrap();
+    assert_eq!(request_url(acme), "https://api.acme.example/v1/me");
+    let Some(Validation::Http(http)) = &acme.syntax().validation else { unreachable!() };
+    assert_eq!(http.request.headers["X-Acme-Key"], "{{ TOKEN }}");
+
+    let bearer = rules.iter().find(|r| r.id() == "demo.bearer.1").unwrap();
+    assert_eq!(request_url(bearer), "https://api.demo.example/v1/user");
+    Ok(())
+}
+
+#[test]
+fn unknown_templates_fail_to_load() -> Result<()> {
+    let work = TempDir::new()?;
+    fs::write(work.path().join("rules.yml"), RULES)?;
+
+    let err = load(&work).unwrap_err();
+    assert!(format!("{err:#}").contains("unknown validation template `acme_get`"), "{err:#}");
+    Ok(())
+}
+
+#[test]
+fn yaml_errors_keep_their_kind_and_location() -> Result<()> {
+    let load_err = |yaml: &str| {
+        Rules::from_paths_and_contents([(Path::new("rules.yml"), yaml.as_bytes())], Confidence::Low)
+            .err()
+            .unwrap()
+            .downcast::<RulesError>()
+            .unwrap()
+    };
+
+    let err = load_err(
+        "rules:\n  - name: Bad\n    id: demo.bad.1\n    pattern: 'x'\n    min_entropy: lots\n",
+    );
+    assert!(matches!(&err, RulesError::YamlParseError(msg) if msg.contains("line 5")), "{err}");
+
+    let err = load_err(
+        r#"rules:
+  - name: Bad Matcher
+    id: demo.bad.2
+    pattern: 'x'
+    validation:
+      type: Http
+      content:
+        request:
+          method: GET
+          url: https://api.demo.example
+          response_matcher:
+            - status: [200]
+"#,
+    );
+    assert!(matches!(err, RulesError::InvalidResponseMatcherVariant(_, 12, _)), "{err}");
+    Ok(())
+}
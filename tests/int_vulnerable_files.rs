@@ -52,6 +52,10 @@ impl TestContext {
                 rules_path: Vec::new(),
                 rule: vec!["all".into()],
                 load_builtins: true,
+                rule_tag: vec![],
+                exclude_rule_tag: vec![],
+                rules_url: vec![],
+                rules_public_key: None,
             },
             input_specifier_args: InputSpecifierArgs {
                 path_inputs: Vec::new(),
@@ -133,20 +137,31 @@ impl TestContext {
                 no_binary: true,
                 no_extract_archives: false,
                 exclude: Vec::new(), // Exclude patterns
+                stream_large_files: false,
             },
             confidence: ConfidenceLevel::Low,
             no_validate: true,
+            cross_file_dependencies: false,
             rule_stats: false,
             only_valid: false,
             min_entropy: Some(0.0),
             redact: false,
             git_repo_timeout: 1800, // 30 minutes
             output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+            datastore: None,
+            resume: None,
+            blob_cache: None,
             no_dedup: true,
             baseline_file: None,
             manage_baseline: false,
             skip_regex: Vec::new(),
             skip_word: Vec::new(),
+            allowlist: None,
+            safe_list: Vec::new(),
+            disable_safe_rule: Vec::new(),
+            report_suppressed: false,
+            decode: Vec::new(),
+            no_decode: Vec::new(),
             skip_aws_account: Vec::new(),
             skip_aws_account_file: None,
             no_base64: false,
@@ -176,6 +191,10 @@ impl TestContext {
                 rules_path: Vec::new(),
                 rule: vec!["all".into()],
                 load_builtins: true,
+                rule_tag: vec![],
+                exclude_rule_tag: vec![],
+                rules_url: vec![],
+                rules_public_key: None,
             },
             input_specifier_args: InputSpecifierArgs {
                 path_inputs: vec![file_path.to_path_buf()],
@@ -259,20 +278,31 @@ impl TestContext {
                 no_binary: true,
                 no_extract_archives: false,
                 exclude: Vec::new(), // Exclude patterns
+                stream_large_files: false,
             },
             confidence: ConfidenceLevel::Low,
             no_validate: true,
+            cross_file_dependencies: false,
             rule_stats: false,
             only_valid: false,
             min_entropy: Some(0.0),
             redact: false,
             git_repo_timeout: 1800, // 30 minutes
             output_args: OutputArgs { output: None, format: ReportOutputFormat::Pretty },
+            datastore: None,
+            resume: None,
+            blob_cache: None,
             no_dedup: true,
             baseline_file: None,
             manage_baseline: false,
             skip_regex: Vec::new(),
             skip_word: Vec::new(),
+            allowlist: None,
+            safe_list: Vec::new(),
+            disable_safe_rule: Vec::new(),
+            report_suppressed: false,
+            decode: Vec::new(),
+            no_decode: Vec::new(),
             skip_aws_account: Vec::new(),
             skip_aws_account_file: None,
             no_base64: false,
@@ -288,6 +318,8 @@ impl TestContext {
             progress: Mode::Never,
             ignore_certs: false,
             user_agent_suffix: None,
+            config: None,
+            no_config: false,
         };
 
         let datastore = Arc::new(Mutex::new(FindingsStore::new(clone_dir)));